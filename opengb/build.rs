@@ -1,6 +1,11 @@
 use std::process::Command;
 
 fn main() {
+    // The shaders are only needed by the rendering engine
+    if std::env::var("CARGO_FEATURE_ENGINE").is_err() {
+        return;
+    }
+
     build_shader("lightmap_texture.vert");
     build_shader("lightmap_texture.frag");
 }
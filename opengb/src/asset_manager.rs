@@ -3,27 +3,26 @@ use crate::loaders::{
     nav_loader::{nav_load_from_file, NavFile},
     sce_loader::{sce_load_from_file, SceFile},
     scn_loader::scn_load_from_file,
+    LoaderError,
 };
 use crate::utilities::StoreExt2;
 use crate::{
-    cpk::CpkFs,
+    cpk::create_vfs,
     scene::{
         CvdModelEntity, PolModelEntity, RoleAnimation, RoleAnimationRepeatMode, RoleEntity,
         ScnScene,
     },
 };
 use encoding::{types::Encoding, DecoderTrap};
+use image::ImageFormat;
 use ini::Ini;
-use log::debug;
+use log::{debug, error, warn};
 use mini_fs::prelude::*;
-use mini_fs::{LocalFs, MiniFs};
-use radiance::rendering::SimpleMaterialDef;
-use radiance::rendering::{ComponentFactory, MaterialDef};
+use mini_fs::MiniFs;
+use radiance::rendering::LitMaterialDef;
+use radiance::rendering::{ComponentFactory, ImguiTexture, MaterialDef, TextureDef};
 use radiance::scene::CoreEntity;
-use std::{
-    fs,
-    path::{Path, PathBuf},
-};
+use std::path::{Path, PathBuf};
 use std::{io, rc::Rc};
 
 pub struct AssetManager {
@@ -36,18 +35,20 @@ pub struct AssetManager {
 }
 
 impl AssetManager {
-    pub fn new<P: AsRef<Path>>(factory: Rc<dyn ComponentFactory>, path: P) -> Self {
-        let local = LocalFs::new(path.as_ref());
-        let vfs = MiniFs::new(false).mount("/", local);
-        let vfs = Self::mount_cpk_recursive(vfs, path.as_ref(), &PathBuf::from("./"));
-        Self {
+    pub fn new<P: AsRef<Path>>(factory: Rc<dyn ComponentFactory>, path: P) -> io::Result<Self> {
+        let vfs = Self::create_vfs(path)?;
+        Ok(Self {
             factory,
             basedata_path: PathBuf::from("/basedata/basedata"),
             scene_path: PathBuf::from("/scene"),
             music_path: PathBuf::from("/music/music/music"),
             snd_path: PathBuf::from("/snd"),
             vfs,
-        }
+        })
+    }
+
+    pub fn create_vfs<P: AsRef<Path>>(path: P) -> io::Result<MiniFs> {
+        create_vfs(path)
     }
 
     pub fn vfs(&self) -> &MiniFs {
@@ -58,28 +59,32 @@ impl AssetManager {
         self.factory.clone()
     }
 
-    pub fn load_scn(self: &Rc<Self>, cpk_name: &str, scn_name: &str) -> ScnScene {
+    pub fn load_scn(
+        self: &Rc<Self>,
+        cpk_name: &str,
+        scn_name: &str,
+    ) -> Result<ScnScene, LoaderError> {
         let scene_base = self.scene_path.join(cpk_name).join(scn_name);
         let scene_path = scene_base.with_extension("scn");
 
-        let scn_file = scn_load_from_file(&self.vfs, scene_path);
-        let nav_file = self.load_nav(&scn_file.cpk_name, &scn_file.scn_base_name);
+        let scn_file = scn_load_from_file(&self.vfs, scene_path)?;
+        let nav_file = self.load_nav(&scn_file.cpk_name, &scn_file.scn_base_name)?;
 
-        ScnScene::new(&self, cpk_name, scn_name, scn_file, nav_file)
+        Ok(ScnScene::new(&self, cpk_name, scn_name, scn_file, nav_file))
     }
 
-    pub fn load_sce(&self, cpk_name: &str) -> SceFile {
+    pub fn load_sce(&self, cpk_name: &str) -> Result<SceFile, LoaderError> {
         let scene_base = self.scene_path.join(cpk_name).join(cpk_name);
         let sce_path = scene_base.with_extension("sce");
         sce_load_from_file(&self.vfs, sce_path)
     }
 
-    pub fn load_init_sce(&self) -> SceFile {
+    pub fn load_init_sce(&self) -> Result<SceFile, LoaderError> {
         let init_sce = self.basedata_path.join("init.sce");
         sce_load_from_file(&self.vfs, init_sce)
     }
 
-    pub fn load_nav(&self, cpk_name: &str, scn_name: &str) -> NavFile {
+    pub fn load_nav(&self, cpk_name: &str, scn_name: &str) -> Result<NavFile, LoaderError> {
         let nav_path = self
             .scene_path
             .join(cpk_name)
@@ -93,7 +98,7 @@ impl AssetManager {
         RoleEntity::new(self.clone(), role_name, default_action).ok()
     }
 
-    pub fn load_role_anim_config(&self, role_name: &str) -> Ini {
+    pub fn load_role_anim_config(&self, role_name: &str) -> Option<Ini> {
         let path = self
             .basedata_path
             .join("ROLE")
@@ -101,10 +106,15 @@ impl AssetManager {
             .join(role_name)
             .with_extension("ini");
 
-        let mv3_ini = encoding::all::GBK
-            .decode(&self.vfs.read_to_end(&path).unwrap(), DecoderTrap::Ignore)
-            .unwrap();
-        Ini::load_from_str(&mv3_ini).unwrap()
+        let data = self.vfs.read_to_end(&path).ok()?;
+        let mv3_ini = encoding::all::GBK.decode(&data, DecoderTrap::Ignore).ok()?;
+        match Ini::load_from_str(&mv3_ini) {
+            Ok(ini) => Some(ini),
+            Err(e) => {
+                debug!("Cannot parse {:?}: {}", path, e);
+                None
+            }
+        }
     }
 
     pub fn load_role_anim_first<'a>(
@@ -113,16 +123,19 @@ impl AssetManager {
         action_names: &[&'a str],
     ) -> Option<(&'a str, RoleAnimation)> {
         for action_name in action_names {
-            let anim = self.load_role_anim(role_name, action_name);
-            if anim.is_some() {
-                return Some((action_name, anim.unwrap()));
+            if let Ok(anim) = self.load_role_anim(role_name, action_name) {
+                return Some((action_name, anim));
             }
         }
 
         None
     }
 
-    pub fn load_role_anim(&self, role_name: &str, action_name: &str) -> Option<RoleAnimation> {
+    pub fn load_role_anim(
+        &self,
+        role_name: &str,
+        action_name: &str,
+    ) -> Result<RoleAnimation, LoaderError> {
         let path = self
             .basedata_path
             .join("ROLE")
@@ -130,24 +143,32 @@ impl AssetManager {
             .join(action_name)
             .with_extension("mv3");
 
-        mv3_load_from_file(&self.vfs, &path)
-            .map(|f| {
-                RoleAnimation::new(
-                    &self.factory,
-                    &f,
-                    self.load_mv3_material(&f, &path),
-                    RoleAnimationRepeatMode::NoRepeat,
-                )
-            })
-            .ok()
+        mv3_load_from_file(&self.vfs, &path).map(|f| {
+            RoleAnimation::new(
+                &self.factory,
+                &f,
+                self.load_mv3_materials(&f, &path),
+                RoleAnimationRepeatMode::NoRepeat,
+            )
+        })
     }
 
-    pub fn load_mv3_material(&self, mv3file: &Mv3File, mv3path: &Path) -> MaterialDef {
-        let mut texture_path = mv3path.to_owned();
-        texture_path.pop();
-        texture_path.push(std::str::from_utf8(&mv3file.textures[0].names[0]).unwrap());
+    /// Loads a material for every texture slot of the mv3 file. There is
+    /// always at least one material even if the file has no textures.
+    pub fn load_mv3_materials(&self, mv3file: &Mv3File, mv3path: &Path) -> Vec<MaterialDef> {
+        let mut materials: Vec<MaterialDef> = mv3_texture_paths(mv3file, mv3path)
+            .into_iter()
+            .map(|texture_path| {
+                let mut reader = texture_path.and_then(|p| self.vfs.open(p).ok());
+                LitMaterialDef::create(reader.as_mut(), false)
+            })
+            .collect();
+
+        if materials.is_empty() {
+            materials.push(LitMaterialDef::create::<mini_fs::File>(None, false));
+        }
 
-        SimpleMaterialDef::create(self.vfs.open(texture_path).as_mut().ok(), false)
+        materials
     }
 
     pub fn mv3_path(&self, role_name: &str, action_name: &str) -> PathBuf {
@@ -164,22 +185,18 @@ impl AssetManager {
         scn_name: &str,
         pol_name: &str,
         index: u16,
-    ) -> Option<CoreEntity<PolModelEntity>> {
+    ) -> Result<CoreEntity<PolModelEntity>, LoaderError> {
         let path = self
             .scene_path
             .join(cpk_name)
             .join(scn_name)
             .join(pol_name)
             .with_extension("pol");
-        if self.vfs.open(&path).is_ok() {
-            Some(CoreEntity::new(
-                PolModelEntity::new(&self.factory, &self.vfs, &path),
-                format!("OBJECT_{}", index),
-                true
-            ))
-        } else {
-            None
-        }
+        Ok(CoreEntity::new(
+            PolModelEntity::new(&self.factory, &self.vfs, &path)?,
+            format!("OBJECT_{}", index),
+            true,
+        ))
     }
 
     pub fn load_scn_cvd(
@@ -188,24 +205,20 @@ impl AssetManager {
         scn_name: &str,
         pol_name: &str,
         index: u16,
-    ) -> Option<CoreEntity<CvdModelEntity>> {
+    ) -> Result<CoreEntity<CvdModelEntity>, LoaderError> {
         let path = self
             .scene_path
             .join(cpk_name)
             .join(scn_name)
             .join(pol_name)
             .with_extension("cvd");
-        if self.vfs.open(&path).is_ok() {
-            Some(CvdModelEntity::create(
-                self.factory.clone(),
-                &self.vfs,
-                &path,
-                format!("OBJECT_{}", index),
-                true
-            ))
-        } else {
-            None
-        }
+        CvdModelEntity::create(
+            self.factory.clone(),
+            &self.vfs,
+            &path,
+            format!("OBJECT_{}", index),
+            true,
+        )
     }
 
     pub fn load_object_item_pol(
@@ -213,17 +226,13 @@ impl AssetManager {
         obj_name: &str,
         index: u16,
         visible: bool,
-    ) -> Option<CoreEntity<PolModelEntity>> {
+    ) -> Result<CoreEntity<PolModelEntity>, LoaderError> {
         let path = self.get_object_item_path(obj_name);
-        if self.vfs.open(&path).is_ok() {
-            Some(CoreEntity::new(
-                PolModelEntity::new(&self.factory, &self.vfs, &path),
-                format!("OBJECT_{}", index),
-                visible,
-            ))
-        } else {
-            None
-        }
+        Ok(CoreEntity::new(
+            PolModelEntity::new(&self.factory, &self.vfs, &path)?,
+            format!("OBJECT_{}", index),
+            visible,
+        ))
     }
 
     pub fn load_object_item_cvd(
@@ -231,24 +240,46 @@ impl AssetManager {
         obj_name: &str,
         index: u16,
         visible: bool,
-    ) -> Option<CoreEntity<CvdModelEntity>> {
+    ) -> Result<CoreEntity<CvdModelEntity>, LoaderError> {
         let path = self.get_object_item_path(obj_name);
-        if self.vfs.open(&path).is_ok() {
-            Some(CvdModelEntity::create(
-                self.factory.clone(),
-                &self.vfs,
-                &path,
-                format!("OBJECT_{}", index),
-                visible
-            ))
-        } else {
-            None
-        }
+        CvdModelEntity::create(
+            self.factory.clone(),
+            &self.vfs,
+            &path,
+            format!("OBJECT_{}", index),
+            visible,
+        )
     }
 
-    pub fn load_music_data(&self, music_name: &str) -> Vec<u8> {
+    /// Loads `basedata/ui/face/<face_name>.tga`. The folder is a guess that
+    /// hasn't been checked against the game files, so the path is logged when
+    /// the image can't be loaded.
+    pub fn load_face_image(&self, face_name: &str) -> Option<ImguiTexture> {
+        let path = self
+            .basedata_path
+            .join("ui")
+            .join("face")
+            .join(face_name)
+            .with_extension("tga");
+        self.load_imgui_texture(&path)
+    }
+
+    /// Loads `basedata/ui/emote/<emote_id>.tga`. Like the face images, the
+    /// folder is a guess and the path is logged when the image can't be
+    /// loaded.
+    pub fn load_emote_image(&self, emote_id: i32) -> Option<ImguiTexture> {
+        let path = self
+            .basedata_path
+            .join("ui")
+            .join("emote")
+            .join(emote_id.to_string())
+            .with_extension("tga");
+        self.load_imgui_texture(&path)
+    }
+
+    pub fn load_music_data(&self, music_name: &str) -> io::Result<Vec<u8>> {
         let path = self.music_path.join(music_name).with_extension("mp3");
-        self.vfs.read_to_end(path).unwrap()
+        self.vfs.read_to_end(path)
     }
 
     pub fn load_snd_data(&self, snd_name: &str) -> io::Result<Vec<u8>> {
@@ -256,28 +287,33 @@ impl AssetManager {
         self.vfs.read_to_end(path)
     }
 
-    fn mount_cpk_recursive(mut vfs: MiniFs, asset_path: &Path, relative_path: &Path) -> MiniFs {
-        let path = asset_path.join(relative_path);
-        if path.is_dir() {
-            for entry in fs::read_dir(path).unwrap() {
-                let entry = entry.unwrap();
-                let new_path = relative_path.join(entry.file_name());
-                vfs = Self::mount_cpk_recursive(vfs, asset_path, &new_path);
+    fn load_imgui_texture(&self, path: &Path) -> Option<ImguiTexture> {
+        let data = match self.vfs.read_to_end(path) {
+            Ok(data) => data,
+            Err(e) => {
+                warn!("Cannot read {:?}: {}", path, e);
+                return None;
             }
-        } else {
-            if Some(true)
-                == path
-                    .extension()
-                    .and_then(|ext| Some(ext.to_str() == Some("cpk")))
-            {
-                let vfs_path = PathBuf::from("/").join(relative_path.with_extension(""));
+        };
 
-                debug!("Mounting {:?} <- {:?}", &vfs_path, &path);
-                vfs = vfs.mount(vfs_path, CpkFs::new(path).unwrap())
+        match image::load_from_memory(&data)
+            .or_else(|_| image::load_from_memory_with_format(&data, ImageFormat::Tga))
+        {
+            Ok(image) => match self
+                .factory
+                .create_imgui_texture(&TextureDef::ImageTextureDef(Some(image.to_rgba8())))
+            {
+                Ok(texture) => Some(texture),
+                Err(e) => {
+                    error!("Cannot create the texture of {:?}: {}", path, e);
+                    None
+                }
+            },
+            Err(e) => {
+                warn!("Cannot decode {:?}: {}", path, e);
+                None
             }
         }
-
-        vfs
     }
 
     fn get_object_item_path(&self, obj_name: &str) -> PathBuf {
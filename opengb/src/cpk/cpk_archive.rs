@@ -1,10 +1,10 @@
 use crate::utilities::ReadExt;
-use byteorder::{LittleEndian, ReadBytesExt};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
 use mini_fs::UserFile;
 use std::{
     cell::RefCell,
     collections::HashMap,
-    io::{Cursor, Read, Seek},
+    io::{Cursor, Read, Seek, Write},
     rc::Rc,
 };
 use std::{clone::Clone, path::Path};
@@ -159,7 +159,7 @@ impl Seek for CpkFile {
 }
 
 #[derive(Debug)]
-struct CpkHeader {
+pub(super) struct CpkHeader {
     label: u32,
     version: u32,
     table_start: u32,
@@ -176,6 +176,27 @@ struct CpkHeader {
 }
 
 impl CpkHeader {
+    pub const LABEL: u32 = 0x1A545352;
+    pub const SIZE: u32 = 0x80;
+
+    pub fn new(table_num: u32, file_num: u32, package_size: u32) -> CpkHeader {
+        CpkHeader {
+            label: Self::LABEL,
+            version: 1,
+            table_start: Self::SIZE,
+            data_start: Self::SIZE + table_num * CpkTable::SIZE,
+            max_file_num: table_num,
+            file_num,
+            is_formatted: 1,
+            size_of_header: Self::SIZE,
+            valid_table_num: table_num,
+            max_table_num: table_num,
+            fragment_num: 0,
+            package_size,
+            reserved: [0; 20],
+        }
+    }
+
     pub fn read<T: AsRef<[u8]>>(cursor: &mut Cursor<T>) -> IoResult<CpkHeader> {
         let label = cursor.read_u32::<LittleEndian>().unwrap();
         let version = cursor.read_u32::<LittleEndian>().unwrap();
@@ -192,7 +213,7 @@ impl CpkHeader {
         let mut reserved: [u32; 20] = Default::default();
         reserved.copy_from_slice(&cursor.read_dw_vec(20).unwrap());
 
-        if label != 0x1A545352 {
+        if label != Self::LABEL {
             return Err(IoError::from(IoErrorKind::InvalidData));
         }
 
@@ -212,6 +233,26 @@ impl CpkHeader {
             reserved,
         })
     }
+
+    pub fn write<W: Write>(&self, writer: &mut W) -> IoResult<()> {
+        writer.write_u32::<LittleEndian>(self.label)?;
+        writer.write_u32::<LittleEndian>(self.version)?;
+        writer.write_u32::<LittleEndian>(self.table_start)?;
+        writer.write_u32::<LittleEndian>(self.data_start)?;
+        writer.write_u32::<LittleEndian>(self.max_file_num)?;
+        writer.write_u32::<LittleEndian>(self.file_num)?;
+        writer.write_u32::<LittleEndian>(self.is_formatted)?;
+        writer.write_u32::<LittleEndian>(self.size_of_header)?;
+        writer.write_u32::<LittleEndian>(self.valid_table_num)?;
+        writer.write_u32::<LittleEndian>(self.max_table_num)?;
+        writer.write_u32::<LittleEndian>(self.fragment_num)?;
+        writer.write_u32::<LittleEndian>(self.package_size)?;
+        for dw in &self.reserved {
+            writer.write_u32::<LittleEndian>(*dw)?;
+        }
+
+        Ok(())
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -226,7 +267,7 @@ pub struct CpkTable {
 }
 
 #[derive(Debug)]
-enum CpkTableFlag {
+pub(super) enum CpkTableFlag {
     None = 0x0,
     IsFile = 0x1,
     IsDir = 0x2,
@@ -234,9 +275,33 @@ enum CpkTableFlag {
     Unknown2 = 0x8,
     IsDeleted = 0x10,
     IsNotCompressed = 0x10000,
+    // The high word is the codec id: 1 for stored data, 2 for LZO
+    IsCompressed = 0x20000,
 }
 
 impl CpkTable {
+    pub const SIZE: u32 = 28;
+
+    pub(super) fn new(
+        crc: u32,
+        flag: u32,
+        father_crc: u32,
+        start_pos: u32,
+        packed_size: u32,
+        origin_size: u32,
+        extra_info_size: u32,
+    ) -> CpkTable {
+        CpkTable {
+            crc,
+            flag,
+            father_crc,
+            start_pos,
+            packed_size,
+            origin_size,
+            extra_info_size,
+        }
+    }
+
     pub fn read<T: AsRef<[u8]>>(cursor: &mut Cursor<T>) -> IoResult<CpkTable> {
         let crc = cursor.read_u32::<LittleEndian>().unwrap();
         let flag = cursor.read_u32::<LittleEndian>().unwrap();
@@ -257,6 +322,26 @@ impl CpkTable {
         })
     }
 
+    pub fn write<W: Write>(&self, writer: &mut W) -> IoResult<()> {
+        writer.write_u32::<LittleEndian>(self.crc)?;
+        writer.write_u32::<LittleEndian>(self.flag)?;
+        writer.write_u32::<LittleEndian>(self.father_crc)?;
+        writer.write_u32::<LittleEndian>(self.start_pos)?;
+        writer.write_u32::<LittleEndian>(self.packed_size)?;
+        writer.write_u32::<LittleEndian>(self.origin_size)?;
+        writer.write_u32::<LittleEndian>(self.extra_info_size)?;
+
+        Ok(())
+    }
+
+    pub fn crc(&self) -> u32 {
+        self.crc
+    }
+
+    pub fn father_crc(&self) -> u32 {
+        self.father_crc
+    }
+
     pub fn is_compressed(&self) -> bool {
         (self.flag & CpkTableFlag::IsNotCompressed as u32) == 0
     }
@@ -294,6 +379,10 @@ impl CpkEntry {
         (self.raw_entry.flag & CpkTableFlag::IsDir as u32) != 0
     }
 
+    pub fn is_deleted(&self) -> bool {
+        (self.raw_entry.flag & CpkTableFlag::IsDeleted as u32) != 0
+    }
+
     pub fn ls<P: AsRef<Path>>(&self, path: P) -> std::io::Result<Vec<Rc<RefCell<CpkEntry>>>> {
         let mut components = path.as_ref().components();
         let first = components.next();
@@ -0,0 +1,353 @@
+use super::cpk_archive::{CpkHeader, CpkTable, CpkTableFlag};
+use super::{crc_checksum, CpkArchive, CpkEntry};
+use crate::utilities::encode_gbk;
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs::File,
+    io::{BufWriter, Read, Write},
+    path::Path,
+};
+
+type IoResult<T> = std::io::Result<T>;
+type IoError = std::io::Error;
+type IoErrorKind = std::io::ErrorKind;
+
+enum CpkWriterContent {
+    Dir,
+    File(Vec<u8>),
+}
+
+struct CpkWriterEntry {
+    path: String,
+    content: CpkWriterContent,
+}
+
+pub struct CpkWriter {
+    entries: BTreeMap<String, CpkWriterEntry>,
+    compress: bool,
+}
+
+impl Default for CpkWriter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CpkWriter {
+    pub fn new() -> CpkWriter {
+        CpkWriter {
+            entries: BTreeMap::new(),
+            compress: true,
+        }
+    }
+
+    pub fn from_directory<P: AsRef<Path>>(dir: P) -> IoResult<CpkWriter> {
+        let mut writer = Self::new();
+        writer.add_directory("", dir.as_ref())?;
+        Ok(writer)
+    }
+
+    pub fn from_archive<T: AsRef<[u8]>>(archive: &mut CpkArchive<T>) -> IoResult<CpkWriter> {
+        let mut writer = Self::new();
+        let root = archive.build_directory();
+        writer.add_archive_entries(archive, "", &root)?;
+        Ok(writer)
+    }
+
+    pub fn set_compress(&mut self, compress: bool) {
+        self.compress = compress;
+    }
+
+    pub fn add_dir(&mut self, path: &str) {
+        let path = Self::normalize_path(path);
+        if path.is_empty() {
+            return;
+        }
+
+        if let Some(parent) = Self::parent_path(&path) {
+            self.add_dir(parent);
+        }
+
+        self.entries
+            .entry(path.to_lowercase())
+            .or_insert(CpkWriterEntry {
+                path: path.to_string(),
+                content: CpkWriterContent::Dir,
+            });
+    }
+
+    pub fn add_file(&mut self, path: &str, data: Vec<u8>) {
+        let path = Self::normalize_path(path);
+        if let Some(parent) = Self::parent_path(&path) {
+            self.add_dir(parent);
+        }
+
+        self.entries.insert(
+            path.to_lowercase(),
+            CpkWriterEntry {
+                path: path.to_string(),
+                content: CpkWriterContent::File(data),
+            },
+        );
+    }
+
+    pub fn remove(&mut self, path: &str) {
+        let key = Self::normalize_path(path).to_lowercase();
+        let prefix = format!("{}\\", key);
+        self.entries
+            .retain(|k, _| k != &key && !k.starts_with(&prefix));
+    }
+
+    pub fn write_to_file<P: AsRef<Path>>(&self, path: P) -> IoResult<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write(&mut writer)?;
+        writer.flush()
+    }
+
+    pub fn write<W: Write>(&self, writer: &mut W) -> IoResult<()> {
+        let mut lzo = minilzo_rs::LZO::init().map_err(|e| IoError::new(IoErrorKind::Other, e))?;
+
+        let mut crcs = HashMap::new();
+        for key in self.entries.keys() {
+            let crc = crc_checksum(&encode_gbk(key)?);
+            if let Some(other) = crcs.insert(crc, key) {
+                return Err(IoError::new(
+                    IoErrorKind::InvalidInput,
+                    format!("Crc collision between {} and {}", other, key),
+                ));
+            }
+        }
+
+        let table_num = self.entries.len() as u32;
+        let mut offset = CpkHeader::SIZE + table_num * CpkTable::SIZE;
+        let mut tables = vec![];
+        let mut blocks = vec![];
+        let mut file_num = 0;
+        for (key, entry) in &self.entries {
+            let crc = crc_checksum(&encode_gbk(key)?);
+            let father_crc = match Self::parent_path(key) {
+                Some(parent) => crc_checksum(&encode_gbk(parent)?),
+                None => 0,
+            };
+
+            let name = Self::file_name(&entry.path);
+            let mut extra_info = encode_gbk(name)?;
+            extra_info.push(0);
+
+            let (flag, payload, origin_size) = match &entry.content {
+                CpkWriterContent::Dir => (
+                    CpkTableFlag::IsFile as u32
+                        | CpkTableFlag::IsDir as u32
+                        | CpkTableFlag::IsNotCompressed as u32,
+                    vec![],
+                    0,
+                ),
+                CpkWriterContent::File(data) => {
+                    file_num += 1;
+                    let compressed = if self.compress && !data.is_empty() {
+                        Some(
+                            lzo.compress(data)
+                                .map_err(|e| IoError::new(IoErrorKind::Other, e))?,
+                        )
+                    } else {
+                        None
+                    };
+
+                    match compressed {
+                        Some(c) if c.len() < data.len() => (
+                            CpkTableFlag::IsFile as u32 | CpkTableFlag::IsCompressed as u32,
+                            c,
+                            data.len(),
+                        ),
+                        _ => (
+                            CpkTableFlag::IsFile as u32 | CpkTableFlag::IsNotCompressed as u32,
+                            data.clone(),
+                            data.len(),
+                        ),
+                    }
+                }
+            };
+
+            tables.push((
+                crc,
+                CpkTable::new(
+                    crc,
+                    flag,
+                    father_crc,
+                    offset,
+                    payload.len() as u32,
+                    origin_size as u32,
+                    extra_info.len() as u32,
+                ),
+            ));
+
+            offset += (payload.len() + extra_info.len()) as u32;
+            blocks.push((payload, extra_info));
+        }
+
+        // The game looks up entries with a binary search on the crc
+        tables.sort_by_key(|t| t.0);
+
+        CpkHeader::new(table_num, file_num, offset).write(writer)?;
+        for (_, table) in &tables {
+            table.write(writer)?;
+        }
+
+        for (payload, extra_info) in &blocks {
+            writer.write_all(payload)?;
+            writer.write_all(extra_info)?;
+        }
+
+        Ok(())
+    }
+
+    fn add_directory(&mut self, relative_path: &str, dir: &Path) -> IoResult<()> {
+        let mut children = vec![];
+        for entry in std::fs::read_dir(dir)? {
+            children.push(entry?.path());
+        }
+
+        children.sort();
+        for child in children {
+            let name = child
+                .file_name()
+                .and_then(|n| n.to_str())
+                .ok_or(IoError::from(IoErrorKind::InvalidInput))?;
+            let path = Self::join_path(relative_path, name);
+            if child.is_dir() {
+                self.add_dir(&path);
+                self.add_directory(&path, &child)?;
+            } else {
+                self.add_file(&path, std::fs::read(&child)?);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn add_archive_entries<T: AsRef<[u8]>>(
+        &mut self,
+        archive: &mut CpkArchive<T>,
+        relative_path: &str,
+        entry: &CpkEntry,
+    ) -> IoResult<()> {
+        for child in entry.children() {
+            let child = child.borrow();
+            if child.name().is_empty() || child.is_deleted() {
+                continue;
+            }
+
+            let path = Self::join_path(relative_path, child.name());
+            if child.is_dir() {
+                self.add_dir(&path);
+                self.add_archive_entries(archive, &path, &child)?;
+            } else {
+                let mut file = archive.open(&encode_gbk(&path.to_lowercase())?)?;
+                let mut data = vec![];
+                file.read_to_end(&mut data)?;
+                self.add_file(&path, data);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn normalize_path(path: &str) -> String {
+        path.replace('/', "\\").trim_matches('\\').to_string()
+    }
+
+    fn join_path(parent: &str, name: &str) -> String {
+        if parent.is_empty() {
+            name.to_string()
+        } else {
+            format!("{}\\{}", parent, name)
+        }
+    }
+
+    fn parent_path(path: &str) -> Option<&str> {
+        path.rfind('\\').map(|pos| &path[..pos])
+    }
+
+    fn file_name(path: &str) -> &str {
+        path.rfind('\\').map(|pos| &path[pos + 1..]).unwrap_or(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{cell::RefCell, io::Cursor, rc::Rc};
+
+    fn child(entry: &CpkEntry, name: &str) -> Rc<RefCell<CpkEntry>> {
+        entry
+            .children()
+            .iter()
+            .find(|c| c.borrow().name() == name)
+            .unwrap_or_else(|| panic!("{} is not under {}", name, entry.name()))
+            .clone()
+    }
+
+    fn read_file(archive: &mut CpkArchive<Vec<u8>>, path: &str) -> Vec<u8> {
+        let mut data = vec![];
+        archive
+            .open_str(path)
+            .unwrap()
+            .read_to_end(&mut data)
+            .unwrap();
+        data
+    }
+
+    #[test]
+    fn written_archive_loads_back() {
+        let stored = b"stored".to_vec();
+        let compressed = vec![0x42; 4096];
+        let mut writer = CpkWriter::new();
+        writer.add_file("scene/q01/stored.txt", stored.clone());
+        writer.add_file("scene/q01/mesh/compressed.bin", compressed.clone());
+        writer.add_file("empty.txt", vec![]);
+
+        let mut data = vec![];
+        writer.write(&mut data).unwrap();
+        let mut archive = CpkArchive::load(Cursor::new(data)).unwrap();
+
+        assert_eq!(read_file(&mut archive, "scene\\q01\\stored.txt"), stored);
+        assert_eq!(
+            read_file(&mut archive, "scene\\q01\\mesh\\compressed.bin"),
+            compressed
+        );
+        assert!(read_file(&mut archive, "empty.txt").is_empty());
+
+        // Only the long run of bytes is worth compressing
+        let compressed_crc = crc_checksum(b"scene\\q01\\mesh\\compressed.bin");
+        for table in &archive.entries {
+            assert_eq!(table.is_compressed(), table.crc() == compressed_crc);
+        }
+
+        let root = archive.build_directory();
+        assert_eq!(root.children().len(), 2);
+        assert!(!child(&root, "empty.txt").borrow().is_dir());
+
+        let scene = child(&root, "scene");
+        assert!(scene.borrow().is_dir());
+        let q01 = child(&scene.borrow(), "q01");
+        assert_eq!(q01.borrow().children().len(), 2);
+        assert!(!child(&q01.borrow(), "stored.txt").borrow().is_dir());
+
+        let mesh = child(&q01.borrow(), "mesh");
+        assert!(mesh.borrow().is_dir());
+        assert_eq!(mesh.borrow().children().len(), 1);
+        assert!(!child(&mesh.borrow(), "compressed.bin").borrow().is_dir());
+
+        let father_crcs: HashMap<u32, u32> = archive
+            .entries
+            .iter()
+            .map(|t| (t.crc(), t.father_crc()))
+            .collect();
+        assert_eq!(father_crcs[&crc_checksum(b"scene")], 0);
+        assert_eq!(father_crcs[&crc_checksum(b"empty.txt")], 0);
+        assert_eq!(
+            father_crcs[&compressed_crc],
+            crc_checksum(b"scene\\q01\\mesh")
+        );
+    }
+}
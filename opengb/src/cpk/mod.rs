@@ -1,7 +1,11 @@
 pub use cpk_archive::{CpkArchive, CpkEntry};
 pub use cpk_fs::CpkFs;
+pub use cpk_writer::CpkWriter;
 pub use crc::crc_checksum;
+pub use vfs::create_vfs;
 
 mod cpk_archive;
 mod cpk_fs;
+mod cpk_writer;
 mod crc;
+mod vfs;
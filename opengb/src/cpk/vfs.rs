@@ -0,0 +1,48 @@
+use super::CpkFs;
+use log::{debug, warn};
+use mini_fs::{LocalFs, MiniFs};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+/// Creates a vfs of the game folder, with every cpk archive under it mounted
+/// as a folder of the same name. The archives that cannot be opened are
+/// skipped.
+pub fn create_vfs<P: AsRef<Path>>(path: P) -> io::Result<MiniFs> {
+    if !fs::metadata(path.as_ref())?.is_dir() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{:?} is not a folder", path.as_ref()),
+        ));
+    }
+
+    let local = LocalFs::new(path.as_ref());
+    let vfs = MiniFs::new(false).mount("/", local);
+    mount_cpk_recursive(vfs, path.as_ref(), &PathBuf::from("./"))
+}
+
+fn mount_cpk_recursive(
+    mut vfs: MiniFs,
+    asset_path: &Path,
+    relative_path: &Path,
+) -> io::Result<MiniFs> {
+    let path = asset_path.join(relative_path);
+    if path.is_dir() {
+        for entry in fs::read_dir(path)? {
+            let new_path = relative_path.join(entry?.file_name());
+            vfs = mount_cpk_recursive(vfs, asset_path, &new_path)?;
+        }
+    } else if path.extension().and_then(|ext| ext.to_str()) == Some("cpk") {
+        let vfs_path = PathBuf::from("/").join(relative_path.with_extension(""));
+        match CpkFs::new(&path) {
+            Ok(cpk_fs) => {
+                debug!("Mounting {:?} <- {:?}", &vfs_path, &path);
+                vfs = vfs.mount(vfs_path, cpk_fs);
+            }
+            Err(e) => warn!("Cannot mount {:?}: {}", &path, e),
+        }
+    }
+
+    Ok(vfs)
+}
@@ -1,20 +1,32 @@
 use std::{cell::RefCell, rc::Rc};
 
-use crate::asset_manager::AssetManager;
+use crate::{asset_manager::AssetManager, loaders::LoaderError};
 
-use super::{global_state::GlobalState, sce_vm::SceVm, PersistentState, SceneManagerExtensions};
+use super::{
+    camera_controller::view_direction, global_state::GlobalState,
+    object_state::restore_object_states, party::activate_party_roles, sce_vm::SceVm,
+    InventoryScreen, PersistentState, SceneManagerExtensions, FADE_DURATION,
+};
 use log::debug;
 use radiance::{
     audio::AudioEngine,
     input::{InputEngine, Key},
-    math::{Mat44, Vec3},
-    scene::{CoreScene, Director, Entity, SceneManager},
+    math::{Mat44, Transform, Vec3},
+    scene::{Camera, CoreScene, Director, Entity, SceneManager},
 };
 
+// How long the follow camera takes to take the camera back from the scripts
+const CAMERA_HANDOFF_DURATION: f32 = 0.8;
+
+// How fast the role catches up with the height of the ground, per second
+const VERTICAL_SMOOTHING_SPEED: f32 = 15.;
+
 pub struct AdventureDirector {
     input_engine: Rc<RefCell<dyn InputEngine>>,
     sce_vm: SceVm,
     camera_rotation: f32,
+    camera_handoff: f32,
+    inventory_screen: InventoryScreen,
 }
 
 impl AdventureDirector {
@@ -23,23 +35,25 @@ impl AdventureDirector {
         asset_mgr: Rc<AssetManager>,
         audio_engine: Rc<dyn AudioEngine>,
         input_engine: Rc<RefCell<dyn InputEngine>>,
-    ) -> Self {
+    ) -> Result<Self, LoaderError> {
         let p_state = Rc::new(RefCell::new(PersistentState::new(app_name.to_string())));
-        let global_state = GlobalState::new(asset_mgr.clone(), &audio_engine, p_state);
+        let global_state = GlobalState::new(asset_mgr.clone(), &audio_engine, p_state)?;
         let mut sce_vm = SceVm::new(
             audio_engine.clone(),
             input_engine.clone(),
-            asset_mgr.load_init_sce(),
+            asset_mgr.load_init_sce()?,
             asset_mgr.clone(),
             global_state,
         );
         sce_vm.call_proc(51);
 
-        Self {
+        Ok(Self {
             sce_vm,
             input_engine,
             camera_rotation: 0.,
-        }
+            camera_handoff: 0.,
+            inventory_screen: InventoryScreen::new(),
+        })
     }
 
     pub fn load(
@@ -58,32 +72,55 @@ impl AdventureDirector {
             return None;
         }
 
-        let scene = Box::new(CoreScene::new(asset_mgr.load_scn(
-            scene_name.as_ref().unwrap(),
-            sub_scene_name.as_ref().unwrap(),
-        )));
-        scene_manager.push_scene(scene);
+        let scene_name = scene_name.as_ref().unwrap();
+        let sub_scene_name = sub_scene_name.as_ref().unwrap();
+        let (mut scn, sce) = match asset_mgr
+            .load_scn(scene_name, sub_scene_name)
+            .and_then(|scn| Ok((scn, asset_mgr.load_sce(scene_name)?)))
+        {
+            Ok(loaded) => loaded,
+            Err(e) => {
+                log::error!("Cannot load save {}: {}", slot, e);
+                return None;
+            }
+        };
+
+        let party = p_state.party().clone();
+        let position = p_state.position();
+        let leader = p_state.role_controlled();
+        scn.set_party_members(party.members());
+        scene_manager.push_scene(Box::new(CoreScene::new(scn)));
 
-        let mut global_state = GlobalState::new(
+        let mut global_state = match GlobalState::new(
             asset_mgr.clone(),
             &audio_engine,
             Rc::new(RefCell::new(p_state)),
-        );
+        ) {
+            Ok(global_state) => global_state,
+            Err(e) => {
+                log::error!("Cannot load save {}: {}", slot, e);
+                return None;
+            }
+        };
 
-        // The role id should be saved in persistant state
-        let role = scene_manager
-            .core_scene_mut_or_fail()
-            .get_role_entity_mut(0);
-        role.set_active(true);
-        role.transform_mut()
-            .set_position(&global_state.persistent_state_mut().position());
+        let scene = scene_manager.core_scene_mut_or_fail();
+        activate_party_roles(scene, &party, leader);
+        restore_object_states(scene, &global_state.persistent_state());
+        for id in std::iter::once(leader).chain(party.followers(leader)) {
+            if scene.has_role_entity(id) {
+                scene
+                    .get_role_entity_mut(id)
+                    .transform_mut()
+                    .set_position(&position);
+            }
+        }
 
         global_state.play_default_bgm();
 
         let sce_vm = SceVm::new(
             audio_engine.clone(),
             input_engine.clone(),
-            asset_mgr.load_sce(scene_name.as_ref().unwrap()),
+            sce,
             asset_mgr.clone(),
             global_state,
         );
@@ -92,9 +129,60 @@ impl AdventureDirector {
             sce_vm,
             input_engine,
             camera_rotation: 0.,
+            camera_handoff: 0.,
+            inventory_screen: InventoryScreen::new(),
         })
     }
 
+    fn update_followers(&self, scene_manager: &mut dyn SceneManager, delta_sec: f32) {
+        const FOLLOW_DISTANCE: f32 = 60.;
+        const TELEPORT_DISTANCE: f32 = 500.;
+        const SPEED: f32 = 175.;
+
+        let global_state = self.sce_vm.global_state();
+        let leader = global_state.role_controlled();
+        let party = global_state.persistent_state().party().clone();
+        if !party.followers_visible() {
+            return;
+        }
+
+        // Every follower walks behind the role ahead of it in the team
+        let scene = scene_manager.core_scene_mut_or_fail();
+        let mut ahead = scene.get_role_entity(leader).transform().position();
+        for id in party.followers(leader) {
+            if !scene.has_role_entity(id) {
+                continue;
+            }
+
+            let position = scene.get_role_entity(id).transform().position();
+            let offset = Vec3::new(ahead.x - position.x, 0., ahead.z - position.z);
+            let distance = offset.norm();
+            if distance > TELEPORT_DISTANCE {
+                // Too far behind, e.g. when the leader has just been placed
+                scene
+                    .get_role_entity_mut(id)
+                    .transform_mut()
+                    .set_position(&ahead);
+            } else if distance > FOLLOW_DISTANCE {
+                let step = (SPEED * delta_sec).min(distance - FOLLOW_DISTANCE);
+                let mut new_position =
+                    Vec3::add(&position, &Vec3::dot(step, &Vec3::normalized(&offset)));
+                new_position.y = scene.get_height(&new_position);
+
+                let entity = scene.get_role_entity_mut(id);
+                entity.run();
+                entity
+                    .transform_mut()
+                    .look_at(&Vec3::new(ahead.x, new_position.y, ahead.z))
+                    .set_position(&new_position);
+                ahead = new_position;
+            } else {
+                scene.get_role_entity_mut(id).idle();
+                ahead = position;
+            }
+        }
+    }
+
     fn test_save(&self) {
         let input = self.input_engine.borrow_mut();
         let save_slot = if input.get_key_state(Key::Num1).pressed() {
@@ -114,29 +202,53 @@ impl AdventureDirector {
             .persistent_state()
             .save(save_slot);
     }
-}
 
-impl Director for AdventureDirector {
-    fn activate(&mut self, scene_manager: &mut dyn SceneManager) {
-        debug!("AdventureDirector activated");
-    }
-
-    fn update(
+    fn update_adventure(
         &mut self,
         scene_manager: &mut dyn SceneManager,
         ui: &mut imgui::Ui,
         delta_sec: f32,
     ) -> Option<Rc<RefCell<dyn Director>>> {
         self.sce_vm.update(scene_manager, ui, delta_sec);
+        if let Some(scene) = scene_manager.scene_mut() {
+            self.sce_vm
+                .global_state_mut()
+                .camera_controller_mut()
+                .update(scene.camera_mut(), delta_sec);
+        }
+
+        self.sce_vm
+            .global_state_mut()
+            .caption_layer_mut()
+            .update(ui, delta_sec);
+
         if !self.sce_vm.global_state().input_enabled() {
+            self.camera_handoff = CAMERA_HANDOFF_DURATION;
             return None;
         }
 
+        // The screen is left covered after the scripts load a scene
+        if scene_manager.screen_fade().is_covered() {
+            scene_manager.screen_fade_mut().fade_in(FADE_DURATION);
+        }
+
         if scene_manager.scene_mut().is_none() {
             return None;
         }
 
         self.test_save();
+        if self.input_engine.borrow().get_key_state(Key::I).pressed() {
+            self.inventory_screen.toggle();
+        }
+
+        self.inventory_screen.update(ui, self.sce_vm.global_state());
+        if self.inventory_screen.visible() {
+            scene_manager
+                .get_resolved_role_entity_mut(self.sce_vm.state(), -1)
+                .idle();
+            return None;
+        }
+
         let input = self.input_engine.borrow_mut();
         let mut direction = Vec3::new(0., 0., 0.);
 
@@ -191,23 +303,31 @@ impl Director for AdventureDirector {
             .transform()
             .position();
 
-        scene_manager
-            .scene_mut()
-            .unwrap()
-            .camera_mut()
-            .transform_mut()
-            .set_position(&Vec3::new(400., 400., 400.))
-            .rotate_axis_angle(&Vec3::UP, self.camera_rotation)
-            .translate(&position)
-            .look_at(&position);
+        if self.sce_vm.global_state().camera_controller().is_scripted() {
+            self.camera_handoff = CAMERA_HANDOFF_DURATION;
+        } else {
+            follow_role(
+                scene_manager.scene_mut().unwrap().camera_mut(),
+                &position,
+                self.camera_rotation,
+                &mut self.camera_handoff,
+                delta_sec,
+            );
+        }
 
         let scene = scene_manager.core_scene_mut_or_fail();
         let speed = 175.;
         let mut target_position = Vec3::add(&position, &Vec3::dot(speed * delta_sec, &direction));
-        let target_nav_coord = scene.scene_coord_to_nav_coord(&target_position);
-        let height = scene.get_height(target_nav_coord);
-        target_position.y = height;
-        let distance_to_border = scene.get_distance_to_border_by_scene_coord(&target_position);
+        let can_move = scene.can_move_to(&position, &target_position);
+        if !can_move {
+            target_position = position;
+        }
+
+        // The role follows the ground smoothly instead of snapping to it, so
+        // that walking on stairs and uneven ground looks continuous
+        let height = scene.get_height(&target_position);
+        let smoothing = (VERTICAL_SMOOTHING_SPEED * delta_sec).min(1.);
+        target_position.y = position.y + (height - position.y) * smoothing;
 
         if let Some(proc_id) = scene.test_nav_trigger(&target_position) {
             debug!("New proc triggerd by nav: {}", proc_id);
@@ -226,22 +346,95 @@ impl Director for AdventureDirector {
         }
 
         let entity = scene_manager.get_resolved_role_entity_mut(self.sce_vm.state(), -1);
-        if direction.norm() > 0.5 && distance_to_border > std::f32::EPSILON {
+        if direction.norm() > 0.5 && can_move {
             entity.run();
             let look_at = Vec3::new(target_position.x, position.y, target_position.z);
             entity
                 .transform_mut()
                 .look_at(&look_at)
                 .set_position(&target_position);
-
-            self.sce_vm
-                .global_state_mut()
-                .persistent_state_mut()
-                .set_position(target_position);
         } else {
             entity.idle();
+            entity.transform_mut().set_position(&target_position);
         }
 
+        self.sce_vm
+            .global_state_mut()
+            .persistent_state_mut()
+            .set_position(target_position);
+
+        self.update_followers(scene_manager, delta_sec);
+
         None
     }
 }
+
+impl Director for AdventureDirector {
+    fn activate(&mut self, scene_manager: &mut dyn SceneManager) {
+        debug!("AdventureDirector activated");
+    }
+
+    fn update(
+        &mut self,
+        scene_manager: &mut dyn SceneManager,
+        ui: &mut imgui::Ui,
+        delta_sec: f32,
+    ) -> Option<Rc<RefCell<dyn Director>>> {
+        // The shake is layered on top of the camera that the director or the
+        // scripts set, so it's taken off before they move the camera.
+        if let Some(scene) = scene_manager.scene_mut() {
+            self.sce_vm
+                .global_state_mut()
+                .camera_controller_mut()
+                .remove_shake(scene.camera_mut());
+        }
+
+        let director = self.update_adventure(scene_manager, ui, delta_sec);
+        if let Some(scene) = scene_manager.scene_mut() {
+            self.sce_vm
+                .global_state_mut()
+                .camera_controller_mut()
+                .apply_shake(scene.camera_mut(), delta_sec);
+        }
+
+        director
+    }
+}
+
+// Places the camera behind the role. Right after the scripts give the camera
+// back, it glides from where the scripts left it instead of jumping.
+fn follow_role(
+    camera: &mut Camera,
+    role_position: &Vec3,
+    rotation: f32,
+    handoff: &mut f32,
+    delta_sec: f32,
+) {
+    let mut follow = Transform::new();
+    follow
+        .set_position(&Vec3::new(400., 400., 400.))
+        .rotate_axis_angle(&Vec3::UP, rotation)
+        .translate(role_position);
+
+    let mut position = follow.position();
+    let mut target = *role_position;
+    if *handoff > 0. {
+        let t = (delta_sec / *handoff).min(1.);
+        let current_position = camera.transform().position();
+        let current_target = Vec3::add(
+            &current_position,
+            &Vec3::dot(
+                Vec3::sub(role_position, &current_position).norm(),
+                &view_direction(camera),
+            ),
+        );
+        position = Vec3::lerp(&current_position, &position, t);
+        target = Vec3::lerp(&current_target, &target, t);
+        *handoff -= delta_sec;
+    }
+
+    camera
+        .transform_mut()
+        .set_position(&position)
+        .look_at(&target);
+}
@@ -0,0 +1,242 @@
+use super::{easing::ease, SceneManagerExtensions};
+use radiance::{
+    math::Vec3,
+    scene::{Camera, Scene, SceneManager},
+};
+use rand::Rng;
+
+// How far in front of the camera the point that it orbits around and looks
+// at is, as the scripts don't tell it
+const TARGET_DISTANCE: f32 = 500.;
+
+#[derive(Copy, Clone, Debug)]
+pub enum CameraMove {
+    /// Moves the camera towards the point it looks at.
+    Push { distance: f32 },
+
+    /// Orbits the camera around the point it looks at, in degrees.
+    Rotate { yaw: f32, pitch: f32 },
+
+    /// Turns the camera in place, in degrees.
+    Wag { yaw: f32, pitch: f32 },
+
+    /// Moves the camera to a position without turning it.
+    MoveTo { position: Vec3 },
+}
+
+struct ActiveMove {
+    camera_move: CameraMove,
+    duration: f32,
+    elapsed: f32,
+    start_position: Vec3,
+    start_target: Vec3,
+}
+
+struct Shake {
+    amplitude: f32,
+    duration: f32,
+    elapsed: f32,
+}
+
+/// Runs the timed camera moves of the scripts. While a move runs, or while
+/// the scripts have freed the camera, the director doesn't move the camera.
+pub struct CameraController {
+    active_move: Option<ActiveMove>,
+    free: bool,
+    shake: Option<Shake>,
+    shake_offset: Vec3,
+}
+
+impl CameraController {
+    pub fn new() -> Self {
+        Self {
+            active_move: None,
+            free: false,
+            shake: None,
+            shake_offset: Vec3::new(0., 0., 0.),
+        }
+    }
+
+    /// Starts a move that takes `duration_sec` seconds. The move that is
+    /// running is finished first.
+    pub fn start_move(&mut self, camera: &mut Camera, camera_move: CameraMove, duration_sec: f32) {
+        self.finish_move(camera);
+
+        let start_position = camera.transform().position();
+        let start_target = Vec3::add(
+            &start_position,
+            &Vec3::dot(TARGET_DISTANCE, &view_direction(camera)),
+        );
+        self.active_move = Some(ActiveMove {
+            camera_move,
+            duration: duration_sec.max(0.),
+            elapsed: 0.,
+            start_position,
+            start_target,
+        });
+
+        self.update(camera, 0.);
+    }
+
+    /// Starts a move of the camera of the scene that the scripts run in.
+    pub fn start_scene_move(
+        &mut self,
+        scene_manager: &mut dyn SceneManager,
+        camera_move: CameraMove,
+        duration_sec: f32,
+    ) {
+        let camera = scene_manager.core_scene_mut_or_fail().camera_mut();
+        self.start_move(camera, camera_move, duration_sec);
+    }
+
+    pub fn is_moving(&self) -> bool {
+        self.active_move.is_some()
+    }
+
+    /// Whether a script that started a move can go on. The scripts that sync
+    /// with the move wait until it ends.
+    pub fn can_continue(&self, sync: bool) -> bool {
+        !sync || !self.is_moving()
+    }
+
+    /// Whether the scripts control the camera.
+    pub fn is_scripted(&self) -> bool {
+        self.free || self.is_moving()
+    }
+
+    pub fn set_free(&mut self, free: bool) {
+        self.free = free;
+    }
+
+    pub fn update(&mut self, camera: &mut Camera, delta_sec: f32) {
+        let active_move = match self.active_move.as_mut() {
+            Some(active_move) => active_move,
+            None => return,
+        };
+
+        active_move.elapsed = (active_move.elapsed + delta_sec).min(active_move.duration);
+        let t = if active_move.duration > 0. {
+            active_move.elapsed / active_move.duration
+        } else {
+            1.
+        };
+
+        apply_move(active_move, camera, ease(t));
+        if t >= 1. {
+            self.active_move = None;
+        }
+    }
+
+    /// Shakes the camera by up to `amplitude` for `duration_sec` seconds. The
+    /// shake gets weaker until it stops.
+    pub fn shake(&mut self, amplitude: f32, duration_sec: f32) {
+        self.shake = Some(Shake {
+            amplitude,
+            duration: duration_sec.max(0.),
+            elapsed: 0.,
+        });
+    }
+
+    pub fn is_shaking(&self) -> bool {
+        self.shake.is_some()
+    }
+
+    /// Moves the camera back to where it was before the last shake offset was
+    /// applied.
+    pub fn remove_shake(&mut self, camera: &mut Camera) {
+        let mut offset = self.shake_offset;
+        camera.transform_mut().translate(offset.neg());
+        self.shake_offset = Vec3::new(0., 0., 0.);
+    }
+
+    /// Offsets the camera by a new random amount of the running shake.
+    pub fn apply_shake(&mut self, camera: &mut Camera, delta_sec: f32) {
+        let shake = match self.shake.as_mut() {
+            Some(shake) => shake,
+            None => return,
+        };
+
+        shake.elapsed += delta_sec;
+        if shake.elapsed >= shake.duration {
+            self.shake = None;
+            return;
+        }
+
+        let decay = 1. - shake.elapsed / shake.duration;
+        let amplitude = shake.amplitude.abs() * decay * decay;
+        let mut rng = rand::thread_rng();
+        self.shake_offset = Vec3::new(
+            rng.gen_range(-amplitude..=amplitude),
+            rng.gen_range(-amplitude..=amplitude),
+            rng.gen_range(-amplitude..=amplitude),
+        );
+        camera.transform_mut().translate(&self.shake_offset);
+    }
+
+    fn finish_move(&mut self, camera: &mut Camera) {
+        if let Some(active_move) = self.active_move.take() {
+            apply_move(&active_move, camera, 1.);
+        }
+    }
+}
+
+fn apply_move(active_move: &ActiveMove, camera: &mut Camera, t: f32) {
+    let position = active_move.start_position;
+    let target = active_move.start_target;
+    match active_move.camera_move {
+        CameraMove::Push { distance } => {
+            let direction = Vec3::normalized(&Vec3::sub(&target, &position));
+            let position = Vec3::add(&position, &Vec3::dot(distance * t, &direction));
+            camera
+                .transform_mut()
+                .set_position(&position)
+                .look_at(&target);
+        }
+        CameraMove::Rotate { yaw, pitch } => {
+            let offset = rotate_yaw_pitch(&Vec3::sub(&position, &target), yaw * t, pitch * t);
+            camera
+                .transform_mut()
+                .set_position(&Vec3::add(&target, &offset))
+                .look_at(&target);
+        }
+        CameraMove::Wag { yaw, pitch } => {
+            let direction = rotate_yaw_pitch(&Vec3::sub(&target, &position), yaw * t, pitch * t);
+            camera
+                .transform_mut()
+                .set_position(&position)
+                .look_at(&Vec3::add(&position, &direction));
+        }
+        CameraMove::MoveTo { position: to } => {
+            camera
+                .transform_mut()
+                .set_position(&Vec3::lerp(&position, &to, t));
+        }
+    }
+}
+
+/// The direction the camera looks at, which is along its -Z axis.
+pub fn view_direction(camera: &Camera) -> Vec3 {
+    let mat = camera.transform().matrix();
+    Vec3::normalized(&Vec3::new(-mat[0][2], -mat[1][2], -mat[2][2]))
+}
+
+fn rotate_yaw_pitch(vec: &Vec3, yaw: f32, pitch: f32) -> Vec3 {
+    let vec = rotate_axis_angle(vec, &Vec3::UP, yaw.to_radians());
+    let right = Vec3::cross(&Vec3::UP, &vec);
+    if right.norm() <= std::f32::EPSILON {
+        return vec;
+    }
+
+    rotate_axis_angle(&vec, &Vec3::normalized(&right), pitch.to_radians())
+}
+
+// Rodrigues' rotation formula, `axis` is normalized
+fn rotate_axis_angle(vec: &Vec3, axis: &Vec3, radian: f32) -> Vec3 {
+    let c = radian.cos();
+    let s = radian.sin();
+    let dot = axis.x * vec.x + axis.y * vec.y + axis.z * vec.z;
+    Vec3::add(
+        &Vec3::add(&Vec3::dot(c, vec), &Vec3::dot(s, &Vec3::cross(axis, vec))),
+        &Vec3::dot(dot * (1. - c), axis),
+    )
+}
@@ -0,0 +1,73 @@
+use imgui::{im_str, Ui};
+
+// How long a caption takes to fade in and to fade out, in seconds
+const CAPTION_FADE_DURATION: f32 = 0.5;
+
+struct Caption {
+    lines: Vec<String>,
+    duration: f32,
+    elapsed: f32,
+}
+
+/// Shows timed text at the centre of the screen, apart from the dialog box.
+/// The text fades in and out, and stays over the screen fade so that the
+/// chapter titles can be shown on a black screen.
+pub struct CaptionLayer {
+    caption: Option<Caption>,
+}
+
+impl CaptionLayer {
+    pub fn new() -> Self {
+        Self { caption: None }
+    }
+
+    /// Shows `text` for `duration_sec` seconds in place of the caption that
+    /// is showing.
+    pub fn show(&mut self, text: &str, duration_sec: f32) {
+        self.caption = Some(Caption {
+            lines: text
+                .replace("\\n", "\n")
+                .lines()
+                .map(|line| line.to_string())
+                .collect(),
+            duration: duration_sec.max(0.),
+            elapsed: 0.,
+        });
+    }
+
+    pub fn skip(&mut self) {
+        self.caption = None;
+    }
+
+    pub fn is_showing(&self) -> bool {
+        self.caption.is_some()
+    }
+
+    pub fn update(&mut self, ui: &Ui, delta_sec: f32) {
+        let caption = match self.caption.as_mut() {
+            Some(caption) => caption,
+            None => return,
+        };
+
+        caption.elapsed += delta_sec;
+        if caption.elapsed >= caption.duration {
+            self.caption = None;
+            return;
+        }
+
+        let alpha = (caption.elapsed / CAPTION_FADE_DURATION)
+            .min((caption.duration - caption.elapsed) / CAPTION_FADE_DURATION)
+            .min(1.);
+        let [window_width, window_height] = ui.io().display_size;
+        let line_height = ui.text_line_height_with_spacing();
+        let mut y = (window_height - line_height * caption.lines.len() as f32) / 2.;
+        let draw_list = ui.get_foreground_draw_list();
+        for line in &caption.lines {
+            let [width, _] = ui.calc_text_size(&im_str!("{}", line), false, 0.);
+            let x = (window_width - width) / 2.;
+            draw_list.add_text([x + 1., y + 1.], [0., 0., 0., alpha], line);
+            draw_list.add_text([x, y], [1., 1., 1., alpha], line);
+            y += line_height;
+        }
+    }
+}
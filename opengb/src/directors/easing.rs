@@ -0,0 +1,5 @@
+/// Eases in and out so that the moves don't start or stop abruptly. `t` is
+/// how far the move has gone, from 0 to 1.
+pub fn ease(t: f32) -> f32 {
+    t * t * (3. - 2. * t)
+}
@@ -1,24 +1,38 @@
-use super::PersistentState;
+use super::{
+    camera_controller::CameraController,
+    caption::CaptionLayer,
+    ui_images::{DlgFace, UiImages},
+    ItemTable, PersistentState,
+};
 use crate::asset_manager::AssetManager;
+use crate::loaders::LoaderError;
 use crate::utilities::StoreExt2;
 use radiance::audio::{AudioEngine, AudioSource, AudioSourceState, Codec};
+use radiance::rendering::ImguiTexture;
 use regex::Regex;
 use std::{
     cell::{Ref, RefCell, RefMut},
     collections::HashMap,
+    path::PathBuf,
     rc::Rc,
 };
 
+const MUSIC_MAPPING_PATH: &str = "/basedata/basedata\\datascript\\music.txt";
+
 pub struct GlobalState {
     persistent_state: Rc<RefCell<PersistentState>>,
     fop_state: FopState,
     input_enabled: bool,
-    role_controlled: i32,
 
     asset_mgr: Rc<AssetManager>,
     bgm_source: Box<dyn AudioSource>,
     sound_sources: Vec<Rc<RefCell<Box<dyn AudioSource>>>>,
     default_scene_bgm: HashMap<String, String>,
+    item_table: ItemTable,
+    camera_controller: CameraController,
+    ui_images: UiImages,
+    dlg_face: Option<DlgFace>,
+    caption_layer: CaptionLayer,
 }
 
 impl GlobalState {
@@ -26,26 +40,34 @@ impl GlobalState {
         asset_mgr: Rc<AssetManager>,
         audio_engine: &Rc<dyn AudioEngine>,
         persistent_state: Rc<RefCell<PersistentState>>,
-    ) -> Self {
+    ) -> Result<Self, LoaderError> {
         let bgm_source = audio_engine.create_source();
         let sound_sources = vec![];
         let default_scene_bgm = parse_music_mapping(
             asset_mgr
                 .vfs()
-                .read_to_end_from_gbk("/basedata/basedata\\datascript\\music.txt")
-                .unwrap(),
+                .read_to_end_from_gbk(MUSIC_MAPPING_PATH)
+                .map_err(|error| LoaderError::IoError {
+                    path: PathBuf::from(MUSIC_MAPPING_PATH),
+                    error,
+                })?,
         );
+        let item_table = ItemTable::load(asset_mgr.vfs());
 
-        Self {
+        Ok(Self {
             persistent_state,
             fop_state: FopState::new(),
             input_enabled: true,
-            role_controlled: 0,
             asset_mgr,
             bgm_source,
             sound_sources,
             default_scene_bgm,
-        }
+            item_table,
+            camera_controller: CameraController::new(),
+            ui_images: UiImages::new(),
+            dlg_face: None,
+            caption_layer: CaptionLayer::new(),
+        })
     }
 
     pub fn input_enabled(&self) -> bool {
@@ -57,16 +79,20 @@ impl GlobalState {
     }
 
     pub fn role_controlled(&self) -> i32 {
-        self.role_controlled
+        self.persistent_state.borrow().role_controlled()
     }
 
     pub fn set_role_controlled(&mut self, role_controlled: i32) {
-        self.role_controlled = role_controlled
+        self.persistent_state
+            .borrow_mut()
+            .set_role_controlled(role_controlled)
     }
 
     pub fn play_bgm(&mut self, name: &str) {
-        let data = self.asset_mgr.load_music_data(name);
-        self.bgm_source.play(data, Codec::Mp3, true);
+        match self.asset_mgr.load_music_data(name) {
+            Ok(data) => self.bgm_source.play(data, Codec::Mp3, true),
+            Err(e) => log::error!("Cannot open music {}: {}", name, e),
+        }
     }
 
     pub fn play_default_bgm(&mut self) {
@@ -108,6 +134,43 @@ impl GlobalState {
         self.persistent_state.borrow_mut()
     }
 
+    pub fn item_table(&self) -> &ItemTable {
+        &self.item_table
+    }
+
+    pub fn camera_controller(&self) -> &CameraController {
+        &self.camera_controller
+    }
+
+    pub fn camera_controller_mut(&mut self) -> &mut CameraController {
+        &mut self.camera_controller
+    }
+
+    pub fn caption_layer(&self) -> &CaptionLayer {
+        &self.caption_layer
+    }
+
+    pub fn caption_layer_mut(&mut self) -> &mut CaptionLayer {
+        &mut self.caption_layer
+    }
+
+    pub fn load_face_image(&mut self, face_name: &str) -> Option<Rc<ImguiTexture>> {
+        self.ui_images.face(&self.asset_mgr, face_name)
+    }
+
+    pub fn load_emote_image(&mut self, emote_id: i32) -> Option<Rc<ImguiTexture>> {
+        self.ui_images.emote(&self.asset_mgr, emote_id)
+    }
+
+    /// The portrait that the dialogs show, until another one is set.
+    pub fn dlg_face(&self) -> Option<&DlgFace> {
+        self.dlg_face.as_ref()
+    }
+
+    pub fn set_dlg_face(&mut self, dlg_face: Option<DlgFace>) {
+        self.dlg_face = dlg_face;
+    }
+
     pub fn add_sound_source(&mut self, source: Rc<RefCell<Box<dyn AudioSource>>>) {
         self.sound_sources.push(source);
     }
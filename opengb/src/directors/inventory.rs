@@ -0,0 +1,206 @@
+use super::GlobalState;
+use crate::utilities::StoreExt2;
+use imgui::{im_str, ChildWindow, Condition, Selectable, Ui, Window};
+use mini_fs::MiniFs;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+const ITEM_TABLE_PATH: &str = "/basedata/basedata\\datascript\\item.txt";
+
+lazy_static::lazy_static! {
+    static ref ITEM_REGEX: Regex = Regex::new(r"^\s*(\d+)\s*\$(.*?)\$(.*?)&").unwrap();
+}
+
+/// The items that the party carries, with their counts.
+#[derive(Default, Serialize, Deserialize)]
+pub struct Inventory {
+    items: BTreeMap<i32, i32>,
+}
+
+impl Inventory {
+    pub fn new() -> Self {
+        Self {
+            items: BTreeMap::new(),
+        }
+    }
+
+    /// Adds `count` of the item. A negative count takes the items away, and
+    /// the item is dropped when none is left.
+    pub fn add_item(&mut self, item_id: i32, count: i32) {
+        let new_count = self.item_count(item_id) + count;
+        if new_count > 0 {
+            self.items.insert(item_id, new_count);
+        } else {
+            self.items.remove(&item_id);
+        }
+    }
+
+    pub fn item_count(&self, item_id: i32) -> i32 {
+        self.items.get(&item_id).copied().unwrap_or(0)
+    }
+
+    pub fn has_item(&self, item_id: i32) -> bool {
+        self.item_count(item_id) > 0
+    }
+
+    pub fn items(&self) -> impl Iterator<Item = (i32, i32)> + '_ {
+        self.items.iter().map(|(&id, &count)| (id, count))
+    }
+}
+
+pub struct ItemInfo {
+    pub name: String,
+    pub description: String,
+}
+
+/// The names and the descriptions of the items, from the item table of
+/// basedata.
+pub struct ItemTable {
+    items: HashMap<i32, ItemInfo>,
+}
+
+impl ItemTable {
+    pub fn load(vfs: &MiniFs) -> Self {
+        let items = match vfs.read_to_end_from_gbk(ITEM_TABLE_PATH) {
+            Ok(content) => parse_item_table(&content),
+            Err(e) => {
+                log::warn!("Cannot load the item table: {}", e);
+                HashMap::new()
+            }
+        };
+
+        Self { items }
+    }
+
+    pub fn get(&self, item_id: i32) -> Option<&ItemInfo> {
+        self.items.get(&item_id)
+    }
+
+    /// The name of the item, or its id if it isn't in the table.
+    pub fn name(&self, item_id: i32) -> String {
+        self.get(item_id)
+            .map(|item| item.name.clone())
+            .unwrap_or_else(|| format!("#{}", item_id))
+    }
+}
+
+/// A window that lists the items in the inventory and shows the description
+/// of the selected one.
+pub struct InventoryScreen {
+    visible: bool,
+    selected_item: Option<i32>,
+}
+
+impl InventoryScreen {
+    pub fn new() -> Self {
+        Self {
+            visible: false,
+            selected_item: None,
+        }
+    }
+
+    pub fn visible(&self) -> bool {
+        self.visible
+    }
+
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+
+    pub fn update(&mut self, ui: &mut Ui, global_state: &GlobalState) {
+        if !self.visible {
+            return;
+        }
+
+        let [window_width, window_height] = ui.io().display_size;
+        let p_state = global_state.persistent_state();
+        let inventory = p_state.inventory();
+        let item_table = global_state.item_table();
+        if self
+            .selected_item
+            .map_or(false, |item_id| !inventory.has_item(item_id))
+        {
+            self.selected_item = None;
+        }
+
+        let mut visible = self.visible;
+        let selected_item = &mut self.selected_item;
+        Window::new(im_str!("Inventory"))
+            .opened(&mut visible)
+            .collapsible(false)
+            .resizable(false)
+            .size([window_width / 2., window_height / 2.], Condition::Always)
+            .position_pivot([0.5, 0.5])
+            .position([window_width / 2., window_height / 2.], Condition::Always)
+            .build(ui, || {
+                ChildWindow::new(im_str!("items"))
+                    .size([0., -ui.text_line_height_with_spacing() * 3.])
+                    .build(ui, || {
+                        for (item_id, count) in inventory.items() {
+                            let label =
+                                im_str!("{} x{}##{}", item_table.name(item_id), count, item_id);
+                            if Selectable::new(&label)
+                                .selected(*selected_item == Some(item_id))
+                                .build(ui)
+                            {
+                                *selected_item = Some(item_id);
+                            }
+                        }
+                    });
+
+                ui.separator();
+                if let Some(item) = selected_item.and_then(|item_id| item_table.get(item_id)) {
+                    ui.text_wrapped(&im_str!("{}", item.description));
+                }
+            });
+
+        self.visible = visible;
+    }
+}
+
+// Every line of the table is an item: `<id> $<name>$<description>&`
+fn parse_item_table(content: &str) -> HashMap<i32, ItemInfo> {
+    content
+        .lines()
+        .filter_map(|line| ITEM_REGEX.captures(line))
+        .filter_map(|c| {
+            let item_id = c[1].parse().ok()?;
+            Some((
+                item_id,
+                ItemInfo {
+                    name: c[2].trim().to_string(),
+                    description: c[3].trim().replace("\\n", "\n"),
+                },
+            ))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_item_table;
+    use encoding::{DecoderTrap, Encoding};
+
+    #[test]
+    fn items_are_parsed_from_the_lines_of_the_table() {
+        // 金疮药 (Jin Chuang Yao) and its description, in GBK like item.txt
+        let data = b"1001 $\xbd\xf0\xb4\xaf\xd2\xa9$\xbb\xd6\xb8\xb4\xc9\xd9\xc1\xbf\xcc\xe5\xc1\xa6\\n+50&\r\n\
+            1002$ Antidote $Cures poison& trailing\r\n\
+            // A comment\r\n\
+            1003 $Broken$no end\r\n\
+            abc $Name$Description&\r\n\
+            99999999999 $Too large$Description&\r\n\
+            \r\n";
+        let content = encoding::all::GBK
+            .decode(data, DecoderTrap::Ignore)
+            .unwrap();
+
+        let items = parse_item_table(&content);
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[&1001].name, "金疮药");
+        assert_eq!(items[&1001].description, "恢复少量体力\n+50");
+        assert_eq!(items[&1002].name, "Antidote");
+        assert_eq!(items[&1002].description, "Cures poison");
+    }
+}
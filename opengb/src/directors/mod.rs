@@ -1,15 +1,31 @@
 mod adv_director;
+mod camera_controller;
+mod caption;
+mod easing;
 mod global_state;
+mod inventory;
+mod object_state;
+mod party;
 mod persistent_state;
 mod sce_commands;
 mod sce_vm;
+mod ui_images;
 
 use self::sce_vm::SceState;
 use crate::scene::{RoleEntity, ScnScene};
 pub use adv_director::AdventureDirector;
+pub use camera_controller::{CameraController, CameraMove};
+pub use caption::CaptionLayer;
 pub use global_state::GlobalState;
+pub use inventory::{Inventory, InventoryScreen, ItemInfo, ItemTable};
+pub use object_state::ObjectState;
+pub use party::Party;
 pub use persistent_state::PersistentState;
 use radiance::scene::{CoreEntity, CoreScene, SceneManager};
+pub use ui_images::{DlgFace, FaceSide, UiImages};
+
+/// How long the screen takes to fade in or out, in seconds.
+pub const FADE_DURATION: f32 = 0.5;
 
 pub trait SceneManagerExtensions: SceneManager {
     fn core_scene_mut(&mut self) -> Option<&mut CoreScene<ScnScene>> {
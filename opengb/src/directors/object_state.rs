@@ -0,0 +1,33 @@
+use super::PersistentState;
+use crate::scene::{CvdModelEntity, ScnScene};
+use radiance::math::Mat44;
+use radiance::scene::{CoreEntity, CoreScene, Entity};
+use serde::{Deserialize, Serialize};
+
+/// What the scripts have done to an object of a scene, which is kept when
+/// the scene is loaded again.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct ObjectState {
+    pub transform: Option<Mat44>,
+    pub opened: bool,
+}
+
+/// Puts the objects of the scene back where the scripts left them.
+pub fn restore_object_states(scene: &mut CoreScene<ScnScene>, p_state: &PersistentState) {
+    for (&object_id, state) in p_state.object_states() {
+        let entity = match scene.get_root_object_mut(object_id) {
+            Some(entity) => entity,
+            None => continue,
+        };
+
+        if let Some(transform) = state.transform {
+            entity.transform_mut().set_matrix(transform);
+        }
+
+        if state.opened {
+            if let Some(cvd) = entity.downcast_mut::<CoreEntity<CvdModelEntity>>() {
+                cvd.finish();
+            }
+        }
+    }
+}
@@ -0,0 +1,92 @@
+use super::GlobalState;
+use crate::scene::ScnScene;
+use radiance::scene::{CoreScene, SceneManager};
+use serde::{Deserialize, Serialize};
+
+/// The roles in the team. The controlled role leads the team, and the other
+/// members follow it when the followers are visible.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Party {
+    members: Vec<i32>,
+    followers_visible: bool,
+}
+
+impl Default for Party {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Party {
+    pub fn new() -> Self {
+        Self {
+            members: vec![0],
+            followers_visible: true,
+        }
+    }
+
+    pub fn members(&self) -> &[i32] {
+        &self.members
+    }
+
+    pub fn is_member(&self, role_id: i32) -> bool {
+        self.members.contains(&role_id)
+    }
+
+    /// Adds the role to the end of the team, or takes it out of the team.
+    pub fn set_member(&mut self, role_id: i32, in_team: bool) {
+        if in_team {
+            if !self.is_member(role_id) {
+                self.members.push(role_id);
+            }
+        } else {
+            self.members.retain(|&id| id != role_id);
+        }
+    }
+
+    /// The members that follow `leader`, in the order they walk behind it.
+    pub fn followers(&self, leader: i32) -> impl Iterator<Item = i32> + '_ {
+        self.members.iter().copied().filter(move |&id| id != leader)
+    }
+
+    pub fn followers_visible(&self) -> bool {
+        self.followers_visible
+    }
+
+    pub fn set_followers_visible(&mut self, followers_visible: bool) {
+        self.followers_visible = followers_visible;
+    }
+}
+
+/// Spawns the leader and the members of the party that aren't in the scene
+/// yet, and shows or hides the roles according to the party.
+pub fn activate_party_roles(scene: &mut CoreScene<ScnScene>, party: &Party, leader: i32) {
+    for id in std::iter::once(leader).chain(party.followers(leader)) {
+        scene.spawn_party_role(id);
+        if scene.has_role_entity(id) {
+            scene
+                .get_role_entity_mut(id)
+                .set_active(id == leader || party.followers_visible());
+        }
+    }
+}
+
+/// Changes the party and updates the roles of the current scene to match it.
+/// The party can change before the first scene is loaded, so the scene is
+/// only returned when there is one.
+pub fn change_party<'a, F: FnOnce(&mut Party)>(
+    scene_manager: &'a mut dyn SceneManager,
+    global_state: &mut GlobalState,
+    change: F,
+) -> Option<&'a mut CoreScene<ScnScene>> {
+    let leader = global_state.role_controlled();
+    let mut p_state = global_state.persistent_state_mut();
+    let party = p_state.party_mut();
+    change(party);
+
+    let scene = scene_manager
+        .scene_mut()
+        .and_then(|s| s.downcast_mut::<CoreScene<ScnScene>>())?;
+    activate_party_roles(scene, party, leader);
+    Some(scene)
+}
@@ -1,3 +1,4 @@
+use super::{Inventory, ObjectState, Party};
 use radiance::math::Vec3;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -9,6 +10,14 @@ pub struct PersistentState {
     position: Vec3,
     scene: Option<String>,
     sub_scene: Option<String>,
+    #[serde(default)]
+    inventory: Inventory,
+    #[serde(default)]
+    party: Party,
+    #[serde(default)]
+    object_states: HashMap<String, HashMap<i32, ObjectState>>,
+    #[serde(default)]
+    role_controlled: i32,
 }
 
 impl PersistentState {
@@ -19,6 +28,10 @@ impl PersistentState {
             position: Vec3::new(0., 0., 0.),
             scene: None,
             sub_scene: None,
+            inventory: Inventory::new(),
+            party: Party::new(),
+            object_states: HashMap::new(),
+            role_controlled: 0,
         }
     }
 
@@ -74,6 +87,14 @@ impl PersistentState {
         self.position = position;
     }
 
+    pub fn role_controlled(&self) -> i32 {
+        self.role_controlled
+    }
+
+    pub fn set_role_controlled(&mut self, role_controlled: i32) {
+        self.role_controlled = role_controlled;
+    }
+
     pub fn set_scene_name(&mut self, scene: String, sub_scene: String) {
         self.scene = Some(scene);
         self.sub_scene = Some(sub_scene);
@@ -86,4 +107,44 @@ impl PersistentState {
     pub fn sub_scene_name(&self) -> Option<String> {
         self.sub_scene.clone()
     }
+
+    pub fn inventory(&self) -> &Inventory {
+        &self.inventory
+    }
+
+    pub fn inventory_mut(&mut self) -> &mut Inventory {
+        &mut self.inventory
+    }
+
+    pub fn party(&self) -> &Party {
+        &self.party
+    }
+
+    pub fn party_mut(&mut self) -> &mut Party {
+        &mut self.party
+    }
+
+    /// The states of the objects of the current scene.
+    pub fn object_states(&self) -> impl Iterator<Item = (&i32, &ObjectState)> {
+        self.object_states
+            .get(&self.scene_key())
+            .into_iter()
+            .flat_map(|states| states.iter())
+    }
+
+    pub fn object_state_mut(&mut self, object_id: i32) -> &mut ObjectState {
+        self.object_states
+            .entry(self.scene_key())
+            .or_insert_with(HashMap::new)
+            .entry(object_id)
+            .or_insert_with(ObjectState::default)
+    }
+
+    fn scene_key(&self) -> String {
+        format!(
+            "{}_{}",
+            self.scene.as_deref().unwrap_or_default(),
+            self.sub_scene.as_deref().unwrap_or_default()
+        )
+    }
 }
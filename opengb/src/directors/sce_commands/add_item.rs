@@ -0,0 +1,32 @@
+use crate::directors::sce_vm::{SceCommand, SceState};
+use imgui::Ui;
+use radiance::scene::SceneManager;
+
+#[derive(Clone)]
+pub struct SceCommandAddItem {
+    item_id: i32,
+    count: i32,
+}
+
+impl SceCommand for SceCommandAddItem {
+    fn update(
+        &mut self,
+        scene_manager: &mut dyn SceneManager,
+        ui: &mut Ui,
+        state: &mut SceState,
+        delta_sec: f32,
+    ) -> bool {
+        state
+            .global_state_mut()
+            .persistent_state_mut()
+            .inventory_mut()
+            .add_item(self.item_id, self.count);
+        true
+    }
+}
+
+impl SceCommandAddItem {
+    pub fn new(item_id: i32, count: i32) -> Self {
+        Self { item_id, count }
+    }
+}
@@ -0,0 +1,30 @@
+use crate::directors::sce_vm::{SceCommand, SceState};
+use imgui::Ui;
+use radiance::scene::SceneManager;
+
+#[derive(Clone)]
+pub struct SceCommandCameraFree {
+    free: i32,
+}
+
+impl SceCommand for SceCommandCameraFree {
+    fn update(
+        &mut self,
+        scene_manager: &mut dyn SceneManager,
+        ui: &mut Ui,
+        state: &mut SceState,
+        delta_sec: f32,
+    ) -> bool {
+        state
+            .global_state_mut()
+            .camera_controller_mut()
+            .set_free(self.free != 0);
+        true
+    }
+}
+
+impl SceCommandCameraFree {
+    pub fn new(free: i32) -> Self {
+        Self { free }
+    }
+}
@@ -1,15 +1,27 @@
+use crate::directors::camera_controller::CameraMove;
 use crate::directors::sce_vm::{SceCommand, SceState};
-use crate::directors::SceneManagerExtensions;
 use imgui::Ui;
-use radiance::scene::Scene;
 use radiance::{math::Vec3, scene::SceneManager};
 
 #[derive(Clone)]
 pub struct SceCommandCameraMove {
     position: Vec3,
+    duration: f32,
+    sync: bool,
 }
 
 impl SceCommand for SceCommandCameraMove {
+    fn initialize(&mut self, scene_manager: &mut dyn SceneManager, state: &mut SceState) {
+        let controller = state.global_state_mut().camera_controller_mut();
+        controller.start_scene_move(
+            scene_manager,
+            CameraMove::MoveTo {
+                position: self.position,
+            },
+            self.duration,
+        );
+    }
+
     fn update(
         &mut self,
         scene_manager: &mut dyn SceneManager,
@@ -17,13 +29,8 @@ impl SceCommand for SceCommandCameraMove {
         state: &mut SceState,
         delta_sec: f32,
     ) -> bool {
-        let scene = scene_manager.core_scene_mut_or_fail();
-        scene
-            .camera_mut()
-            .transform_mut()
-            .set_position(&self.position);
-
-        return true;
+        let controller = state.global_state().camera_controller();
+        controller.can_continue(self.sync)
     }
 }
 
@@ -32,11 +39,13 @@ impl SceCommandCameraMove {
         position_x: f32,
         position_y: f32,
         position_z: f32,
-        unknown_1: f32,
-        unknown_2: f32,
+        duration: f32,
+        sync: f32,
     ) -> Self {
         Self {
             position: Vec3::new(position_x, position_y, position_z),
+            duration,
+            sync: sync != 0.,
         }
     }
 }
@@ -0,0 +1,45 @@
+use crate::directors::camera_controller::CameraMove;
+use crate::directors::sce_vm::{SceCommand, SceState};
+use imgui::Ui;
+use radiance::scene::SceneManager;
+
+#[derive(Clone)]
+pub struct SceCommandCameraPush {
+    distance: f32,
+    duration: f32,
+    sync: bool,
+}
+
+impl SceCommand for SceCommandCameraPush {
+    fn initialize(&mut self, scene_manager: &mut dyn SceneManager, state: &mut SceState) {
+        let controller = state.global_state_mut().camera_controller_mut();
+        controller.start_scene_move(
+            scene_manager,
+            CameraMove::Push {
+                distance: self.distance,
+            },
+            self.duration,
+        );
+    }
+
+    fn update(
+        &mut self,
+        scene_manager: &mut dyn SceneManager,
+        ui: &mut Ui,
+        state: &mut SceState,
+        delta_sec: f32,
+    ) -> bool {
+        let controller = state.global_state().camera_controller();
+        controller.can_continue(self.sync)
+    }
+}
+
+impl SceCommandCameraPush {
+    pub fn new(distance: f32, duration: f32, sync: i32) -> Self {
+        Self {
+            distance,
+            duration,
+            sync: sync != 0,
+        }
+    }
+}
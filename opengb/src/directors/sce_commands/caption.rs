@@ -0,0 +1,42 @@
+use crate::directors::sce_vm::{SceCommand, SceState};
+use imgui::Ui;
+use radiance::{input::Key, scene::SceneManager};
+
+#[derive(Clone)]
+pub struct SceCommandCaption {
+    text: String,
+    duration: f32,
+}
+
+impl SceCommand for SceCommandCaption {
+    fn initialize(&mut self, scene_manager: &mut dyn SceneManager, state: &mut SceState) {
+        state
+            .global_state_mut()
+            .caption_layer_mut()
+            .show(&self.text, self.duration);
+    }
+
+    fn update(
+        &mut self,
+        scene_manager: &mut dyn SceneManager,
+        ui: &mut Ui,
+        state: &mut SceState,
+        delta_sec: f32,
+    ) -> bool {
+        if state.input().get_key_state(Key::Space).pressed() {
+            state.global_state_mut().caption_layer_mut().skip();
+        }
+
+        !state.global_state().caption_layer().is_showing()
+    }
+}
+
+impl SceCommandCaption {
+    // The duration is in seconds
+    pub fn new(text: String, duration: i32) -> Self {
+        Self {
+            text,
+            duration: duration as f32,
+        }
+    }
+}
@@ -1,5 +1,6 @@
 use crate::directors::sce_vm::{SceCommand, SceState};
-use imgui::{im_str, Condition, Ui, Window};
+use crate::directors::FaceSide;
+use imgui::{im_str, Condition, Image, Ui, Window};
 use radiance::{input::Key, scene::SceneManager};
 
 #[derive(Clone)]
@@ -10,6 +11,7 @@ pub struct SceCommandDlg {
 impl SceCommandDlg {
     const DLG_HEIGHT_FACTOR: f32 = 0.25;
     const DLG_Y_POSITION_FACTOR: f32 = 1. - SceCommandDlg::DLG_HEIGHT_FACTOR;
+    const FACE_SPACING: f32 = 10.;
 }
 
 impl SceCommand for SceCommandDlg {
@@ -49,8 +51,28 @@ impl SceCommand for SceCommandDlg {
                 ],
                 Condition::Appearing,
             );
-        w.build(ui, || {
-            ui.text_wrapped(&im_str!("{}", self.text));
+        let face = state.global_state().dlg_face().cloned();
+        w.build(ui, || match face {
+            Some(face) => {
+                let [content_width, content_height] = ui.content_region_avail();
+                let face_height = content_height;
+                let face_width =
+                    face_height * face.image.width() as f32 / face.image.height().max(1) as f32;
+                let text_width = content_width - face_width - SceCommandDlg::FACE_SPACING;
+                let [x, y] = ui.cursor_pos();
+                let (face_x, text_x) = match face.side {
+                    FaceSide::Left => (x, x + face_width + SceCommandDlg::FACE_SPACING),
+                    FaceSide::Right => (x + text_width + SceCommandDlg::FACE_SPACING, x),
+                };
+
+                ui.set_cursor_pos([face_x, y]);
+                Image::new(face.image.texture_id(), [face_width, face_height]).build(ui);
+                ui.set_cursor_pos([text_x, y]);
+                let wrap_pos = ui.push_text_wrap_pos(text_x + text_width);
+                ui.text(&im_str!("{}", self.text));
+                wrap_pos.pop(ui);
+            }
+            None => ui.text_wrapped(&im_str!("{}", self.text)),
         });
 
         state.input().get_key_state(Key::Space).pressed()
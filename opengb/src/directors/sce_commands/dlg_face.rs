@@ -0,0 +1,48 @@
+use crate::directors::sce_vm::{SceCommand, SceState};
+use crate::directors::{DlgFace, FaceSide};
+use imgui::Ui;
+use radiance::scene::SceneManager;
+
+#[derive(Clone)]
+pub struct SceCommandDlgFace {
+    face_name: String,
+    side: FaceSide,
+}
+
+impl SceCommand for SceCommandDlgFace {
+    fn update(
+        &mut self,
+        scene_manager: &mut dyn SceneManager,
+        ui: &mut Ui,
+        state: &mut SceState,
+        delta_sec: f32,
+    ) -> bool {
+        // The face name is the file name of the portrait and already tells
+        // the role, so an empty name takes the portrait away
+        let image = if self.face_name.is_empty() {
+            None
+        } else {
+            state.global_state_mut().load_face_image(&self.face_name)
+        };
+
+        let side = self.side;
+        state
+            .global_state_mut()
+            .set_dlg_face(image.map(|image| DlgFace { image, side }));
+        true
+    }
+}
+
+impl SceCommandDlgFace {
+    // The role is already told by the face name
+    pub fn new(_role_id: i32, face_name: String, side: i32) -> Self {
+        Self {
+            face_name: face_name.trim().to_string(),
+            side: if side == 0 {
+                FaceSide::Left
+            } else {
+                FaceSide::Right
+            },
+        }
+    }
+}
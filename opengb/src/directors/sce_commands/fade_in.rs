@@ -0,0 +1,29 @@
+use crate::directors::sce_vm::{SceCommand, SceState};
+use crate::directors::FADE_DURATION;
+use imgui::Ui;
+use radiance::scene::SceneManager;
+
+#[derive(Clone)]
+pub struct SceCommandFadeIn {}
+
+impl SceCommand for SceCommandFadeIn {
+    fn initialize(&mut self, scene_manager: &mut dyn SceneManager, state: &mut SceState) {
+        scene_manager.screen_fade_mut().fade_in(FADE_DURATION);
+    }
+
+    fn update(
+        &mut self,
+        scene_manager: &mut dyn SceneManager,
+        ui: &mut Ui,
+        state: &mut SceState,
+        delta_sec: f32,
+    ) -> bool {
+        !scene_manager.screen_fade().is_fading()
+    }
+}
+
+impl SceCommandFadeIn {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
@@ -0,0 +1,31 @@
+use crate::directors::sce_vm::{SceCommand, SceState};
+use crate::directors::FADE_DURATION;
+use imgui::Ui;
+use radiance::{math::Vec3, scene::SceneManager};
+
+#[derive(Clone)]
+pub struct SceCommandFadeOut {}
+
+impl SceCommand for SceCommandFadeOut {
+    fn initialize(&mut self, scene_manager: &mut dyn SceneManager, state: &mut SceState) {
+        scene_manager
+            .screen_fade_mut()
+            .fade_out(&Vec3::new(0., 0., 0.), FADE_DURATION);
+    }
+
+    fn update(
+        &mut self,
+        scene_manager: &mut dyn SceneManager,
+        ui: &mut Ui,
+        state: &mut SceState,
+        delta_sec: f32,
+    ) -> bool {
+        !scene_manager.screen_fade().is_fading()
+    }
+}
+
+impl SceCommandFadeOut {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
@@ -15,8 +15,15 @@ impl SceCommand for SceCommandHaveItem {
         state: &mut SceState,
         delta_sec: f32,
     ) -> bool {
-        // TODO
-        state.global_state_mut().fop_state_mut().push_value(true);
+        let has_item = state
+            .global_state()
+            .persistent_state()
+            .inventory()
+            .has_item(self.item_id);
+        state
+            .global_state_mut()
+            .fop_state_mut()
+            .push_value(has_item);
         true
     }
 }
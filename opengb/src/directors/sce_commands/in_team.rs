@@ -0,0 +1,40 @@
+use crate::directors::party::change_party;
+use crate::directors::sce_vm::{SceCommand, SceState};
+use imgui::Ui;
+use radiance::scene::SceneManager;
+
+#[derive(Clone)]
+pub struct SceCommandInTeam {
+    role_id: i32,
+    in_team: i32,
+}
+
+impl SceCommand for SceCommandInTeam {
+    fn update(
+        &mut self,
+        scene_manager: &mut dyn SceneManager,
+        ui: &mut Ui,
+        state: &mut SceState,
+        delta_sec: f32,
+    ) -> bool {
+        let in_team = self.in_team != 0;
+        let role_id = self.role_id;
+        let scene = change_party(scene_manager, state.global_state_mut(), |party| {
+            party.set_member(role_id, in_team)
+        });
+
+        if let Some(scene) = scene {
+            if !in_team && scene.has_role_entity(role_id) {
+                scene.get_role_entity_mut(role_id).set_active(false);
+            }
+        }
+
+        true
+    }
+}
+
+impl SceCommandInTeam {
+    pub fn new(role_id: i32, in_team: i32) -> Self {
+        Self { role_id, in_team }
+    }
+}
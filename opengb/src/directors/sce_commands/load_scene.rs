@@ -1,6 +1,9 @@
+use crate::directors::object_state::restore_object_states;
+use crate::directors::party::activate_party_roles;
 use crate::directors::sce_vm::{SceCommand, SceState};
-use crate::directors::SceneManagerExtensions;
+use crate::directors::{SceneManagerExtensions, FADE_DURATION};
 use imgui::Ui;
+use radiance::math::Vec3;
 use radiance::scene::{CoreScene, SceneManager};
 use std::rc::Rc;
 
@@ -18,16 +21,38 @@ impl SceCommand for SceCommandLoadScene {
         state: &mut SceState,
         delta_sec: f32,
     ) -> bool {
+        // The screen fades out before the scene is swapped. The director fades
+        // it in again when the player gets the control back.
+        let screen_fade = scene_manager.screen_fade_mut();
+        if !screen_fade.is_covered() {
+            if !screen_fade.is_fading() {
+                screen_fade.fade_out(&Vec3::new(0., 0., 0.), FADE_DURATION);
+            }
+
+            return false;
+        }
+
         let last_scene = scene_manager.core_scene_mut();
         let cpk_changed = last_scene
             .and_then(|s| Some(s.name() != &self.name))
             .or(Some(true))
             .unwrap();
 
+        let mut scene = match state.asset_mgr().load_scn(&self.name, &self.sub_name) {
+            Ok(scene) => scene,
+            Err(e) => {
+                log::error!("Cannot load scene {} {}: {}", self.name, self.sub_name, e);
+                return true;
+            }
+        };
+
+        let leader = state.global_state().role_controlled();
+        let party = state.global_state().persistent_state().party().clone();
+        scene.set_party_members(party.members());
+
         scene_manager.pop_scene();
-        scene_manager.push_scene(Box::new(CoreScene::new(
-            state.asset_mgr().load_scn(&self.name, &self.sub_name),
-        )));
+        scene_manager.push_scene(Box::new(CoreScene::new(scene)));
+        activate_party_roles(scene_manager.core_scene_mut_or_fail(), &party, leader);
         scene_manager
             .get_resolved_role_entity_mut(state, -1)
             .set_active(true);
@@ -36,9 +61,15 @@ impl SceCommand for SceCommandLoadScene {
             .global_state_mut()
             .persistent_state_mut()
             .set_scene_name(self.name.clone(), self.sub_name.clone());
+        restore_object_states(
+            scene_manager.core_scene_mut_or_fail(),
+            &state.global_state().persistent_state(),
+        );
         if cpk_changed {
-            let sce = Rc::new(state.asset_mgr().load_sce(&self.name));
-            state.context_mut().set_sce(sce);
+            match state.asset_mgr().load_sce(&self.name) {
+                Ok(sce) => state.context_mut().set_sce(Rc::new(sce)),
+                Err(e) => log::error!("Cannot load sce {}: {}", self.name, e),
+            }
             state.global_state_mut().bgm_source().stop();
             state.global_state_mut().play_default_bgm();
         }
@@ -1,11 +1,20 @@
 mod _let;
+mod add_item;
 mod call;
 mod camera_default;
+mod camera_free;
 mod camera_move;
+mod camera_push;
+mod camera_rotate;
 mod camera_set;
+mod camera_wag;
+mod caption;
 mod cmp;
 mod dlg;
+mod dlg_face;
 mod dlg_sel;
+mod fade_in;
+mod fade_out;
 mod fop;
 mod get_appr;
 mod get_dlg_sel;
@@ -13,15 +22,21 @@ mod goto;
 mod have_item;
 mod hy_fly;
 mod idle;
+mod in_team;
 mod load_scene;
 mod music;
 mod nop;
+mod note;
 mod object_active;
+mod object_move;
+mod open_door;
 mod play_sound;
+mod quake;
 mod rnd;
 mod role_act_auto_stand;
 mod role_active;
 mod role_ctrl;
+mod role_emote;
 mod role_face_role;
 mod role_input;
 mod role_move_back;
@@ -34,18 +49,29 @@ mod role_show_action;
 mod role_turn_face;
 mod script_run_mode;
 mod stop_music;
+mod team_close;
+mod team_open;
 mod testgoto;
 
 pub use _let::SceCommandLet;
+pub use add_item::SceCommandAddItem;
 pub use call::SceCommandCall;
 pub use camera_default::SceCommandCameraDefault;
+pub use camera_free::SceCommandCameraFree;
 pub use camera_move::SceCommandCameraMove;
+pub use camera_push::SceCommandCameraPush;
+pub use camera_rotate::SceCommandCameraRotate;
 pub use camera_set::SceCommandCameraSet;
+pub use camera_wag::SceCommandCameraWag;
+pub use caption::SceCommandCaption;
 pub use cmp::{
     SceCommandEq, SceCommandGeq, SceCommandGt, SceCommandLeq, SceCommandLs, SceCommandNeq,
 };
 pub use dlg::SceCommandDlg;
+pub use dlg_face::SceCommandDlgFace;
 pub use dlg_sel::SceCommandDlgSel;
+pub use fade_in::SceCommandFadeIn;
+pub use fade_out::SceCommandFadeOut;
 pub use fop::SceCommandFop;
 pub use get_appr::SceCommandGetAppr;
 pub use get_dlg_sel::SceCommandGetDlgSel;
@@ -53,15 +79,21 @@ pub use goto::SceCommandGoto;
 pub use have_item::SceCommandHaveItem;
 pub use hy_fly::SceCommandHyFly;
 pub use idle::SceCommandIdle;
+pub use in_team::SceCommandInTeam;
 pub use load_scene::SceCommandLoadScene;
 pub use music::SceCommandMusic;
 pub use nop::SceCommandNop;
+pub use note::SceCommandNote;
 pub use object_active::SceCommandObjectActive;
+pub use object_move::SceCommandObjectMove;
+pub use open_door::SceCommandOpenDoor;
 pub use play_sound::SceCommandPlaySound;
+pub use quake::SceCommandQuake;
 pub use rnd::SceCommandRnd;
 pub use role_act_auto_stand::SceCommandRoleActAutoStand;
 pub use role_active::SceCommandRoleActive;
 pub use role_ctrl::SceCommandRoleCtrl;
+pub use role_emote::SceCommandRoleEmote;
 pub use role_face_role::SceCommandRoleFaceRole;
 pub use role_input::SceCommandRoleInput;
 pub use role_move_back::SceCommandRoleMoveBack;
@@ -74,6 +106,8 @@ pub use role_show_action::SceCommandRoleShowAction;
 pub use role_turn_face::SceCommandRoleTurnFace;
 pub use script_run_mode::SceCommandScriptRunMode;
 pub use stop_music::SceCommandStopMusic;
+pub use team_close::SceCommandTeamClose;
+pub use team_open::SceCommandTeamOpen;
 pub use testgoto::SceCommandTestGoto;
 
 use radiance::math::Vec3;
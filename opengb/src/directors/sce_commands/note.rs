@@ -0,0 +1,35 @@
+use crate::directors::sce_vm::{SceCommand, SceState};
+use imgui::Ui;
+use radiance::scene::SceneManager;
+
+#[derive(Clone)]
+pub struct SceCommandNote {
+    text: String,
+}
+
+impl SceCommandNote {
+    const DURATION: f32 = 3.;
+}
+
+impl SceCommand for SceCommandNote {
+    fn update(
+        &mut self,
+        scene_manager: &mut dyn SceneManager,
+        ui: &mut Ui,
+        state: &mut SceState,
+        delta_sec: f32,
+    ) -> bool {
+        // Unlike Caption, the scripts go on while the note is shown
+        state
+            .global_state_mut()
+            .caption_layer_mut()
+            .show(&self.text, SceCommandNote::DURATION);
+        true
+    }
+}
+
+impl SceCommandNote {
+    pub fn new(text: String) -> Self {
+        Self { text }
+    }
+}
@@ -0,0 +1,83 @@
+use crate::directors::easing::ease;
+use crate::directors::sce_vm::{SceCommand, SceState};
+use crate::directors::SceneManagerExtensions;
+use imgui::Ui;
+use radiance::{math::Vec3, scene::SceneManager};
+
+#[derive(Clone)]
+pub struct SceCommandObjectMove {
+    object_id: i32,
+    position: Vec3,
+    duration: f32,
+    elapsed: f32,
+    start_position: Option<Vec3>,
+}
+
+impl SceCommand for SceCommandObjectMove {
+    fn initialize(&mut self, scene_manager: &mut dyn SceneManager, state: &mut SceState) {
+        self.start_position = scene_manager
+            .core_scene_mut_or_fail()
+            .get_root_object_mut(self.object_id)
+            .map(|e| e.transform().position());
+
+        if self.start_position.is_none() {
+            log::warn!("ObjectMove: object {} not found", self.object_id);
+        }
+    }
+
+    fn update(
+        &mut self,
+        scene_manager: &mut dyn SceneManager,
+        ui: &mut Ui,
+        state: &mut SceState,
+        delta_sec: f32,
+    ) -> bool {
+        let start_position = match self.start_position {
+            Some(position) => position,
+            None => return true,
+        };
+
+        self.elapsed = (self.elapsed + delta_sec).min(self.duration);
+        let t = if self.duration > 0. {
+            self.elapsed / self.duration
+        } else {
+            1.
+        };
+
+        let entity = match scene_manager
+            .core_scene_mut_or_fail()
+            .get_root_object_mut(self.object_id)
+        {
+            Some(entity) => entity,
+            None => return true,
+        };
+
+        entity
+            .transform_mut()
+            .set_position(&Vec3::lerp(&start_position, &self.position, ease(t)));
+
+        let completed = t >= 1.;
+        if completed {
+            let transform = *entity.transform().matrix();
+            state
+                .global_state_mut()
+                .persistent_state_mut()
+                .object_state_mut(self.object_id)
+                .transform = Some(transform);
+        }
+
+        completed
+    }
+}
+
+impl SceCommandObjectMove {
+    pub fn new(object_id: i32, x: f32, y: f32, z: f32, duration: f32) -> Self {
+        Self {
+            object_id,
+            position: Vec3::new(x, y, z),
+            duration,
+            elapsed: 0.,
+            start_position: None,
+        }
+    }
+}
@@ -0,0 +1,117 @@
+use crate::directors::easing::ease;
+use crate::directors::sce_vm::{SceCommand, SceState};
+use crate::directors::SceneManagerExtensions;
+use crate::scene::{CvdAnimationState, CvdModelEntity};
+use imgui::Ui;
+use radiance::{
+    math::{Mat44, Vec3},
+    scene::{CoreEntity, Entity, SceneManager},
+};
+
+#[derive(Clone)]
+enum DoorMotion {
+    None,
+    // Doors with a cvd model open with their own animation
+    Animation,
+    // The others swing around their vertical axis
+    Swing { start_transform: Mat44 },
+}
+
+#[derive(Clone)]
+pub struct SceCommandOpenDoor {
+    object_id: i32,
+    motion: DoorMotion,
+    elapsed: f32,
+}
+
+impl SceCommandOpenDoor {
+    const SWING_ANGLE: f32 = std::f32::consts::FRAC_PI_2;
+    const SWING_DURATION: f32 = 1.;
+}
+
+impl SceCommand for SceCommandOpenDoor {
+    fn initialize(&mut self, scene_manager: &mut dyn SceneManager, state: &mut SceState) {
+        let entity = match scene_manager
+            .core_scene_mut_or_fail()
+            .get_root_object_mut(self.object_id)
+        {
+            Some(entity) => entity,
+            None => {
+                log::warn!("OpenDoor: object {} not found", self.object_id);
+                return;
+            }
+        };
+
+        self.motion = match entity.downcast_mut::<CoreEntity<CvdModelEntity>>() {
+            Some(cvd) if cvd.animation_duration() > 0. => {
+                cvd.play(false);
+                DoorMotion::Animation
+            }
+            _ => DoorMotion::Swing {
+                start_transform: *entity.transform().matrix(),
+            },
+        };
+    }
+
+    fn update(
+        &mut self,
+        scene_manager: &mut dyn SceneManager,
+        ui: &mut Ui,
+        state: &mut SceState,
+        delta_sec: f32,
+    ) -> bool {
+        let entity = match scene_manager
+            .core_scene_mut_or_fail()
+            .get_root_object_mut(self.object_id)
+        {
+            Some(entity) => entity,
+            None => return true,
+        };
+
+        let completed = match &self.motion {
+            DoorMotion::None => return true,
+            DoorMotion::Animation => entity
+                .downcast_ref::<CoreEntity<CvdModelEntity>>()
+                .map_or(true, |cvd| {
+                    cvd.animation_state() != CvdAnimationState::Playing
+                }),
+            DoorMotion::Swing { start_transform } => {
+                self.elapsed = (self.elapsed + delta_sec).min(Self::SWING_DURATION);
+                let t = self.elapsed / Self::SWING_DURATION;
+                entity
+                    .transform_mut()
+                    .set_matrix(*start_transform)
+                    .rotate_axis_angle_local(&Vec3::UP, Self::SWING_ANGLE * ease(t));
+                t >= 1.
+            }
+        };
+
+        if completed {
+            state
+                .global_state_mut()
+                .persistent_state_mut()
+                .object_state_mut(self.object_id)
+                .opened = true;
+            if let DoorMotion::Swing { .. } = self.motion {
+                let transform = *entity.transform().matrix();
+                state
+                    .global_state_mut()
+                    .persistent_state_mut()
+                    .object_state_mut(self.object_id)
+                    .transform = Some(transform);
+            }
+        }
+
+        completed
+    }
+}
+
+impl SceCommandOpenDoor {
+    pub fn new(object_id: i32) -> Self {
+        Self {
+            object_id,
+            motion: DoorMotion::None,
+            elapsed: 0.,
+        }
+    }
+}
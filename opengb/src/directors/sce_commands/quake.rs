@@ -0,0 +1,34 @@
+use crate::directors::sce_vm::{SceCommand, SceState};
+use imgui::Ui;
+use radiance::scene::SceneManager;
+
+#[derive(Clone)]
+pub struct SceCommandQuake {
+    amplitude: f32,
+    duration: f32,
+}
+
+impl SceCommand for SceCommandQuake {
+    fn update(
+        &mut self,
+        scene_manager: &mut dyn SceneManager,
+        ui: &mut Ui,
+        state: &mut SceState,
+        delta_sec: f32,
+    ) -> bool {
+        state
+            .global_state_mut()
+            .camera_controller_mut()
+            .shake(self.amplitude, self.duration);
+        true
+    }
+}
+
+impl SceCommandQuake {
+    pub fn new(amplitude: f32, duration: f32) -> Self {
+        Self {
+            amplitude,
+            duration,
+        }
+    }
+}
@@ -0,0 +1,115 @@
+use crate::directors::easing::ease;
+use crate::directors::sce_vm::{SceCommand, SceState};
+use crate::directors::SceneManagerExtensions;
+use imgui::Ui;
+use radiance::{
+    math::{Mat44, Vec3},
+    rendering::ImguiTexture,
+    scene::{Camera, Entity, Scene, SceneManager},
+};
+use std::rc::Rc;
+
+#[derive(Clone)]
+pub struct SceCommandRoleEmote {
+    role_id: i32,
+    emote_id: i32,
+    image: Option<Rc<ImguiTexture>>,
+    elapsed: f32,
+}
+
+impl SceCommandRoleEmote {
+    const DURATION: f32 = 1.5;
+    const POP_DURATION: f32 = 0.2;
+    const FADE_DURATION: f32 = 0.3;
+
+    // How far above the feet of the role the emote is
+    const HEAD_HEIGHT: f32 = 110.;
+
+    const BOB_SPEED: f32 = 8.;
+    const BOB_HEIGHT: f32 = 3.;
+}
+
+impl SceCommand for SceCommandRoleEmote {
+    fn initialize(&mut self, scene_manager: &mut dyn SceneManager, state: &mut SceState) {
+        self.image = state.global_state_mut().load_emote_image(self.emote_id);
+    }
+
+    fn update(
+        &mut self,
+        scene_manager: &mut dyn SceneManager,
+        ui: &mut Ui,
+        state: &mut SceState,
+        delta_sec: f32,
+    ) -> bool {
+        let image = match self.image.as_ref() {
+            Some(image) => image,
+            None => return true,
+        };
+
+        self.elapsed += delta_sec;
+        if self.elapsed >= Self::DURATION {
+            return true;
+        }
+
+        let head = Vec3::add(
+            &scene_manager
+                .get_resolved_role_entity(state, self.role_id)
+                .transform()
+                .position(),
+            &Vec3::new(0., Self::HEAD_HEIGHT, 0.),
+        );
+        let camera = scene_manager.core_scene_mut_or_fail().camera();
+        let [x, y] = match project(camera, &head, ui.io().display_size) {
+            Some(point) => point,
+            None => return false,
+        };
+
+        let scale = ease((self.elapsed / Self::POP_DURATION).min(1.));
+        let alpha = ((Self::DURATION - self.elapsed) / Self::FADE_DURATION).min(1.);
+        let width = image.width() as f32 * scale;
+        let height = image.height() as f32 * scale;
+        let bottom = y - (self.elapsed * Self::BOB_SPEED).sin().abs() * Self::BOB_HEIGHT;
+        ui.get_background_draw_list()
+            .add_image(
+                image.texture_id(),
+                [x - width / 2., bottom - height],
+                [x + width / 2., bottom],
+            )
+            .col([1., 1., 1., alpha])
+            .build();
+
+        false
+    }
+}
+
+impl SceCommandRoleEmote {
+    pub fn new(role_id: i32, emote_id: i32) -> Self {
+        Self {
+            role_id,
+            emote_id,
+            image: None,
+            elapsed: 0.,
+        }
+    }
+}
+
+// Where the position is on the screen, or `None` if it's behind the camera
+fn project(camera: &Camera, position: &Vec3, display_size: [f32; 2]) -> Option<[f32; 2]> {
+    let view_projection = Mat44::multiplied(
+        camera.projection_matrix(),
+        &Mat44::inversed(camera.transform().matrix()),
+    );
+    let v = [position.x, position.y, position.z, 1.];
+    let clip: Vec<f32> = (0..4)
+        .map(|i| (0..4).map(|j| view_projection[i][j] * v[j]).sum())
+        .collect();
+    if clip[3] <= std::f32::EPSILON {
+        return None;
+    }
+
+    let [width, height] = display_size;
+    Some([
+        (clip[0] / clip[3] + 1.) / 2. * width,
+        (1. - clip[1] / clip[3]) / 2. * height,
+    ])
+}
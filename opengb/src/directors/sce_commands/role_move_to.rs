@@ -1,6 +1,7 @@
 use crate::directors::sce_vm::{SceCommand, SceState};
 
 use crate::directors::SceneManagerExtensions;
+use crate::scene::RolePath;
 use imgui::Ui;
 use radiance::scene::Entity;
 use radiance::{math::Vec3, scene::SceneManager};
@@ -11,10 +12,22 @@ pub struct SceCommandRoleMoveTo {
     nav_x: f32,
     nav_z: f32,
     unknown: i32,
+    path: RolePath,
 }
 
 impl SceCommand for SceCommandRoleMoveTo {
     fn initialize(&mut self, scene_manager: &mut dyn SceneManager, state: &mut SceState) {
+        let position = scene_manager
+            .get_resolved_role_entity(state, self.role_id)
+            .transform()
+            .position();
+        let scene = scene_manager.core_scene_mut_or_fail();
+        let to = scene.nav_coord_to_scene_coord(self.nav_x, self.nav_z);
+        self.path = RolePath::new(scene.find_path(&position, &to).unwrap_or_else(|| {
+            log::debug!("No path found to {:?}, moving straight", to);
+            vec![to]
+        }));
+
         scene_manager
             .get_resolved_role_entity_mut(state, self.role_id)
             .run();
@@ -29,23 +42,17 @@ impl SceCommand for SceCommandRoleMoveTo {
     ) -> bool {
         const SPEED: f32 = 175.;
 
-        let scene = scene_manager.core_scene_mut_or_fail();
-        let to = scene.nav_coord_to_scene_coord(self.nav_x, self.nav_z);
         let entity = scene_manager.get_resolved_role_entity_mut(state, self.role_id);
         let position = entity.transform().position();
-        let step = SPEED * delta_sec;
-        let remain = Vec3::sub(&to, &position);
-        let completed = remain.norm() < step;
-        let new_position = if completed {
-            to
-        } else {
-            Vec3::add(&position, &Vec3::dot(step, &Vec3::normalized(&remain)))
-        };
+        let new_position = self.path.advance(&position, SPEED * delta_sec);
+        let completed = self.path.completed();
+
+        let look_at = Vec3::new(new_position.x, position.y, new_position.z);
+        if Vec3::sub(&look_at, &position).norm() > std::f32::EPSILON {
+            entity.transform_mut().look_at(&look_at);
+        }
 
-        entity
-            .transform_mut()
-            .look_at(&Vec3::new(to.x, position.y, to.z))
-            .set_position(&new_position);
+        entity.transform_mut().set_position(&new_position);
 
         if completed {
             entity.idle();
@@ -61,6 +68,7 @@ impl SceCommandRoleMoveTo {
             nav_x: nav_x as f32,
             nav_z: nav_z as f32,
             unknown,
+            path: RolePath::new(vec![]),
         }
     }
 }
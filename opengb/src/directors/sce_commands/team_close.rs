@@ -0,0 +1,29 @@
+use crate::directors::party::change_party;
+use crate::directors::sce_vm::{SceCommand, SceState};
+use imgui::Ui;
+use radiance::scene::SceneManager;
+
+#[derive(Clone)]
+pub struct SceCommandTeamClose {}
+
+impl SceCommand for SceCommandTeamClose {
+    fn update(
+        &mut self,
+        scene_manager: &mut dyn SceneManager,
+        ui: &mut Ui,
+        state: &mut SceState,
+        delta_sec: f32,
+    ) -> bool {
+        change_party(scene_manager, state.global_state_mut(), |party| {
+            party.set_followers_visible(false)
+        });
+
+        true
+    }
+}
+
+impl SceCommandTeamClose {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
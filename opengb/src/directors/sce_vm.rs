@@ -1,6 +1,9 @@
 use super::{global_state::GlobalState, sce_commands::*};
-use crate::{asset_manager::AssetManager, loaders::sce_loader::SceFile};
-use encoding::{DecoderTrap, Encoding};
+use crate::{
+    asset_manager::AssetManager,
+    loaders::sce_loader::SceFile,
+    sce::{FromSceArg, SceInstruction},
+};
 use imgui::*;
 use log::{debug, error};
 use radiance::scene::{Director, SceneManager};
@@ -92,46 +95,29 @@ impl SceVm {
 }
 
 macro_rules! command {
-    ($self: ident, $cmd_name: ident $(, $param_names: ident : $param_types: ident)* $(,)*) => {
-        command! {@reverse_arg $self, $cmd_name $(, $param_names : $param_types)* [] [$(, $param_names)*]}
-    };
-
-    (@reverse_arg $self: ident, $cmd_name: ident, $param_name: ident : $param_type: ident $(, $param_names: ident : $param_types: ident)* [$(, $reversed_names: ident : $reversed_types: ident)*] [$(, $asc_param_names :ident)*]) => {
-        command! {@reverse_arg $self, $cmd_name $(, $param_names : $param_types)* [, $param_name : $param_type $(, $reversed_names : $reversed_types)*] [$(, $asc_param_names)*]}
-    };
-
-    (@reverse_arg $self: ident, $cmd_name: ident [$(, $reversed_names: ident : $reversed_types: ident)*] [$(, $asc_param_names :ident)*]) => {
-        command! {@inner $self, $cmd_name $(, $reversed_names : $reversed_types)* [] [$(, $asc_param_names)*]}
-    };
-
-    (@inner $self: ident, $cmd_name: ident $(, $param_names: ident : $param_types: ident)* [$(, $evaluated: ident)*] [$(, $asc_param_names :ident)*]) => {
+    ($inst: ident, $cmd_name: ident $(, $param_names: ident : $param_types: ty)* $(,)*) => {
         {
-            $(let $param_names = data_read::$param_types($self);)*
-            debug!(concat!("{} ", $(concat!("{", stringify!($asc_param_names), ":?} "), )*), stringify!($cmd_name), $($asc_param_names=$asc_param_names, )*);
-            Some(Box::new($cmd_name::new($($asc_param_names),*)))
+            let mut args = $inst.args.iter();
+            $(let $param_names: $param_types = match args.next().and_then(FromSceArg::from_arg) {
+                Some(arg) => arg,
+                None => {
+                    error!(concat!("Bad argument ", stringify!($param_names), " for ", stringify!($cmd_name)));
+                    return None;
+                }
+            };)*
+            debug!(concat!("{} ", $(concat!("{", stringify!($param_names), ":?} "), )*), stringify!($cmd_name), $($param_names=$param_names, )*);
+            Some(Box::new($cmd_name::new($($param_names),*)))
         }
     };
 }
 
+// The arguments are already consumed by the decoding, so the types that
+// follow the instruction only document the command
 macro_rules! nop_command {
-    ($self: ident $(, $param_types: ident)* $(,)*) => {
-        nop_command! {@reverse_arg $self $(, $param_types)* []}
-    };
-
-    (@reverse_arg $self: ident, $param_type: ident $(, $param_types: ident)* [$(, $reversed_types: ident)*]) => {
-        nop_command! {@reverse_arg $self $(, $param_types)* [, $param_type $(, $reversed_types)*]}
-    };
-
-    (@reverse_arg $self: ident [$(, $reversed_types: ident)*]) => {
-        nop_command! {@inner $self $(, $reversed_types)*}
-    };
-
-    (@inner $self: ident $(, $param_types: ident)*) => {
-        {
-            $(let _ = data_read::$param_types($self); )*
-            Some(Box::new(SceCommandNop::new()))
-        }
-    };
+    ($inst: ident $(, $arg_types: ident)*) => {{
+        debug!("{} {:?} (nop)", $inst.opcode.name, $inst.args);
+        Some(Box::new(SceCommandNop::new()))
+    }};
 }
 
 pub struct SceProcContext {
@@ -197,67 +183,90 @@ impl SceProcContext {
             return None;
         }
 
-        match data_read::i32(self) {
+        let proc = self.sce.procs.get(&self.proc_id).unwrap();
+        let inst = match SceInstruction::decode(&proc.inst, self.program_counter) {
+            Ok(inst) => inst,
+            Err(e) => {
+                error!("Unsupported command, stopping the proc: {}", e);
+                self.program_counter = proc.inst.len();
+                return None;
+            }
+        };
+
+        self.program_counter = inst.next_offset();
+        let command = Self::create_command(&inst);
+        if command.is_none() {
+            error!("Cannot run {}, stopping the proc", inst.opcode.name);
+            self.program_counter = proc.inst.len();
+        }
+
+        command
+    }
+
+    // Builds the command that runs the instruction, or `None` if the VM
+    // doesn't support it or its arguments don't match the command
+    fn create_command(inst: &SceInstruction) -> Option<Box<dyn SceCommand>> {
+        match inst.code {
             1 => {
                 // Idle
-                command!(self, SceCommandIdle, length: f32)
+                command!(inst, SceCommandIdle, length: f32)
             }
             2 => {
                 // ScriptRunMode
-                command!(self, SceCommandScriptRunMode, mode: i32)
+                command!(inst, SceCommandScriptRunMode, mode: i32)
             }
             3 => {
                 // Goto
-                command!(self, SceCommandGoto, offset: u32)
+                command!(inst, SceCommandGoto, offset: u32)
             }
             5 => {
                 // FOP
-                command!(self, SceCommandFop, op: i32)
+                command!(inst, SceCommandFop, op: i32)
             }
             6 | 65542 => {
                 // GT
-                command!(self, SceCommandGt, var: i16, value: i32)
+                command!(inst, SceCommandGt, var: i16, value: i32)
             }
             7 | 65543 => {
                 // LS
-                command!(self, SceCommandLs, var: i16, value: i32)
+                command!(inst, SceCommandLs, var: i16, value: i32)
             }
             8 | 65544 => {
                 // EQ
-                command!(self, SceCommandEq, var: i16, value: i32)
+                command!(inst, SceCommandEq, var: i16, value: i32)
             }
             9 | 65545 => {
                 // NEQ
-                command!(self, SceCommandNeq, var: i16, value: i32)
+                command!(inst, SceCommandNeq, var: i16, value: i32)
             }
             10 | 65546 => {
                 // GEQ
-                command!(self, SceCommandGeq, var: i16, value: i32)
+                command!(inst, SceCommandGeq, var: i16, value: i32)
             }
             11 | 65547 => {
                 // LEQ
-                command!(self, SceCommandLeq, var: i16, value: i32)
+                command!(inst, SceCommandLeq, var: i16, value: i32)
             }
             12 => {
                 // TestGoto
-                command!(self, SceCommandTestGoto, offset: u32)
+                command!(inst, SceCommandTestGoto, offset: u32)
             }
             13 | 65549 => {
                 // Let
-                command!(self, SceCommandLet, var: i16, value: i32)
+                command!(inst, SceCommandLet, var: i16, value: i32)
             }
             16 => {
                 //Call
-                command!(self, SceCommandCall, proc_id: u32)
+                command!(inst, SceCommandCall, proc_id: u32)
             }
             17 | 65553 => {
                 // Rnd
-                command!(self, SceCommandRnd, var: i16, value: i32)
+                command!(inst, SceCommandRnd, var: i16, value: i32)
             }
             20 => {
                 // RolePathTo
                 command!(
-                    self,
+                    inst,
                     SceCommandRolePathTo,
                     role_id: i32,
                     x: i32,
@@ -267,62 +276,82 @@ impl SceProcContext {
             }
             21 => {
                 // RoleSetPos
-                command!(self, SceCommandRoleSetPos, role_id: i32, x: i32, y: i32)
+                command!(inst, SceCommandRoleSetPos, role_id: i32, x: i32, y: i32)
             }
             22 => {
                 // RoleShowAction
                 command!(
-                    self,
+                    inst,
                     SceCommandRoleShowAction,
                     role_id: i32,
-                    action_name: string,
+                    action_name: String,
                     repeat_mode: i32,
                 )
             }
             23 => {
                 // RoleSetFace
-                command!(self, SceCommandRoleSetFace, role_id: i32, direction: i32)
+                command!(inst, SceCommandRoleSetFace, role_id: i32, direction: i32)
             }
             24 => {
                 // RoleTurnFace
-                command!(self, SceCommandRoleTurnFace, role_id: i32, degree: f32)
+                command!(inst, SceCommandRoleTurnFace, role_id: i32, degree: f32)
             }
             27 => {
                 // RoleInput
-                command!(self, SceCommandRoleInput, enable_input: i32)
+                command!(inst, SceCommandRoleInput, enable_input: i32)
             }
             28 => {
                 // RoleActive
-                command!(self, SceCommandRoleActive, role: i32, active: i32)
+                command!(inst, SceCommandRoleActive, role: i32, active: i32)
             }
             32 => {
                 // CameraPush
-                nop_command!(self, f32, f32, i32)
+                command!(
+                    inst,
+                    SceCommandCameraPush,
+                    distance: f32,
+                    duration: f32,
+                    sync: i32
+                )
             }
             33 => {
                 // CameraRotate
-                nop_command!(self, f32, f32, f32, i32)
+                command!(
+                    inst,
+                    SceCommandCameraRotate,
+                    yaw: f32,
+                    pitch: f32,
+                    duration: f32,
+                    sync: i32
+                )
             }
             34 => {
                 // CameraMove
                 command!(
-                    self,
+                    inst,
                     SceCommandCameraMove,
                     position_x: f32,
                     position_y: f32,
                     position_z: f32,
-                    unknown_1: f32,
-                    unknown_2: f32
+                    duration: f32,
+                    sync: f32
                 )
             }
             35 => {
                 //CameraWag
-                nop_command!(self, f32, f32, f32, i32)
+                command!(
+                    inst,
+                    SceCommandCameraWag,
+                    yaw: f32,
+                    pitch: f32,
+                    duration: f32,
+                    sync: i32
+                )
             }
             36 => {
                 // CameraSet
                 command!(
-                    self,
+                    inst,
                     SceCommandCameraSet,
                     y_rot: f32,
                     x_rot: f32,
@@ -334,79 +363,85 @@ impl SceProcContext {
             }
             37 => {
                 // CameraDefault
-                command!(self, SceCommandCameraDefault, unknown: i32)
+                command!(inst, SceCommandCameraDefault, unknown: i32)
             }
             46 => {
                 // AddItem
-                nop_command!(self, i32, i32)
+                command!(inst, SceCommandAddItem, item_id: i32, count: i32)
             }
             62 => {
                 // Dlg
-                command!(self, SceCommandDlg, text: string)
+                command!(inst, SceCommandDlg, text: String)
             }
             63 => {
                 // LoadScene
-                command!(self, SceCommandLoadScene, name: string, sub_name: string)
+                command!(inst, SceCommandLoadScene, name: String, sub_name: String)
             }
             65 => {
                 // DlgSel
-                command!(self, SceCommandDlgSel, list: list)
+                command!(inst, SceCommandDlgSel, list: Vec<String>)
             }
             66 | 65602 => {
                 // GetDlgSel
-                command!(self, SceCommandGetDlgSel, var: i16)
+                command!(inst, SceCommandGetDlgSel, var: i16)
             }
             67 => {
                 // DlgFace
-                nop_command!(self, i32, string, i32)
+                command!(
+                    inst,
+                    SceCommandDlgFace,
+                    role_id: i32,
+                    face_name: String,
+                    side: i32
+                )
             }
             68 => {
                 // Note
-                nop_command!(self, string)
+                command!(inst, SceCommandNote, text: String)
             }
             69 => {
                 // FadeOut
-                nop_command!(self)
+                command!(inst, SceCommandFadeOut)
             }
             70 => {
                 // FadeIn
-                nop_command!(self)
+                command!(inst, SceCommandFadeIn)
             }
             71 => {
                 // RoleStop
-                nop_command!(self, i32)
+                nop_command!(inst, i32)
             }
             72 => {
                 // RoleEmote
-                nop_command!(self, i32, i32)
+                command!(inst, SceCommandRoleEmote, role_id: i32, emote_id: i32)
             }
             78 => {
-                command!(self, SceCommandHaveItem, item_id: i32)
+                command!(inst, SceCommandHaveItem, item_id: i32)
             }
             79 => {
                 // PlaySound
-                command!(self, SceCommandPlaySound, name: string, repeat: i32)
+                command!(inst, SceCommandPlaySound, name: String, repeat: i32)
             }
             85 => {
                 // ObjectActive
-                command!(self, SceCommandObjectActive, object_id: i32, active: i32)
+                command!(inst, SceCommandObjectActive, object_id: i32, active: i32)
             }
             86 => {
                 // Caption
-                nop_command!(self, string, i32)
+                command!(inst, SceCommandCaption, text: String, duration: i32)
             }
             87 => {
                 // OpenDoor
-                nop_command!(self, i32)
+                command!(inst, SceCommandOpenDoor, object_id: i32)
             }
             88 => {
                 // HY_Mode
-                nop_command!(self, i32)
+                nop_command!(inst, i32)
             }
             89 => {
                 // HY_FLY
                 command!(
-                    self,
+                    inst,
                     SceCommandHyFly,
                     position_x: f32,
                     position_y: f32,
@@ -415,60 +450,68 @@ impl SceProcContext {
             }
             90 => {
                 // ObjectMove
-                nop_command!(self, i32, f32, f32, f32, f32)
+                command!(
+                    inst,
+                    SceCommandObjectMove,
+                    object_id: i32,
+                    x: f32,
+                    y: f32,
+                    z: f32,
+                    duration: f32
+                )
             }
             104 => {
                 // APPR Entry
-                nop_command!(self)
+                nop_command!(inst)
             }
             108 | 65644 => {
                 // Get Appr
-                command!(self, SceCommandGetAppr, var: i16)
+                command!(inst, SceCommandGetAppr, var: i16)
             }
             115 => {
                 // Movie
-                nop_command!(self, string)
+                nop_command!(inst, string)
             }
             116 => {
                 // SetRoleTexture
-                nop_command!(self, i32, string)
+                nop_command!(inst, i32, string)
             }
             118 => {
                 // Quake
-                nop_command!(self, f32, f32)
+                command!(inst, SceCommandQuake, amplitude: f32, duration: f32)
             }
             124 => {
                 // Trigger
-                nop_command!(self, i32)
+                nop_command!(inst, i32)
             }
             133 => {
                 // Music
-                command!(self, SceCommandMusic, name: string, unknown: i32)
+                command!(inst, SceCommandMusic, name: String, unknown: i32)
             }
             134 => {
                 // StopMusic
-                command!(self, SceCommandStopMusic)
+                command!(inst, SceCommandStopMusic)
             }
             142 => {
                 // CEft_Pos
-                nop_command!(self, f32, f32, f32)
+                nop_command!(inst, f32, f32, f32)
             }
             143 => {
                 // CEft
-                nop_command!(self, i32)
+                nop_command!(inst, i32)
             }
             148 => {
                 // CEft_Load
-                nop_command!(self, i32)
+                nop_command!(inst, i32)
             }
             150 => {
                 // LoadAct
-                nop_command!(self, i32, string)
+                nop_command!(inst, i32, string)
             }
             201 => {
                 // RolePathOut
                 command!(
-                    self,
+                    inst,
                     SceCommandRolePathOut,
                     role_id: i32,
                     x: i32,
@@ -478,16 +521,16 @@ impl SceProcContext {
             }
             202 => {
                 // InTeam
-                nop_command!(self, i32, i32)
+                command!(inst, SceCommandInTeam, role_id: i32, in_team: i32)
             }
             204 => {
                 // RoleCtrl
-                command!(self, SceCommandRoleCtrl, role_id: i32)
+                command!(inst, SceCommandRoleCtrl, role_id: i32)
             }
             207 => {
                 // RoleActAutoStand
                 command!(
-                    self,
+                    inst,
                     SceCommandRoleActAutoStand,
                     role_id: i32,
                     auto_play_idle: i32
@@ -495,28 +538,28 @@ impl SceProcContext {
             }
             208 => {
                 // RoleMoveBack
-                command!(self, SceCommandRoleMoveBack, role_id: i32, speed: f32)
+                command!(inst, SceCommandRoleMoveBack, role_id: i32, speed: f32)
             }
             209 => {
                 // RoleFaceRole
-                command!(self, SceCommandRoleFaceRole, role_id: i32, role_id2: i32)
+                command!(inst, SceCommandRoleFaceRole, role_id: i32, role_id2: i32)
             }
             210 => {
                 // RoleTurnFaceA
-                command!(self, SceCommandRoleSetFace, role_id: i32, direction: i32)
+                command!(inst, SceCommandRoleSetFace, role_id: i32, direction: i32)
             }
             211 => {
                 // TeamOpenA
-                nop_command!(self)
+                command!(inst, SceCommandTeamOpen)
             }
             212 => {
                 // TeamCloseA
-                nop_command!(self)
+                command!(inst, SceCommandTeamClose)
             }
             214 => {
                 // RoleMovTo
                 command!(
-                    self,
+                    inst,
                     SceCommandRoleMoveTo,
                     role_id: i32,
                     x: i32,
@@ -526,16 +569,15 @@ impl SceProcContext {
             }
             221 => {
                 // RoleEndAction
-                nop_command!(self, i32)
+                nop_command!(inst, i32)
             }
             250 => {
                 // CameraFree
-                nop_command!(self, i32)
+                command!(inst, SceCommandCameraFree, free: i32)
             }
             default => {
                 error!("Unsupported command: {}", default);
-                self.put(4);
-                panic!();
+                None
             }
         }
     }
@@ -544,71 +586,12 @@ impl SceProcContext {
         self.program_counter = addr as usize;
     }
 
-    fn put(&mut self, count: usize) {
-        self.program_counter -= count;
-    }
-
-    fn read_string(&mut self, len: usize) -> String {
-        let proc = self.sce.procs.get(&self.proc_id).unwrap();
-        let end = self.program_counter + len;
-        let text = encoding::all::GBK
-            .decode(
-                &proc.inst[self.program_counter..end - 1],
-                DecoderTrap::Ignore,
-            )
-            .unwrap();
-        self.program_counter = end;
-        text
-    }
-
-    fn read(&mut self, count: usize) -> &[u8] {
-        let proc = self.sce.procs.get(&self.proc_id).unwrap();
-        let ret = &proc.inst[self.program_counter..self.program_counter + count];
-        self.program_counter += count;
-        ret
-    }
-
     fn proc_completed(&self) -> bool {
         let proc = self.sce.procs.get(&self.proc_id).unwrap();
         self.program_counter >= proc.inst.len()
     }
 }
 
-mod data_read {
-    use byteorder::{LittleEndian, ReadBytesExt};
-
-    pub(super) fn i16(context: &mut super::SceProcContext) -> i16 {
-        context.read(2).read_i16::<LittleEndian>().unwrap()
-    }
-
-    pub(super) fn i32(context: &mut super::SceProcContext) -> i32 {
-        context.read(4).read_i32::<LittleEndian>().unwrap()
-    }
-
-    pub(super) fn u32(context: &mut super::SceProcContext) -> u32 {
-        context.read(4).read_u32::<LittleEndian>().unwrap()
-    }
-
-    pub(super) fn f32(context: &mut super::SceProcContext) -> f32 {
-        context.read(4).read_f32::<LittleEndian>().unwrap()
-    }
-
-    pub(super) fn string(context: &mut super::SceProcContext) -> String {
-        let len = context.read(2).read_u16::<LittleEndian>().unwrap();
-        context.read_string(len as usize)
-    }
-
-    pub(super) fn list(context: &mut super::SceProcContext) -> Vec<String> {
-        let len = context.read(2).read_u16::<LittleEndian>().unwrap();
-        (0..len)
-            .map(|_| {
-                let _ = context.read(1);
-                string(context)
-            })
-            .collect()
-    }
-}
-
 pub struct SceExecutionContext {
     sce: Rc<SceFile>,
     proc_stack: Vec<SceProcContext>,
@@ -0,0 +1,60 @@
+use crate::asset_manager::AssetManager;
+use radiance::rendering::ImguiTexture;
+use std::{collections::HashMap, rc::Rc};
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum FaceSide {
+    Left,
+    Right,
+}
+
+/// The portrait that the dialog box shows next to the text.
+#[derive(Clone)]
+pub struct DlgFace {
+    pub image: Rc<ImguiTexture>,
+    pub side: FaceSide,
+}
+
+/// The images of the portraits and the emotes. Every image is loaded only
+/// once and kept, as imgui can only draw the textures that are alive.
+pub struct UiImages {
+    faces: HashMap<String, Option<Rc<ImguiTexture>>>,
+    emotes: HashMap<i32, Option<Rc<ImguiTexture>>>,
+}
+
+impl UiImages {
+    pub fn new() -> Self {
+        Self {
+            faces: HashMap::new(),
+            emotes: HashMap::new(),
+        }
+    }
+
+    pub fn face(&mut self, asset_mgr: &AssetManager, face_name: &str) -> Option<Rc<ImguiTexture>> {
+        self.faces
+            .entry(face_name.to_string())
+            .or_insert_with(|| {
+                let image = asset_mgr.load_face_image(face_name).map(Rc::new);
+                if image.is_none() {
+                    log::warn!("Cannot load the face {}", face_name);
+                }
+
+                image
+            })
+            .clone()
+    }
+
+    pub fn emote(&mut self, asset_mgr: &AssetManager, emote_id: i32) -> Option<Rc<ImguiTexture>> {
+        self.emotes
+            .entry(emote_id)
+            .or_insert_with(|| {
+                let image = asset_mgr.load_emote_image(emote_id).map(Rc::new);
+                if image.is_none() {
+                    log::warn!("Cannot load the emote {}", emote_id);
+                }
+
+                image
+            })
+            .clone()
+    }
+}
@@ -0,0 +1,53 @@
+use crate::loaders::LoaderError;
+use std::{
+    error::Error,
+    fmt::{Display, Formatter},
+};
+
+#[derive(Debug)]
+pub enum ExportError {
+    LoaderError(LoaderError),
+    ImageError(image::ImageError),
+    IoError(std::io::Error),
+    InvalidModel(String),
+}
+
+impl Display for ExportError {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            ExportError::LoaderError(e) => write!(f, "{}", e),
+            ExportError::ImageError(e) => write!(f, "bad texture: {}", e),
+            ExportError::IoError(e) => write!(f, "{}", e),
+            ExportError::InvalidModel(message) => write!(f, "invalid model: {}", message),
+        }
+    }
+}
+
+impl Error for ExportError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ExportError::LoaderError(e) => Some(e),
+            ExportError::ImageError(e) => Some(e),
+            ExportError::IoError(e) => Some(e),
+            ExportError::InvalidModel(_) => None,
+        }
+    }
+}
+
+impl From<LoaderError> for ExportError {
+    fn from(e: LoaderError) -> Self {
+        ExportError::LoaderError(e)
+    }
+}
+
+impl From<image::ImageError> for ExportError {
+    fn from(e: image::ImageError) -> Self {
+        ExportError::ImageError(e)
+    }
+}
+
+impl From<std::io::Error> for ExportError {
+    fn from(e: std::io::Error) -> Self {
+        ExportError::IoError(e)
+    }
+}
@@ -0,0 +1,233 @@
+use byteorder::{LittleEndian, WriteBytesExt};
+use serde_json::{json, Map, Value};
+use std::io::{self, Write};
+
+const ARRAY_BUFFER: u32 = 34962;
+const ELEMENT_ARRAY_BUFFER: u32 = 34963;
+const UNSIGNED_INT: u32 = 5125;
+const FLOAT: u32 = 5126;
+
+const GLB_MAGIC: u32 = 0x4654_6C67;
+const GLB_CHUNK_JSON: u32 = 0x4E4F_534A;
+const GLB_CHUNK_BIN: u32 = 0x004E_4942;
+
+/// Collects the glTF objects together with a single binary buffer, and writes
+/// them as a self-contained glb file.
+#[derive(Default)]
+pub struct GltfBuilder {
+    buffer: Vec<u8>,
+    buffer_views: Vec<Value>,
+    accessors: Vec<Value>,
+    images: Vec<Value>,
+    textures: Vec<Value>,
+    materials: Vec<Value>,
+    meshes: Vec<Value>,
+    nodes: Vec<Value>,
+    scene_nodes: Vec<usize>,
+    animations: Vec<Value>,
+}
+
+impl GltfBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an accessor of `data.len() / components` elements. `min` and `max`
+    /// are always filled in as they are required for positions and
+    /// animation inputs.
+    pub fn add_f32_accessor(
+        &mut self,
+        data: &[f32],
+        components: usize,
+        is_vertex_attribute: bool,
+    ) -> usize {
+        let mut bytes = Vec::with_capacity(data.len() * 4);
+        for f in data {
+            bytes.write_f32::<LittleEndian>(*f).unwrap();
+        }
+
+        let target = if is_vertex_attribute {
+            Some(ARRAY_BUFFER)
+        } else {
+            None
+        };
+        let view = self.add_buffer_view(&bytes, target);
+
+        let mut min = vec![f32::MAX; components];
+        let mut max = vec![f32::MIN; components];
+        for element in data.chunks(components) {
+            for (i, f) in element.iter().enumerate() {
+                min[i] = min[i].min(*f);
+                max[i] = max[i].max(*f);
+            }
+        }
+
+        push(
+            &mut self.accessors,
+            json!({
+                "bufferView": view,
+                "componentType": FLOAT,
+                "count": data.len() / components,
+                "type": accessor_type(components),
+                "min": min,
+                "max": max,
+            }),
+        )
+    }
+
+    pub fn add_indices(&mut self, indices: &[u32]) -> usize {
+        let mut bytes = Vec::with_capacity(indices.len() * 4);
+        for i in indices {
+            bytes.write_u32::<LittleEndian>(*i).unwrap();
+        }
+
+        let view = self.add_buffer_view(&bytes, Some(ELEMENT_ARRAY_BUFFER));
+        push(
+            &mut self.accessors,
+            json!({
+                "bufferView": view,
+                "componentType": UNSIGNED_INT,
+                "count": indices.len(),
+                "type": "SCALAR",
+            }),
+        )
+    }
+
+    /// Embeds a png image and returns the index of the texture using it.
+    pub fn add_png_texture(&mut self, name: &str, png: &[u8]) -> usize {
+        let view = self.add_buffer_view(png, None);
+        let image = push(
+            &mut self.images,
+            json!({
+                "name": name,
+                "bufferView": view,
+                "mimeType": "image/png",
+            }),
+        );
+
+        push(&mut self.textures, json!({ "source": image }))
+    }
+
+    pub fn add_material(&mut self, material: Value) -> usize {
+        push(&mut self.materials, material)
+    }
+
+    pub fn add_mesh(&mut self, mesh: Value) -> usize {
+        push(&mut self.meshes, mesh)
+    }
+
+    pub fn add_node(&mut self, node: Value) -> usize {
+        push(&mut self.nodes, node)
+    }
+
+    /// Adds a node that sits at the root of the scene.
+    pub fn add_scene_node(&mut self, node: Value) -> usize {
+        let index = self.add_node(node);
+        self.scene_nodes.push(index);
+        index
+    }
+
+    pub fn add_animation(&mut self, animation: Value) -> usize {
+        push(&mut self.animations, animation)
+    }
+
+    pub fn to_json(&self) -> Value {
+        let mut root = Map::new();
+        root.insert(
+            "asset".to_string(),
+            json!({ "version": "2.0", "generator": "opengb" }),
+        );
+        root.insert("scene".to_string(), json!(0));
+        root.insert("scenes".to_string(), json!([{ "nodes": self.scene_nodes }]));
+
+        if !self.buffer.is_empty() {
+            root.insert(
+                "buffers".to_string(),
+                json!([{ "byteLength": self.buffer.len() }]),
+            );
+        }
+
+        let arrays = [
+            ("bufferViews", &self.buffer_views),
+            ("accessors", &self.accessors),
+            ("images", &self.images),
+            ("textures", &self.textures),
+            ("materials", &self.materials),
+            ("meshes", &self.meshes),
+            ("nodes", &self.nodes),
+            ("animations", &self.animations),
+        ];
+        for (name, array) in &arrays {
+            if !array.is_empty() {
+                root.insert(name.to_string(), Value::Array(array.to_vec()));
+            }
+        }
+
+        Value::Object(root)
+    }
+
+    pub fn write_glb<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        let mut json = serde_json::to_vec(&self.to_json())?;
+        pad(&mut json, b' ');
+        let mut bin = self.buffer.clone();
+        pad(&mut bin, 0);
+
+        let mut length = 12 + 8 + json.len();
+        if !bin.is_empty() {
+            length += 8 + bin.len();
+        }
+
+        writer.write_u32::<LittleEndian>(GLB_MAGIC)?;
+        writer.write_u32::<LittleEndian>(2)?;
+        writer.write_u32::<LittleEndian>(length as u32)?;
+
+        writer.write_u32::<LittleEndian>(json.len() as u32)?;
+        writer.write_u32::<LittleEndian>(GLB_CHUNK_JSON)?;
+        writer.write_all(&json)?;
+
+        if !bin.is_empty() {
+            writer.write_u32::<LittleEndian>(bin.len() as u32)?;
+            writer.write_u32::<LittleEndian>(GLB_CHUNK_BIN)?;
+            writer.write_all(&bin)?;
+        }
+
+        Ok(())
+    }
+
+    fn add_buffer_view(&mut self, bytes: &[u8], target: Option<u32>) -> usize {
+        pad(&mut self.buffer, 0);
+        let mut view = json!({
+            "buffer": 0,
+            "byteOffset": self.buffer.len(),
+            "byteLength": bytes.len(),
+        });
+
+        if let Some(target) = target {
+            view["target"] = json!(target);
+        }
+
+        self.buffer.extend_from_slice(bytes);
+        push(&mut self.buffer_views, view)
+    }
+}
+
+fn push(array: &mut Vec<Value>, value: Value) -> usize {
+    array.push(value);
+    array.len() - 1
+}
+
+fn pad(data: &mut Vec<u8>, byte: u8) {
+    while data.len() % 4 != 0 {
+        data.push(byte);
+    }
+}
+
+fn accessor_type(components: usize) -> &'static str {
+    match components {
+        1 => "SCALAR",
+        2 => "VEC2",
+        3 => "VEC3",
+        4 => "VEC4",
+        _ => panic!("Unsupported accessor components: {}", components),
+    }
+}
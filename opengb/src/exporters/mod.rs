@@ -0,0 +1,17 @@
+pub use error::ExportError;
+pub use gltf::GltfBuilder;
+
+pub mod mv3_exporter;
+pub mod pol_exporter;
+
+mod error;
+mod gltf;
+
+use image::ImageOutputFormat;
+
+/// Converts a texture in any format that the `image` crate reads to png.
+pub fn encode_png(data: &[u8]) -> Result<Vec<u8>, ExportError> {
+    let mut png = vec![];
+    image::load_from_memory(data)?.write_to(&mut png, ImageOutputFormat::Png)?;
+    Ok(png)
+}
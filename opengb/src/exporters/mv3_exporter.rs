@@ -0,0 +1,232 @@
+use super::{encode_png, ExportError, GltfBuilder};
+use crate::loaders::mv3_loader::*;
+use crate::utilities::StoreExt2;
+use mini_fs::MiniFs;
+use serde_json::{json, Value};
+use std::{
+    collections::HashMap,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+// The same rate as `RoleAnimation` plays the frames at
+const MV3_TICKS_PER_SECOND: f32 = 4580.;
+const MV3_VERTEX_SCALE: f32 = 0.01562;
+
+/// Exports a mv3 file in the vfs as glb, together with the texture next to it.
+pub fn mv3_export_gltf_from_file<P: AsRef<Path>, W: Write>(
+    vfs: &MiniFs,
+    path: P,
+    writer: &mut W,
+) -> Result<(), ExportError> {
+    let mv3 = mv3_load_from_file(vfs, path.as_ref())?;
+    let texture = match mv3_texture_path(&mv3, path.as_ref()) {
+        Some(texture_path) => match vfs.read_to_end(&texture_path) {
+            Ok(data) => Some(data),
+            Err(e) => {
+                log::warn!("Cannot read texture {:?}: {}", texture_path, e);
+                None
+            }
+        },
+        None => None,
+    };
+
+    mv3_export_gltf(&mv3, texture.as_deref(), writer)
+}
+
+/// Every model becomes a mesh whose morph targets are the frames, played by
+/// an animation keyed on `Mv3Frame::timestamp`. The texture is embedded as png.
+pub fn mv3_export_gltf<W: Write>(
+    mv3: &Mv3File,
+    texture: Option<&[u8]>,
+    writer: &mut W,
+) -> Result<(), ExportError> {
+    let mut gltf = GltfBuilder::new();
+
+    let mut pbr = json!({ "metallicFactor": 0.0 });
+    if let Some(data) = texture {
+        let png = encode_png(data)?;
+        let name = mv3
+            .textures
+            .first()
+            .and_then(mv3_texture_name)
+            .unwrap_or_default();
+        let texture = gltf.add_png_texture(&name, &png);
+        pbr["baseColorTexture"] = json!({ "index": texture });
+    }
+
+    let material = gltf.add_material(json!({ "pbrMetallicRoughness": pbr }));
+
+    let mut channels = vec![];
+    let mut samplers = vec![];
+    for (i, model) in mv3.models.iter().enumerate() {
+        if model.frames.is_empty() {
+            return Err(ExportError::InvalidModel(format!(
+                "model {} has no frames",
+                i
+            )));
+        }
+
+        let mut primitives = vec![];
+        for mesh in model.meshes.iter().filter(|m| !m.triangles.is_empty()) {
+            primitives.push(add_mv3_primitive(&mut gltf, model, mesh, material)?);
+        }
+
+        if primitives.is_empty() {
+            continue;
+        }
+
+        let mut mesh = json!({
+            "name": format!("model_{}", i),
+            "primitives": primitives,
+        });
+        if model.frames.len() > 1 {
+            mesh["weights"] = json!(frame_weights(model.frames.len(), 0));
+        }
+
+        let mesh = gltf.add_mesh(mesh);
+        let node = gltf.add_scene_node(json!({
+            "name": format!("model_{}", i),
+            "mesh": mesh,
+        }));
+
+        if model.frames.len() > 1 {
+            samplers.push(add_mv3_sampler(&mut gltf, model, i)?);
+            channels.push(json!({
+                "sampler": samplers.len() - 1,
+                "target": { "node": node, "path": "weights" },
+            }));
+        }
+    }
+
+    if !channels.is_empty() {
+        gltf.add_animation(json!({
+            "name": "action",
+            "channels": channels,
+            "samplers": samplers,
+        }));
+    }
+
+    gltf.write_glb(writer)?;
+    Ok(())
+}
+
+pub fn mv3_texture_path(mv3: &Mv3File, mv3_path: &Path) -> Option<PathBuf> {
+    mv3_texture_paths(mv3, mv3_path).into_iter().next()?
+}
+
+// The first frame is the base mesh and every frame, including the first one,
+// is a morph target holding the displacement from the base.
+fn add_mv3_primitive(
+    gltf: &mut GltfBuilder,
+    model: &Mv3Model,
+    mesh: &Mv3Mesh,
+    material: usize,
+) -> Result<Value, ExportError> {
+    let mut index_map = HashMap::new();
+    let mut vertices = vec![];
+    let mut indices = vec![];
+    for t in &mesh.triangles {
+        for (&i, &j) in t.indices.iter().zip(&t.texcoord_indices) {
+            if i as u32 >= model.vertex_per_frame || j as usize >= model.texcoords.len() {
+                return Err(ExportError::InvalidModel(format!(
+                    "vertex {} or texcoord {} is out of range",
+                    i, j
+                )));
+            }
+
+            let index = *index_map.entry((i, j)).or_insert_with(|| {
+                vertices.push((i as usize, j as usize));
+                vertices.len() as u32 - 1
+            });
+            indices.push(index);
+        }
+    }
+
+    let frame_positions = |frame: &Mv3Frame| -> Vec<f32> {
+        vertices
+            .iter()
+            .flat_map(|&(i, _)| {
+                let v = &frame.vertices[i];
+                vec![
+                    v.x as f32 * MV3_VERTEX_SCALE,
+                    v.y as f32 * MV3_VERTEX_SCALE,
+                    v.z as f32 * MV3_VERTEX_SCALE,
+                ]
+            })
+            .collect()
+    };
+
+    let base = frame_positions(&model.frames[0]);
+    let texcoords: Vec<f32> = vertices
+        .iter()
+        .flat_map(|&(_, j)| vec![model.texcoords[j].u, -model.texcoords[j].v])
+        .collect();
+
+    let mut primitive = json!({
+        "attributes": {
+            "POSITION": gltf.add_f32_accessor(&base, 3, true),
+            "TEXCOORD_0": gltf.add_f32_accessor(&texcoords, 2, true),
+        },
+        "indices": gltf.add_indices(&indices),
+        "material": material,
+    });
+
+    if model.frames.len() > 1 {
+        let targets: Vec<Value> = model
+            .frames
+            .iter()
+            .map(|frame| {
+                let displacement: Vec<f32> = frame_positions(frame)
+                    .iter()
+                    .zip(&base)
+                    .map(|(p, b)| p - b)
+                    .collect();
+                json!({ "POSITION": gltf.add_f32_accessor(&displacement, 3, true) })
+            })
+            .collect();
+        primitive["targets"] = json!(targets);
+    }
+
+    Ok(primitive)
+}
+
+// Each keyframe sets the weight of its own frame to 1, so that the linear
+// interpolation blends two frames the same way as `RoleAnimation` does.
+fn add_mv3_sampler(
+    gltf: &mut GltfBuilder,
+    model: &Mv3Model,
+    model_index: usize,
+) -> Result<Value, ExportError> {
+    if model
+        .frames
+        .windows(2)
+        .any(|w| w[0].timestamp >= w[1].timestamp)
+    {
+        return Err(ExportError::InvalidModel(format!(
+            "the frame timestamps of model {} are not increasing",
+            model_index
+        )));
+    }
+
+    let times: Vec<f32> = model
+        .frames
+        .iter()
+        .map(|f| f.timestamp as f32 / MV3_TICKS_PER_SECOND)
+        .collect();
+    let weights: Vec<f32> = (0..model.frames.len())
+        .flat_map(|i| frame_weights(model.frames.len(), i))
+        .collect();
+
+    Ok(json!({
+        "input": gltf.add_f32_accessor(&times, 1, false),
+        "output": gltf.add_f32_accessor(&weights, 1, false),
+        "interpolation": "LINEAR",
+    }))
+}
+
+fn frame_weights(frame_count: usize, frame: usize) -> Vec<f32> {
+    let mut weights = vec![0.; frame_count];
+    weights[frame] = 1.;
+    weights
+}
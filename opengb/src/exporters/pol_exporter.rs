@@ -0,0 +1,344 @@
+use super::{encode_png, ExportError, GltfBuilder};
+use crate::loaders::pol_loader::*;
+use crate::utilities::StoreExt2;
+use mini_fs::MiniFs;
+use serde_json::{json, Value};
+use std::{
+    collections::HashMap,
+    io::{self, Write},
+    path::Path,
+};
+
+// The shaders discard the texels whose alpha is 0 even for opaque materials
+const POL_ALPHA_CUTOFF: f32 = 1. / 255.;
+
+/// Exports a pol file in the vfs as glb, together with the textures next to it.
+pub fn pol_export_gltf_from_file<P: AsRef<Path>, W: Write>(
+    vfs: &MiniFs,
+    path: P,
+    writer: &mut W,
+) -> Result<(), ExportError> {
+    let pol = pol_load_from_file(vfs, path.as_ref())?;
+    pol_export_gltf(&pol, writer, |name| {
+        let texture_path = pol_texture_path(vfs, path.as_ref(), name);
+        match vfs.read_to_end(&texture_path) {
+            Ok(data) => Some(data),
+            Err(e) => {
+                log::warn!("Cannot read texture {:?}: {}", texture_path, e);
+                None
+            }
+        }
+    })
+}
+
+/// Every mesh becomes a glTF mesh with a primitive per `PolMaterialInfo`.
+///
+/// A material with a single texture samples it with `TEXCOORD_0`. A material
+/// with two textures is a lightmapped one like in `LightMapMaterialDef`: the
+/// first texture is the lightmap on `TEXCOORD_0` and the second one is the
+/// base color on `TEXCOORD_1`. glTF has no lightmap slot, so the lightmap is
+/// kept as its own image and referenced by both `occlusionTexture` and
+/// `extras.lightmap`.
+///
+/// `load_texture` returns the content of a texture by its name in the pol
+/// file, and the textures are embedded as png.
+pub fn pol_export_gltf<W: Write, F: FnMut(&str) -> Option<Vec<u8>>>(
+    pol: &PolFile,
+    writer: &mut W,
+    mut load_texture: F,
+) -> Result<(), ExportError> {
+    let mut gltf = GltfBuilder::new();
+    let mut textures: HashMap<String, Option<usize>> = HashMap::new();
+    let mut add_texture = |gltf: &mut GltfBuilder, name: &str| -> Result<_, ExportError> {
+        if let Some(texture) = textures.get(name) {
+            return Ok(*texture);
+        }
+
+        let texture = match load_texture(name) {
+            Some(data) => Some(gltf.add_png_texture(name, &encode_png(&data)?)),
+            None => None,
+        };
+
+        textures.insert(name.to_string(), texture);
+        Ok(texture)
+    };
+
+    for (i, mesh) in pol.meshes.iter().enumerate() {
+        if mesh.vertices.is_empty() {
+            continue;
+        }
+
+        let has_tex_coord2 = has_tex_coord2(mesh);
+        let attributes = add_pol_attributes(&mut gltf, mesh);
+        let mut primitives = vec![];
+        for material in mesh.material_info.iter() {
+            if material.triangles.is_empty() {
+                continue;
+            }
+
+            let (base, lightmap) = match material.texture_names.as_slice() {
+                [] => (None, None),
+                [base] => (Some(base), None),
+                [lightmap, base, ..] => (Some(base), Some(lightmap)),
+            };
+
+            let base = match base {
+                Some(name) => add_texture(&mut gltf, name)?,
+                None => None,
+            };
+
+            let lightmap = match lightmap {
+                Some(name) => add_texture(&mut gltf, name)?,
+                None => None,
+            };
+
+            let base_tex_coord = if lightmap.is_some() && has_tex_coord2 {
+                1
+            } else {
+                0
+            };
+
+            let material_index =
+                gltf.add_material(pol_material(material, base, base_tex_coord, lightmap));
+
+            let mut indices = vec![];
+            for t in &material.triangles {
+                for &index in &t.indices {
+                    if index as usize >= mesh.vertices.len() {
+                        return Err(ExportError::InvalidModel(format!(
+                            "vertex {} of mesh {} is out of range",
+                            index, i
+                        )));
+                    }
+
+                    indices.push(index as u32);
+                }
+            }
+
+            primitives.push(json!({
+                "attributes": attributes.clone(),
+                "indices": gltf.add_indices(&indices),
+                "material": material_index,
+            }));
+        }
+
+        if primitives.is_empty() {
+            continue;
+        }
+
+        let mesh = gltf.add_mesh(json!({
+            "name": format!("mesh_{}", i),
+            "primitives": primitives,
+        }));
+        gltf.add_scene_node(json!({
+            "name": format!("mesh_{}", i),
+            "mesh": mesh,
+        }));
+    }
+
+    gltf.write_glb(writer)?;
+    Ok(())
+}
+
+/// OBJ has only one set of texcoords, so lightmapped materials use the
+/// second set where their base color is, and the lightmaps are dropped. The
+/// materials refer to the textures by `pol_png_file_name`.
+pub fn pol_export_obj<W: Write, M: Write>(
+    pol: &PolFile,
+    mtl_file_name: &str,
+    obj: &mut W,
+    mtl: &mut M,
+) -> io::Result<()> {
+    writeln!(obj, "mtllib {}", mtl_file_name)?;
+
+    let mut vertex_base = 1;
+    let mut tex_coord_base = 1;
+    let mut normal_base = 1;
+    for (i, mesh) in pol.meshes.iter().enumerate() {
+        writeln!(obj, "o mesh_{}", i)?;
+        for v in &mesh.vertices {
+            writeln!(obj, "v {} {} {}", v.position.x, v.position.y, v.position.z)?;
+        }
+
+        for v in &mesh.vertices {
+            writeln!(obj, "vt {} {}", v.tex_coord.u, 1. - v.tex_coord.v)?;
+        }
+
+        let has_tex_coord2 = has_tex_coord2(mesh);
+        if has_tex_coord2 {
+            for tex_coord2 in mesh.vertices.iter().filter_map(|v| v.tex_coord2.as_ref()) {
+                writeln!(obj, "vt {} {}", tex_coord2.u, 1. - tex_coord2.v)?;
+            }
+        }
+
+        let has_normal = has_normal(mesh);
+        if has_normal {
+            for normal in mesh.vertices.iter().filter_map(|v| v.normal) {
+                let [x, y, z] = normalize(normal);
+                writeln!(obj, "vn {} {} {}", x, y, z)?;
+            }
+        }
+
+        for (j, material) in mesh.material_info.iter().enumerate() {
+            let name = format!("mesh_{}_{}", i, j);
+            write_pol_mtl(mtl, &name, material)?;
+
+            writeln!(obj, "usemtl {}", name)?;
+            let tex_coord_base = if material.texture_names.len() > 1 && has_tex_coord2 {
+                tex_coord_base + mesh.vertices.len()
+            } else {
+                tex_coord_base
+            };
+
+            for t in &material.triangles {
+                let mut face = String::from("f");
+                for &index in &t.indices {
+                    let index = index as usize;
+                    if index >= mesh.vertices.len() {
+                        return Err(io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            format!("vertex {} of mesh {} is out of range", index, i),
+                        ));
+                    }
+
+                    face.push_str(&format!(
+                        " {}/{}",
+                        vertex_base + index,
+                        tex_coord_base + index
+                    ));
+                    if has_normal {
+                        face.push_str(&format!("/{}", normal_base + index));
+                    }
+                }
+
+                writeln!(obj, "{}", face)?;
+            }
+        }
+
+        vertex_base += mesh.vertices.len();
+        tex_coord_base += mesh.vertices.len() * if has_tex_coord2 { 2 } else { 1 };
+        if has_normal {
+            normal_base += mesh.vertices.len();
+        }
+    }
+
+    Ok(())
+}
+
+pub fn pol_png_file_name(texture_name: &str) -> String {
+    Path::new(texture_name)
+        .with_extension("png")
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+fn write_pol_mtl<M: Write>(mtl: &mut M, name: &str, material: &PolMaterialInfo) -> io::Result<()> {
+    writeln!(mtl, "newmtl {}", name)?;
+    writeln!(mtl, "Kd 1 1 1")?;
+
+    let base = match material.texture_names.as_slice() {
+        [] => None,
+        [base] => Some(base),
+        [_, base, ..] => Some(base),
+    };
+
+    if let Some(base) = base {
+        writeln!(mtl, "map_Kd {}", pol_png_file_name(base))?;
+        if material.use_alpha != 0 {
+            writeln!(mtl, "map_d {}", pol_png_file_name(base))?;
+        }
+    }
+
+    writeln!(mtl)
+}
+
+fn add_pol_attributes(gltf: &mut GltfBuilder, mesh: &PolMesh) -> Value {
+    let positions: Vec<f32> = mesh
+        .vertices
+        .iter()
+        .flat_map(|v| vec![v.position.x, v.position.y, v.position.z])
+        .collect();
+    let tex_coords: Vec<f32> = mesh
+        .vertices
+        .iter()
+        .flat_map(|v| vec![v.tex_coord.u, v.tex_coord.v])
+        .collect();
+
+    let mut attributes = json!({
+        "POSITION": gltf.add_f32_accessor(&positions, 3, true),
+        "TEXCOORD_0": gltf.add_f32_accessor(&tex_coords, 2, true),
+    });
+
+    if has_tex_coord2(mesh) {
+        let tex_coords2: Vec<f32> = mesh
+            .vertices
+            .iter()
+            .filter_map(|v| v.tex_coord2.as_ref())
+            .flat_map(|t| vec![t.u, t.v])
+            .collect();
+        attributes["TEXCOORD_1"] = json!(gltf.add_f32_accessor(&tex_coords2, 2, true));
+    }
+
+    if has_normal(mesh) {
+        let normals: Vec<f32> = mesh
+            .vertices
+            .iter()
+            .filter_map(|v| v.normal)
+            .flat_map(|n| normalize(n).to_vec())
+            .collect();
+        attributes["NORMAL"] = json!(gltf.add_f32_accessor(&normals, 3, true));
+    }
+
+    attributes
+}
+
+fn pol_material(
+    material: &PolMaterialInfo,
+    base: Option<usize>,
+    base_tex_coord: usize,
+    lightmap: Option<usize>,
+) -> Value {
+    let mut pbr = json!({ "metallicFactor": 0.0 });
+    if let Some(base) = base {
+        pbr["baseColorTexture"] = json!({ "index": base, "texCoord": base_tex_coord });
+    }
+
+    let mut gltf_material = json!({
+        "name": material.texture_names.join(" "),
+        "pbrMetallicRoughness": pbr,
+    });
+
+    if material.use_alpha != 0 {
+        gltf_material["alphaMode"] = json!("BLEND");
+    } else {
+        gltf_material["alphaMode"] = json!("MASK");
+        gltf_material["alphaCutoff"] = json!(POL_ALPHA_CUTOFF);
+    }
+
+    if let Some(lightmap) = lightmap {
+        gltf_material["occlusionTexture"] = json!({ "index": lightmap, "texCoord": 0 });
+        gltf_material["extras"] = json!({
+            "lightmap": { "index": lightmap, "texCoord": 0 },
+        });
+    }
+
+    gltf_material
+}
+
+fn has_tex_coord2(mesh: &PolMesh) -> bool {
+    mesh.vertices.iter().all(|v| v.tex_coord2.is_some())
+}
+
+fn has_normal(mesh: &PolMesh) -> bool {
+    mesh.vertices.iter().all(|v| v.normal.is_some())
+}
+
+fn normalize(n: [f32; 3]) -> [f32; 3] {
+    let length = (n[0] * n[0] + n[1] * n[1] + n[2] * n[2]).sqrt();
+    if length > 0. {
+        [n[0] / length, n[1] / length, n[2] / length]
+    } else {
+        [0., 1., 0.]
+    }
+}
@@ -2,11 +2,17 @@
 #![feature(arbitrary_self_types)]
 #![allow(unused_variables)]
 
+#[cfg(feature = "engine")]
 pub mod asset_manager;
 pub mod config;
 pub mod cpk;
+#[cfg(feature = "engine")]
 pub mod directors;
+pub mod exporters;
 pub mod loaders;
+#[cfg(feature = "engine")]
 pub mod material;
+pub mod sce;
+#[cfg(feature = "engine")]
 pub mod scene;
 pub mod utilities;
@@ -0,0 +1,25 @@
+use super::{BinaryReader, LoaderError};
+use mini_fs::MiniFs;
+use serde::Serialize;
+use std::path::Path;
+
+/// An `.ani` file that sits next to a cvd file with the same name. Its layout
+/// hasn't been figured out yet, so the content is kept as is.
+#[derive(Debug, Clone, Serialize)]
+pub struct AniFile {
+    pub size: u32,
+    pub unknown: Vec<u8>,
+}
+
+pub fn ani_load_from_file<P: AsRef<Path>>(vfs: &MiniFs, path: P) -> Result<AniFile, LoaderError> {
+    let mut reader = BinaryReader::open(vfs, path)?;
+    let size = reader.len() as u32;
+    let unknown = reader.read_u8_vec(size as usize)?;
+
+    Ok(AniFile { size, unknown })
+}
+
+/// The path of the `.ani` file that belongs to a cvd file.
+pub fn ani_path_for_cvd<P: AsRef<Path>>(cvd_path: P) -> std::path::PathBuf {
+    cvd_path.as_ref().with_extension("ani")
+}
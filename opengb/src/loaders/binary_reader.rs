@@ -0,0 +1,202 @@
+use super::LoaderError;
+use crate::utilities::{decode_gbk, StoreExt2};
+use byteorder::{LittleEndian, ReadBytesExt};
+use mini_fs::MiniFs;
+use std::{
+    io::{self, Cursor, Read},
+    path::{Path, PathBuf},
+};
+
+/// Reads little-endian data from an in-memory file, reporting every failure
+/// as a `LoaderError` with the file path and the offset where it happened.
+pub struct BinaryReader {
+    cursor: Cursor<Vec<u8>>,
+    path: PathBuf,
+}
+
+impl BinaryReader {
+    pub fn new<P: AsRef<Path>>(data: Vec<u8>, path: P) -> Self {
+        Self {
+            cursor: Cursor::new(data),
+            path: path.as_ref().to_owned(),
+        }
+    }
+
+    pub fn open<P: AsRef<Path>>(vfs: &MiniFs, path: P) -> Result<Self, LoaderError> {
+        let data = vfs
+            .read_to_end(path.as_ref())
+            .map_err(|error| LoaderError::IoError {
+                path: path.as_ref().to_owned(),
+                error,
+            })?;
+
+        Ok(Self::new(data, path))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn position(&self) -> u64 {
+        self.cursor.position()
+    }
+
+    pub fn len(&self) -> u64 {
+        self.cursor.get_ref().len() as u64
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cursor.get_ref().is_empty()
+    }
+
+    pub fn seek(&mut self, target: u64) -> Result<(), LoaderError> {
+        if target > self.len() {
+            return Err(LoaderError::InvalidOffset {
+                path: self.path.clone(),
+                offset: self.position(),
+                target,
+            });
+        }
+
+        self.cursor.set_position(target);
+        Ok(())
+    }
+
+    pub fn read_magic(&mut self, expected: &[&[u8; 4]]) -> Result<[u8; 4], LoaderError> {
+        let offset = self.position();
+        let mut magic = [0u8; 4];
+        self.read_exact(&mut magic)?;
+        if expected.iter().any(|m| **m == magic) {
+            Ok(magic)
+        } else {
+            Err(LoaderError::BadMagic {
+                path: self.path.clone(),
+                offset,
+                magic: magic.to_vec(),
+            })
+        }
+    }
+
+    pub fn read_exact(&mut self, buf: &mut [u8]) -> Result<(), LoaderError> {
+        self.read_with(|c| c.read_exact(buf))
+    }
+
+    pub fn read_u8(&mut self) -> Result<u8, LoaderError> {
+        self.read_with(|c| c.read_u8())
+    }
+
+    pub fn read_i8(&mut self) -> Result<i8, LoaderError> {
+        self.read_with(|c| c.read_i8())
+    }
+
+    pub fn read_u16(&mut self) -> Result<u16, LoaderError> {
+        self.read_with(|c| c.read_u16::<LittleEndian>())
+    }
+
+    pub fn read_i16(&mut self) -> Result<i16, LoaderError> {
+        self.read_with(|c| c.read_i16::<LittleEndian>())
+    }
+
+    pub fn read_u32(&mut self) -> Result<u32, LoaderError> {
+        self.read_with(|c| c.read_u32::<LittleEndian>())
+    }
+
+    pub fn read_i32(&mut self) -> Result<i32, LoaderError> {
+        self.read_with(|c| c.read_i32::<LittleEndian>())
+    }
+
+    pub fn read_f32(&mut self) -> Result<f32, LoaderError> {
+        self.read_with(|c| c.read_f32::<LittleEndian>())
+    }
+
+    pub fn read_u16_into(&mut self, buf: &mut [u16]) -> Result<(), LoaderError> {
+        self.read_with(|c| c.read_u16_into::<LittleEndian>(buf))
+    }
+
+    pub fn read_f32_into(&mut self, buf: &mut [f32]) -> Result<(), LoaderError> {
+        self.read_with(|c| c.read_f32_into::<LittleEndian>(buf))
+    }
+
+    pub fn read_u8_vec(&mut self, size: usize) -> Result<Vec<u8>, LoaderError> {
+        self.ensure_remaining(size)?;
+        let mut buf = vec![0u8; size];
+        self.read_exact(&mut buf)?;
+        Ok(buf)
+    }
+
+    pub fn read_w_vec(&mut self, count: usize) -> Result<Vec<u16>, LoaderError> {
+        self.ensure_remaining(count * 2)?;
+        let mut buf = vec![0u16; count];
+        self.read_u16_into(&mut buf)?;
+        Ok(buf)
+    }
+
+    pub fn read_dw_vec(&mut self, count: usize) -> Result<Vec<u32>, LoaderError> {
+        self.ensure_remaining(count * 4)?;
+        let mut buf = vec![0u32; count];
+        self.read_with(|c| c.read_u32_into::<LittleEndian>(&mut buf))?;
+        Ok(buf)
+    }
+
+    pub fn read_f32_vec(&mut self, count: usize) -> Result<Vec<f32>, LoaderError> {
+        self.ensure_remaining(count * 4)?;
+        let mut buf = vec![0f32; count];
+        self.read_f32_into(&mut buf)?;
+        Ok(buf)
+    }
+
+    /// Reads a fixed size GBK string that may be terminated by `\0`. The bytes
+    /// that aren't valid GBK are skipped, as some names in the game files
+    /// have stray bytes in them.
+    pub fn read_string(&mut self, size: usize) -> Result<String, LoaderError> {
+        let buf = self.read_u8_vec(size)?;
+        Ok(decode_gbk(&buf))
+    }
+
+    pub fn unsupported_version(&self, offset: u64, version: u32) -> LoaderError {
+        LoaderError::UnsupportedVersion {
+            path: self.path.clone(),
+            offset,
+            version,
+        }
+    }
+
+    pub fn unsupported_feature(&self, offset: u64, feature: &str) -> LoaderError {
+        LoaderError::UnsupportedFeature {
+            path: self.path.clone(),
+            offset,
+            feature: feature.to_string(),
+        }
+    }
+
+    fn ensure_remaining(&self, size: usize) -> Result<(), LoaderError> {
+        if self.position() + size as u64 > self.len() {
+            Err(LoaderError::TruncatedData {
+                path: self.path.clone(),
+                offset: self.position(),
+            })
+        } else {
+            Ok(())
+        }
+    }
+
+    fn read_with<T, F: FnOnce(&mut Cursor<Vec<u8>>) -> io::Result<T>>(
+        &mut self,
+        read: F,
+    ) -> Result<T, LoaderError> {
+        let offset = self.position();
+        read(&mut self.cursor).map_err(|error| {
+            self.cursor.set_position(offset);
+            match error.kind() {
+                io::ErrorKind::UnexpectedEof => LoaderError::TruncatedData {
+                    path: self.path.clone(),
+                    offset,
+                },
+                _ => LoaderError::IoError {
+                    path: self.path.clone(),
+                    error,
+                },
+            }
+        })
+    }
+}
@@ -1,13 +1,8 @@
-use super::calc_vertex_size;
-use crate::utilities::ReadExt;
-use byteorder::{LittleEndian, ReadBytesExt};
-use encoding::{DecoderTrap, Encoding};
-use mini_fs::{MiniFs, StoreExt};
+use super::{calc_vertex_size, BinaryReader, LoaderError};
+use mini_fs::MiniFs;
 use radiance::math::{Mat44, Quaternion, Vec2, Vec3};
 use serde::Serialize;
-use std::error::Error;
-use std::io::{BufReader, Read};
-use std::path::{Path, PathBuf};
+use std::path::Path;
 
 #[derive(Debug, Serialize)]
 pub struct CvdVertex {
@@ -38,7 +33,7 @@ pub struct CvdMesh {
     pub frame_count: u32,
     pub vertex_count: u32,
     pub frames: Vec<Vec<CvdVertex>>,
-    pub unknown_data: Vec<f32>,
+    pub frame_timestamps: Vec<f32>, // When each frame of the morph animation is reached
     pub material_count: u32,
     pub materials: Vec<CvdMaterial>,
 }
@@ -124,33 +119,20 @@ pub struct CvdFile {
     pub models: Vec<CvdModelNode>,
 }
 
-pub fn cvd_load_from_file<P: AsRef<Path>>(
-    vfs: &MiniFs,
-    path: P,
-) -> Result<CvdFile, Box<dyn Error>> {
-    let mut reader = BufReader::new(vfs.open(&path).unwrap());
-    let mut magic = [0u8; 4];
-    reader.read_exact(&mut magic).unwrap();
-
-    let unknown_float = match magic {
-        [0x63, 0x76, 0x64, 0x73] => 0.5, // "cvds"
-        [0x63, 0x76, 0x64, 0x66] => 0.4, // "cvdf"
-        _ => panic!("Not a valid cvd file"),
+pub fn cvd_load_from_file<P: AsRef<Path>>(vfs: &MiniFs, path: P) -> Result<CvdFile, LoaderError> {
+    let mut reader = BinaryReader::open(vfs, &path)?;
+    let magic = reader.read_magic(&[b"cvds", b"cvdf"])?;
+    let unknown_float = match &magic {
+        b"cvds" => 0.5,
+        _ => 0.4,
     };
 
-    let mut ani_path: PathBuf = path.as_ref().to_path_buf();
-    ani_path.set_extension("ani");
-    if ani_path.exists() {
-        println!("Found ani file {:?} which isn't supported yet", ani_path);
-    }
-
-    let model_count = reader.read_u32::<LittleEndian>().unwrap();
+    let model_count = reader.read_u32()?;
 
     let mut models = vec![];
     for _i in 0..model_count {
-        let model = cvd_load_model(&mut reader, unknown_float).unwrap();
-        if model.is_some() {
-            models.push(model.unwrap());
+        if let Some(model) = cvd_load_model(&mut reader, unknown_float)? {
+            models.push(model);
         }
     }
 
@@ -162,26 +144,24 @@ pub fn cvd_load_from_file<P: AsRef<Path>>(
 }
 
 pub fn cvd_load_model(
-    reader: &mut dyn Read,
+    reader: &mut BinaryReader,
     unknown_float: f32,
-) -> Result<Option<CvdModelNode>, Box<dyn Error>> {
-    let unknown_byte = reader.read_u8().unwrap();
+) -> Result<Option<CvdModelNode>, LoaderError> {
+    let unknown_byte = reader.read_u8()?;
 
     let mut model = None;
     if unknown_byte > 0 {
-        let position_keyframes = read_position_keyframes(reader);
-        let rotation_keyframes = read_rotation_keyframes(reader);
-        let scale_keyframes = read_scale_keyframes(reader);
+        let position_keyframes = read_position_keyframes(reader)?;
+        let rotation_keyframes = read_rotation_keyframes(reader)?;
+        let scale_keyframes = read_scale_keyframes(reader)?;
 
-        let scale_factor = reader.read_f32::<LittleEndian>().unwrap();
-        let mesh = cvd_load_mesh(reader, unknown_float).unwrap();
+        let scale_factor = reader.read_f32()?;
+        let mesh = cvd_load_mesh(reader, unknown_float)?;
 
         let mut mat = Mat44::new_zero();
-        reader
-            .read_f32_into::<LittleEndian>(unsafe {
-                std::mem::transmute::<&mut [[f32; 4]; 4], &mut [f32; 16]>(mat.floats_mut())
-            })
-            .unwrap();
+        reader.read_f32_into(unsafe {
+            std::mem::transmute::<&mut [[f32; 4]; 4], &mut [f32; 16]>(mat.floats_mut())
+        })?;
 
         model = Some(CvdModel {
             unknown_byte,
@@ -193,13 +173,14 @@ pub fn cvd_load_model(
         });
     }
 
-    let children_count = reader.read_u32::<LittleEndian>().unwrap();
+    let children_count = reader.read_u32()?;
     let mut models = None;
     if children_count > 0 {
         models = Some(vec![]);
         for _i in 0..children_count {
-            let model = cvd_load_model(reader, unknown_float).unwrap().unwrap();
-            models.as_mut().unwrap().push(model);
+            if let Some(model) = cvd_load_model(reader, unknown_float)? {
+                models.as_mut().unwrap().push(model);
+            }
         }
     }
 
@@ -209,22 +190,25 @@ pub fn cvd_load_model(
     }))
 }
 
-pub fn cvd_load_mesh(reader: &mut dyn Read, unknown_float: f32) -> Result<CvdMesh, Box<dyn Error>> {
-    let frame_count = reader.read_u32::<LittleEndian>().unwrap();
-    let vertex_count = reader.read_u32::<LittleEndian>().unwrap();
+pub fn cvd_load_mesh(
+    reader: &mut BinaryReader,
+    unknown_float: f32,
+) -> Result<CvdMesh, LoaderError> {
+    let frame_count = reader.read_u32()?;
+    let vertex_count = reader.read_u32()?;
     let vertex_size = calc_vertex_size(19);
     let mut frames = vec![];
     for _i in 0..frame_count {
         let mut vertices = vec![];
         for _j in 0..vertex_count {
-            let tx = reader.read_f32::<LittleEndian>().unwrap();
-            let ty = reader.read_f32::<LittleEndian>().unwrap();
-            let nx = reader.read_f32::<LittleEndian>().unwrap();
-            let ny = reader.read_f32::<LittleEndian>().unwrap();
-            let nz = reader.read_f32::<LittleEndian>().unwrap();
-            let px = reader.read_f32::<LittleEndian>().unwrap();
-            let py = reader.read_f32::<LittleEndian>().unwrap();
-            let pz = reader.read_f32::<LittleEndian>().unwrap();
+            let tx = reader.read_f32()?;
+            let ty = reader.read_f32()?;
+            let nx = reader.read_f32()?;
+            let ny = reader.read_f32()?;
+            let nz = reader.read_f32()?;
+            let px = reader.read_f32()?;
+            let py = reader.read_f32()?;
+            let pz = reader.read_f32()?;
             vertices.push(CvdVertex {
                 position: Vec3::new(px, pz, -py),
                 normal: Vec3::new(nx, ny, nz),
@@ -235,39 +219,28 @@ pub fn cvd_load_mesh(reader: &mut dyn Read, unknown_float: f32) -> Result<CvdMes
         frames.push(vertices);
     }
 
-    let mut unknown_data = vec![0f32; frame_count as usize];
-    reader
-        .read_f32_into::<LittleEndian>(unknown_data.as_mut_slice())
-        .unwrap();
+    let mut frame_timestamps = vec![0f32; frame_count as usize];
+    reader.read_f32_into(frame_timestamps.as_mut_slice())?;
 
-    let material_count = reader.read_u32::<LittleEndian>().unwrap();
+    let material_count = reader.read_u32()?;
     let mut materials = vec![];
     for _i in 0..material_count {
-        let unknown_byte = reader.read_u8().unwrap();
-        let color1 = reader.read_u32::<LittleEndian>().unwrap();
-        let color2 = reader.read_u32::<LittleEndian>().unwrap();
-        let color3 = reader.read_u32::<LittleEndian>().unwrap();
-        let color4 = reader.read_u32::<LittleEndian>().unwrap();
-        let unknown_float2 = reader.read_f32::<LittleEndian>().unwrap();
-        let name = reader.read_u8_vec(64).unwrap();
-        let texture_name = encoding::all::GBK
-            .decode(
-                &name
-                    .into_iter()
-                    .take_while(|&c| c != 0)
-                    .collect::<Vec<u8>>(),
-                DecoderTrap::Ignore,
-            )
-            .unwrap();
-
-        let triangle_count = reader.read_u32::<LittleEndian>().unwrap();
+        let unknown_byte = reader.read_u8()?;
+        let color1 = reader.read_u32()?;
+        let color2 = reader.read_u32()?;
+        let color3 = reader.read_u32()?;
+        let color4 = reader.read_u32()?;
+        let unknown_float2 = reader.read_f32()?;
+        let texture_name = reader.read_string(64)?;
+
+        let triangle_count = reader.read_u32()?;
         let mut triangles = None;
         if triangle_count > 0 {
             triangles = Some(vec![]);
             for _j in 0..triangle_count {
-                let index1 = reader.read_u16::<LittleEndian>().unwrap();
-                let index2 = reader.read_u16::<LittleEndian>().unwrap();
-                let index3 = reader.read_u16::<LittleEndian>().unwrap();
+                let index1 = reader.read_u16()?;
+                let index2 = reader.read_u16()?;
+                let index3 = reader.read_u16()?;
                 triangles.as_mut().unwrap().push(CvdTriangle {
                     indices: [index1, index2, index3],
                 })
@@ -275,14 +248,14 @@ pub fn cvd_load_mesh(reader: &mut dyn Read, unknown_float: f32) -> Result<CvdMes
         }
 
         if unknown_float >= 0.5 {
-            let unknown_data2_count = reader.read_u32::<LittleEndian>().unwrap();
+            let unknown_data2_count = reader.read_u32()?;
             if unknown_data2_count > 0 {
                 for _k in 0..unknown_data2_count {
-                    let _ = reader.read_u32::<LittleEndian>().unwrap();
+                    let _ = reader.read_u32()?;
                 }
 
                 for _k in 0..unknown_data2_count {
-                    let _ = reader.read_u8_vec(20);
+                    let _ = reader.read_u8_vec(20)?;
                 }
             }
         }
@@ -303,39 +276,42 @@ pub fn cvd_load_mesh(reader: &mut dyn Read, unknown_float: f32) -> Result<CvdMes
         frame_count,
         vertex_count,
         frames,
-        unknown_data,
+        frame_timestamps,
         material_count,
         materials,
     })
 }
 
-fn read_position_keyframes(reader: &mut dyn Read) -> Option<CvdPositionKeyFrames> {
-    let count = reader.read_i32::<LittleEndian>().unwrap();
+fn read_position_keyframes(
+    reader: &mut BinaryReader,
+) -> Result<Option<CvdPositionKeyFrames>, LoaderError> {
+    let count = reader.read_i32()?;
     if count <= 0 {
-        return None;
+        return Ok(None);
     }
 
-    let version = reader.read_u8().unwrap();
+    let version_offset = reader.position();
+    let version = reader.read_u8()?;
     let mut frames = vec![];
     for _i in 0..count {
-        let timestamp = reader.read_f32::<LittleEndian>().unwrap();
-        let unknown1 = reader.read_f32::<LittleEndian>().unwrap();
-        let unknown2 = reader.read_f32::<LittleEndian>().unwrap();
-        let unknown3 = reader.read_f32::<LittleEndian>().unwrap();
-        let unknown4 = reader.read_f32::<LittleEndian>().unwrap();
-        let unknown5 = reader.read_f32::<LittleEndian>().unwrap();
-        let unknown6 = reader.read_f32::<LittleEndian>().unwrap();
-        let unknown7 = reader.read_f32::<LittleEndian>().unwrap();
-        let unknown8 = reader.read_f32::<LittleEndian>().unwrap();
-        let unknown9 = reader.read_f32::<LittleEndian>().unwrap();
-        let unknown10 = reader.read_f32::<LittleEndian>().unwrap();
+        let timestamp = reader.read_f32()?;
+        let unknown1 = reader.read_f32()?;
+        let unknown2 = reader.read_f32()?;
+        let unknown3 = reader.read_f32()?;
+        let unknown4 = reader.read_f32()?;
+        let unknown5 = reader.read_f32()?;
+        let unknown6 = reader.read_f32()?;
+        let unknown7 = reader.read_f32()?;
+        let unknown8 = reader.read_f32()?;
+        let unknown9 = reader.read_f32()?;
+        let unknown10 = reader.read_f32()?;
 
         let mut position;
         match version {
             1 => position = Vec3::new(unknown7, unknown8, unknown9),
             2 => position = Vec3::new(unknown8, unknown9, unknown10),
             3 => position = Vec3::new(unknown2, unknown3, unknown4),
-            _ => panic!("Unsupported position key frames version: {}", version),
+            _ => return Err(reader.unsupported_version(version_offset, version as u32)),
         }
 
         std::mem::swap(&mut position.y, &mut position.z);
@@ -357,29 +333,32 @@ fn read_position_keyframes(reader: &mut dyn Read) -> Option<CvdPositionKeyFrames
         })
     }
 
-    Some(CvdPositionKeyFrames { version, frames })
+    Ok(Some(CvdPositionKeyFrames { version, frames }))
 }
 
-fn read_rotation_keyframes(reader: &mut dyn Read) -> Option<CvdRotationKeyFrames> {
-    let count = reader.read_i32::<LittleEndian>().unwrap();
+fn read_rotation_keyframes(
+    reader: &mut BinaryReader,
+) -> Result<Option<CvdRotationKeyFrames>, LoaderError> {
+    let count = reader.read_i32()?;
     if count <= 0 {
-        return None;
+        return Ok(None);
     }
 
-    let version = reader.read_u8().unwrap();
+    let version_offset = reader.position();
+    let version = reader.read_u8()?;
     let mut frames = vec![];
     for _i in 0..count {
-        let timestamp = reader.read_f32::<LittleEndian>().unwrap();
-        let unknown1 = reader.read_f32::<LittleEndian>().unwrap();
-        let unknown2 = reader.read_f32::<LittleEndian>().unwrap();
-        let unknown3 = reader.read_f32::<LittleEndian>().unwrap();
-        let unknown4 = reader.read_f32::<LittleEndian>().unwrap();
-        let unknown5 = reader.read_f32::<LittleEndian>().unwrap();
-        let unknown6 = reader.read_f32::<LittleEndian>().unwrap();
-        let unknown7 = reader.read_f32::<LittleEndian>().unwrap();
-        let unknown8 = reader.read_f32::<LittleEndian>().unwrap();
-        let unknown9 = reader.read_f32::<LittleEndian>().unwrap();
-        let unknown10 = reader.read_f32::<LittleEndian>().unwrap();
+        let timestamp = reader.read_f32()?;
+        let unknown1 = reader.read_f32()?;
+        let unknown2 = reader.read_f32()?;
+        let unknown3 = reader.read_f32()?;
+        let unknown4 = reader.read_f32()?;
+        let unknown5 = reader.read_f32()?;
+        let unknown6 = reader.read_f32()?;
+        let unknown7 = reader.read_f32()?;
+        let unknown8 = reader.read_f32()?;
+        let unknown9 = reader.read_f32()?;
+        let unknown10 = reader.read_f32()?;
 
         let mut quaternion;
         match version {
@@ -388,7 +367,7 @@ fn read_rotation_keyframes(reader: &mut dyn Read) -> Option<CvdRotationKeyFrames
                     Quaternion::from_axis_angle(&Vec3::new(unknown7, unknown8, unknown9), unknown10)
             }
             2 | 3 => quaternion = Quaternion::new(unknown2, unknown3, unknown4, unknown5),
-            _ => panic!("Unsupported position key frames version: {}", version),
+            _ => return Err(reader.unsupported_version(version_offset, version as u32)),
         }
 
         std::mem::swap(&mut quaternion.y, &mut quaternion.z);
@@ -410,21 +389,24 @@ fn read_rotation_keyframes(reader: &mut dyn Read) -> Option<CvdRotationKeyFrames
         })
     }
 
-    Some(CvdRotationKeyFrames { version, frames })
+    Ok(Some(CvdRotationKeyFrames { version, frames }))
 }
 
-fn read_scale_keyframes(reader: &mut dyn Read) -> Option<CvdScaleKeyFrames> {
-    let count = reader.read_i32::<LittleEndian>().unwrap();
+fn read_scale_keyframes(
+    reader: &mut BinaryReader,
+) -> Result<Option<CvdScaleKeyFrames>, LoaderError> {
+    let count = reader.read_i32()?;
     if count <= 0 {
-        return None;
+        return Ok(None);
     }
 
-    let version = reader.read_u8().unwrap();
+    let version_offset = reader.position();
+    let version = reader.read_u8()?;
     let mut frames = vec![];
     for _i in 0..count {
-        let timestamp = reader.read_f32::<LittleEndian>().unwrap();
+        let timestamp = reader.read_f32()?;
         let mut unknown = [0f32; 14];
-        reader.read_f32_into::<LittleEndian>(&mut unknown).unwrap();
+        reader.read_f32_into(&mut unknown)?;
 
         let mut quaternion;
         let mut scale;
@@ -441,7 +423,7 @@ fn read_scale_keyframes(reader: &mut dyn Read) -> Option<CvdScaleKeyFrames> {
                 quaternion = Quaternion::new(unknown[4], unknown[5], unknown[6], unknown[7]);
                 scale = Vec3::new(unknown[1], unknown[2], unknown[3]);
             }
-            _ => panic!("Unsupported position key frames version: {}", version),
+            _ => return Err(reader.unsupported_version(version_offset, version as u32)),
         }
 
         std::mem::swap(&mut quaternion.y, &mut quaternion.z);
@@ -457,5 +439,5 @@ fn read_scale_keyframes(reader: &mut dyn Read) -> Option<CvdScaleKeyFrames> {
         })
     }
 
-    Some(CvdScaleKeyFrames { version, frames })
+    Ok(Some(CvdScaleKeyFrames { version, frames }))
 }
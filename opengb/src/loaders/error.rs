@@ -0,0 +1,105 @@
+use std::{
+    error::Error,
+    fmt::{Display, Formatter},
+    path::PathBuf,
+};
+
+#[derive(Debug)]
+pub enum LoaderError {
+    IoError {
+        path: PathBuf,
+        error: std::io::Error,
+    },
+    BadMagic {
+        path: PathBuf,
+        offset: u64,
+        magic: Vec<u8>,
+    },
+    UnsupportedVersion {
+        path: PathBuf,
+        offset: u64,
+        version: u32,
+    },
+    UnsupportedFeature {
+        path: PathBuf,
+        offset: u64,
+        feature: String,
+    },
+    TruncatedData {
+        path: PathBuf,
+        offset: u64,
+    },
+    InvalidOffset {
+        path: PathBuf,
+        offset: u64,
+        target: u64,
+    },
+}
+
+impl LoaderError {
+    pub fn path(&self) -> &PathBuf {
+        match self {
+            LoaderError::IoError { path, .. }
+            | LoaderError::BadMagic { path, .. }
+            | LoaderError::UnsupportedVersion { path, .. }
+            | LoaderError::UnsupportedFeature { path, .. }
+            | LoaderError::TruncatedData { path, .. }
+            | LoaderError::InvalidOffset { path, .. } => path,
+        }
+    }
+
+    pub fn offset(&self) -> Option<u64> {
+        match self {
+            LoaderError::IoError { .. } => None,
+            LoaderError::BadMagic { offset, .. }
+            | LoaderError::UnsupportedVersion { offset, .. }
+            | LoaderError::UnsupportedFeature { offset, .. }
+            | LoaderError::TruncatedData { offset, .. }
+            | LoaderError::InvalidOffset { offset, .. } => Some(*offset),
+        }
+    }
+}
+
+impl Display for LoaderError {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            LoaderError::IoError { path, error } => write!(f, "{:?}: {}", path, error),
+            LoaderError::BadMagic {
+                path,
+                offset,
+                magic,
+            } => write!(f, "{:?}@{:#x}: bad magic {:x?}", path, offset, magic),
+            LoaderError::UnsupportedVersion {
+                path,
+                offset,
+                version,
+            } => write!(
+                f,
+                "{:?}@{:#x}: unsupported version {}",
+                path, offset, version
+            ),
+            LoaderError::UnsupportedFeature {
+                path,
+                offset,
+                feature,
+            } => write!(f, "{:?}@{:#x}: unsupported {}", path, offset, feature),
+            LoaderError::TruncatedData { path, offset } => {
+                write!(f, "{:?}@{:#x}: unexpected end of data", path, offset)
+            }
+            LoaderError::InvalidOffset {
+                path,
+                offset,
+                target,
+            } => write!(f, "{:?}@{:#x}: invalid offset {:#x}", path, offset, target),
+        }
+    }
+}
+
+impl Error for LoaderError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            LoaderError::IoError { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
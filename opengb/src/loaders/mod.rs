@@ -1,3 +1,7 @@
+pub use binary_reader::BinaryReader;
+pub use error::LoaderError;
+
+pub mod ani_loader;
 pub mod cvd_loader;
 pub mod mv3_loader;
 pub mod nav_loader;
@@ -5,6 +9,9 @@ pub mod pol_loader;
 pub mod sce_loader;
 pub mod scn_loader;
 
+mod binary_reader;
+mod error;
+
 fn calc_vertex_size(t: i32) -> usize {
     if t < 0 {
         return (t & 0x7FFFFFFF) as usize;
@@ -1,10 +1,8 @@
-use crate::utilities::ReadExt;
-use byteorder::{LittleEndian, ReadBytesExt};
-use mini_fs::{MiniFs, StoreExt};
+use super::{BinaryReader, LoaderError};
+use encoding::{DecoderTrap, Encoding};
+use mini_fs::MiniFs;
 use serde::Serialize;
-use std::io::{BufReader, Read};
-use std::path::Path;
-use std::{error::Error, io::Cursor};
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Serialize)]
 pub struct Mv3Texture {
@@ -68,6 +66,22 @@ pub struct Mv3Model {
     pub meshes: Vec<Mv3Mesh>,
 }
 
+/// An action starts at `tick` and lasts until the next action starts, or
+/// until the end of the frames for the last action.
+#[derive(Debug, Serialize)]
+pub struct Mv3ActionDesc {
+    pub tick: u32,
+    pub name: String, // size: 16
+}
+
+/// The time range of an action, in the same ticks as `Mv3Frame::timestamp`.
+#[derive(Debug, Clone, Serialize)]
+pub struct Mv3ActionClip {
+    pub name: String,
+    pub start_tick: u32,
+    pub end_tick: u32,
+}
+
 #[derive(Debug, Serialize)]
 pub struct Mv3File {
     pub magic: [u8; 4],
@@ -77,53 +91,94 @@ pub struct Mv3File {
     pub unknown_data_count: u32,
     pub model_count: u32,
     pub action_count: u32,
-    pub action_desc: Vec<[u8; 20]>,
+    pub action_desc: Vec<Mv3ActionDesc>,
     pub unknown_data: Vec<Vec<u8>>,
     pub textures: Vec<Mv3Texture>,
     pub models: Vec<Mv3Model>,
 }
 
-pub fn mv3_load_from_file<P: AsRef<Path>>(
-    vfs: &MiniFs,
-    path: P,
-) -> Result<Mv3File, Box<dyn Error>> {
-    let mut reader = BufReader::new(vfs.open(&path)?);
+pub fn mv3_load_from_file<P: AsRef<Path>>(vfs: &MiniFs, path: P) -> Result<Mv3File, LoaderError> {
+    let mut reader = BinaryReader::open(vfs, path)?;
     mv3_load(&mut reader)
 }
 
-pub fn mv3_load_from_data(data: Vec<u8>) -> Result<Mv3File, Box<dyn Error>> {
-    let mut reader = BufReader::new(Cursor::new(data));
-    mv3_load(&mut reader)
+/// The first name of a texture slot, which is a file next to the mv3 file.
+pub fn mv3_texture_name(texture: &Mv3Texture) -> Option<String> {
+    let name = texture.names.first()?;
+    if name.is_empty() {
+        return None;
+    }
+
+    let name = encoding::all::GBK.decode(name, DecoderTrap::Ignore).ok()?;
+    Some(name.trim_end_matches('\0').to_string())
 }
 
-pub fn mv3_load<T: Read>(reader: &mut BufReader<T>) -> Result<Mv3File, Box<dyn Error>> {
-    let mut magic = [0u8; 4];
-    reader.read_exact(&mut magic)?;
+/// The paths of the textures of every texture slot.
+pub fn mv3_texture_paths(mv3: &Mv3File, mv3_path: &Path) -> Vec<Option<PathBuf>> {
+    mv3.textures
+        .iter()
+        .map(|t| Some(mv3_path.parent()?.join(mv3_texture_name(t)?)))
+        .collect()
+}
 
-    match magic {
-        [0x4d, 0x56, 0x33, 0x00] => (), // "MV3\0"
-        _ => panic!("Not a valid mv3 file"),
-    }
+/// Turns the action descriptors into clips. The last clip ends at the last
+/// frame of the longest model.
+pub fn mv3_action_clips(mv3: &Mv3File) -> Vec<Mv3ActionClip> {
+    let last_tick = mv3
+        .models
+        .iter()
+        .filter_map(|m| m.frames.last())
+        .map(|f| f.timestamp)
+        .max()
+        .unwrap_or(0);
+
+    mv3.action_desc
+        .iter()
+        .enumerate()
+        .map(|(i, action)| Mv3ActionClip {
+            name: action.name.clone(),
+            start_tick: action.tick,
+            end_tick: mv3
+                .action_desc
+                .get(i + 1)
+                .map(|next| next.tick)
+                .unwrap_or(last_tick)
+                .max(action.tick),
+        })
+        .collect()
+}
+
+pub fn mv3_load_from_data(data: Vec<u8>) -> Result<Mv3File, LoaderError> {
+    let mut reader = BinaryReader::new(data, "");
+    mv3_load(&mut reader)
+}
+
+pub fn mv3_load(reader: &mut BinaryReader) -> Result<Mv3File, LoaderError> {
+    let magic = reader.read_magic(&[b"MV3\0"])?;
 
-    let unknown_dw = reader.read_u32::<LittleEndian>()?;
-    let unknown_dw2 = reader.read_u32::<LittleEndian>()?;
-    let texture_count = reader.read_u32::<LittleEndian>()?;
-    let unknown_data_count = reader.read_u32::<LittleEndian>()?;
-    let model_count = reader.read_u32::<LittleEndian>()?;
-    let action_count = reader.read_u32::<LittleEndian>()?;
+    let unknown_dw = reader.read_u32()?;
+    let unknown_dw2 = reader.read_u32()?;
+    let texture_count = reader.read_u32()?;
+    let unknown_data_count = reader.read_u32()?;
+    let model_count = reader.read_u32()?;
+    let action_count = reader.read_u32()?;
 
     let mut action_desc = vec![];
     for _i in 0..action_count {
-        let mut buf = [0; 20];
-        reader.read_exact(&mut buf)?;
-        action_desc.push(buf);
+        let tick = reader.read_u32()?;
+        let name = reader.read_u8_vec(16)?;
+        let name: Vec<u8> = name.into_iter().take_while(|&c| c != 0).collect();
+        let name = encoding::all::GBK
+            .decode(&name, DecoderTrap::Ignore)
+            .unwrap_or_default();
+        action_desc.push(Mv3ActionDesc { tick, name });
     }
 
     let unknown_data = vec![];
     for _i in 0..unknown_data_count {
         let _buf = reader.read_u8_vec(64)?;
-        reader.read_u32::<LittleEndian>()?;
-        let count = reader.read_u32::<LittleEndian>()?;
+        reader.read_u32()?;
+        let count = reader.read_u32()?;
         for _j in 0..count {
             reader.read_u8_vec(68)?;
         }
@@ -136,7 +191,7 @@ pub fn mv3_load<T: Read>(reader: &mut BufReader<T>) -> Result<Mv3File, Box<dyn E
             let mut names = vec![];
 
             for _j in 0..4 {
-                let name_length = reader.read_u32::<LittleEndian>()?;
+                let name_length = reader.read_u32()?;
 
                 let name = if name_length > 0 {
                     reader.read_u8_vec(name_length as usize)?
@@ -177,22 +232,22 @@ pub fn mv3_load<T: Read>(reader: &mut BufReader<T>) -> Result<Mv3File, Box<dyn E
     })
 }
 
-fn read_mv3_model(reader: &mut dyn Read) -> Result<Mv3Model, Box<dyn Error>> {
+fn read_mv3_model(reader: &mut BinaryReader) -> Result<Mv3Model, LoaderError> {
     let unknown = reader.read_u8_vec(64)?;
-    let vertex_per_frame = reader.read_u32::<LittleEndian>()?;
+    let vertex_per_frame = reader.read_u32()?;
     let mut aabb_min = [0f32; 3];
     let mut aabb_max = [0f32; 3];
-    reader.read_f32_into::<LittleEndian>(&mut aabb_min)?;
-    reader.read_f32_into::<LittleEndian>(&mut aabb_max)?;
-    let frame_count = reader.read_u32::<LittleEndian>()?;
+    reader.read_f32_into(&mut aabb_min)?;
+    reader.read_f32_into(&mut aabb_max)?;
+    let frame_count = reader.read_u32()?;
     let mut frames = vec![];
     for _i in 0..frame_count {
-        let timestamp = reader.read_u32::<LittleEndian>()?;
+        let timestamp = reader.read_u32()?;
         let mut vertices = vec![];
         for _j in 0..vertex_per_frame {
-            let x = reader.read_i16::<LittleEndian>()?;
-            let y = reader.read_i16::<LittleEndian>()?;
-            let z = reader.read_i16::<LittleEndian>()?;
+            let x = reader.read_i16()?;
+            let y = reader.read_i16()?;
+            let z = reader.read_i16()?;
             let normal_phi = reader.read_i8()?;
             let normal_theta = reader.read_u8()?;
             vertices.push(Mv3Vertex {
@@ -209,16 +264,16 @@ fn read_mv3_model(reader: &mut dyn Read) -> Result<Mv3Model, Box<dyn Error>> {
         });
     }
 
-    let texcoord_count = reader.read_u32::<LittleEndian>()?;
+    let texcoord_count = reader.read_u32()?;
     let mut texcoords = vec![];
 
     for _i in 0..texcoord_count {
-        let u = reader.read_f32::<LittleEndian>()?;
-        let v = reader.read_f32::<LittleEndian>()?;
+        let u = reader.read_f32()?;
+        let v = reader.read_f32()?;
         texcoords.push(Mv3TexCoord { u, v });
     }
 
-    let mesh_count = reader.read_u32::<LittleEndian>()?;
+    let mesh_count = reader.read_u32()?;
     let mut meshes = vec![];
     for _i in 0..mesh_count {
         meshes.push(read_mv3_mesh(reader)?);
@@ -238,15 +293,15 @@ fn read_mv3_model(reader: &mut dyn Read) -> Result<Mv3Model, Box<dyn Error>> {
     })
 }
 
-fn read_mv3_mesh(reader: &mut dyn Read) -> Result<Mv3Mesh, Box<dyn Error>> {
-    let unknown = reader.read_u32::<LittleEndian>()?;
-    let triangle_count = reader.read_u32::<LittleEndian>()?;
+fn read_mv3_mesh(reader: &mut BinaryReader) -> Result<Mv3Mesh, LoaderError> {
+    let unknown = reader.read_u32()?;
+    let triangle_count = reader.read_u32()?;
     let mut triangles = vec![];
     for _i in 0..triangle_count {
         let mut indices = [0u16; 3];
         let mut texcoord_indices = [0u16; 3];
-        reader.read_u16_into::<LittleEndian>(&mut indices)?;
-        reader.read_u16_into::<LittleEndian>(&mut texcoord_indices)?;
+        reader.read_u16_into(&mut indices)?;
+        reader.read_u16_into(&mut texcoord_indices)?;
 
         triangles.push(Mv3Triangle {
             indices,
@@ -254,11 +309,11 @@ fn read_mv3_mesh(reader: &mut dyn Read) -> Result<Mv3Mesh, Box<dyn Error>> {
         })
     }
 
-    let unknown_data_count = reader.read_u32::<LittleEndian>()?;
+    let unknown_data_count = reader.read_u32()?;
     let mut unknown_data = vec![];
     for _i in 0..unknown_data_count {
-        let u = reader.read_u16::<LittleEndian>()?;
-        let v = reader.read_u16::<LittleEndian>()?;
+        let u = reader.read_u16()?;
+        let v = reader.read_u16()?;
         unknown_data.push(Mv3UnknownDataInMesh { u, v })
     }
 
@@ -1,22 +1,24 @@
-use crate::utilities::ReadExt;
-use byteorder::{LittleEndian, ReadBytesExt};
-use mini_fs::{MiniFs, StoreExt};
+use super::{BinaryReader, LoaderError};
+use crate::utilities::{check_len, WriteExt};
+use byteorder::{LittleEndian, WriteBytesExt};
+use mini_fs::MiniFs;
 use radiance::math::Vec3;
 use serde::Serialize;
-use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::io::{self, Write};
 use std::path::Path;
 
+const NAV_HEADER_SIZE: usize = 14;
+
 #[derive(Debug, Serialize)]
 pub struct NavMapPoint {
     pub height: f32,
     pub distance_to_border: u32,
 }
 
+/// The indices of the vertices in `NavMesh::vertices`.
 #[derive(Debug, Serialize)]
-pub struct NavUnknown22 {
-    pub unknown1: u16,
-    pub unknown2: u16,
-    pub unknown3: u16,
+pub struct NavTriangle {
+    pub indices: [u16; 3],
 }
 
 #[derive(Debug, Serialize)]
@@ -29,81 +31,113 @@ pub struct NavMap {
     pub map: Vec<Vec<NavMapPoint>>,
 }
 
+/// The walkable area of a map as triangles, in scene coordinates.
 #[derive(Debug, Serialize)]
-pub struct NavUnknown2 {
-    pub unknown21: Vec<Vec3>,
-    pub unknown22: Vec<NavUnknown22>,
+pub struct NavMesh {
+    pub vertices: Vec<Vec3>,
+    pub triangles: Vec<NavTriangle>,
 }
 
 #[derive(Debug, Serialize)]
 pub struct NavFile {
     pub version: u32,
     pub maps: Vec<NavMap>,
-    pub unknown2: Vec<NavUnknown2>,
+    pub meshes: Vec<NavMesh>,
+}
+
+pub fn nav_load_from_file<P: AsRef<Path>>(vfs: &MiniFs, path: P) -> Result<NavFile, LoaderError> {
+    let mut reader = BinaryReader::open(vfs, path)?;
+    nav_load(&mut reader)
 }
 
-pub fn nav_load_from_file<P: AsRef<Path>>(vfs: &MiniFs, path: P) -> NavFile {
-    let mut reader = BufReader::new(vfs.open(path).unwrap());
-    let mut magic = [0u8; 4];
-    reader.read_exact(&mut magic).unwrap();
+pub fn nav_load_from_data(data: Vec<u8>) -> Result<NavFile, LoaderError> {
+    let mut reader = BinaryReader::new(data, "");
+    nav_load(&mut reader)
+}
 
-    match magic {
-        [0x4e, 0x41, 0x56, 0x00] => (), // "nav"
-        _ => panic!("Not a valid nav file"),
-    };
+pub fn nav_load(reader: &mut BinaryReader) -> Result<NavFile, LoaderError> {
+    reader.read_magic(&[b"NAV\0"])?;
 
-    let version = reader.read_u8().unwrap();
+    let version_offset = reader.position();
+    let version = reader.read_u8()?;
     if version != 1 && version != 2 {
-        panic!("nav version should be 1 or 2");
+        return Err(reader.unsupported_version(version_offset, version as u32));
     }
 
-    let count = reader.read_u8().unwrap();
-    let unknown1_offset = reader.read_u32::<LittleEndian>().unwrap();
-    let unknown2_offset = reader.read_u32::<LittleEndian>().unwrap();
+    let count = reader.read_u8()?;
+    let maps_offset = reader.read_u32()?;
+    let meshes_offset = reader.read_u32()?;
 
-    reader
-        .seek(SeekFrom::Start(unknown1_offset as u64))
-        .unwrap();
+    reader.seek(maps_offset as u64)?;
     let mut maps = vec![];
     for _ in 0..count {
-        maps.push(nav_read_unknown1(&mut reader, version));
+        maps.push(nav_read_map(reader, version)?);
     }
 
-    reader
-        .seek(SeekFrom::Start(unknown2_offset as u64))
-        .unwrap();
-    let mut unknown2 = vec![];
+    reader.seek(meshes_offset as u64)?;
+    let mut meshes = vec![];
     for _ in 0..count {
-        unknown2.push(nav_read_unknown2(&mut reader, version));
+        meshes.push(nav_read_mesh(reader)?);
     }
 
-    NavFile {
+    Ok(NavFile {
         version: version as u32,
         maps,
-        unknown2,
+        meshes,
+    })
+}
+
+/// Writes the maps right after the header and the meshes after
+/// the maps. The offsets in the header are recalculated from this layout.
+pub fn nav_write<W: Write>(nav: &NavFile, writer: &mut W) -> io::Result<()> {
+    if nav.version != 1 && nav.version != 2 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Unsupported nav version {}", nav.version),
+        ));
+    }
+
+    check_len("NavFile::meshes", &nav.meshes, nav.maps.len())?;
+
+    let mut maps = vec![];
+    for map in &nav.maps {
+        nav_write_map(map, nav.version, &mut maps)?;
     }
+
+    writer.write_all(b"NAV\0")?;
+    writer.write_u8(nav.version as u8)?;
+    writer.write_u8(nav.maps.len() as u8)?;
+    writer.write_u32::<LittleEndian>(NAV_HEADER_SIZE as u32)?;
+    writer.write_u32::<LittleEndian>((NAV_HEADER_SIZE + maps.len()) as u32)?;
+    writer.write_all(&maps)?;
+
+    for mesh in &nav.meshes {
+        nav_write_mesh(mesh, writer)?;
+    }
+
+    Ok(())
 }
 
-fn nav_read_unknown1(reader: &mut dyn Read, version: u8) -> NavMap {
+fn nav_read_map(reader: &mut BinaryReader, version: u8) -> Result<NavMap, LoaderError> {
     let mut unknown = None;
     if version == 2 {
-        unknown = Some(reader.read_dw_vec(32).unwrap());
+        unknown = Some(reader.read_dw_vec(32)?);
     }
 
-    let max_coord_x = reader.read_f32::<LittleEndian>().unwrap();
-    let max_coord_y = reader.read_f32::<LittleEndian>().unwrap();
-    let max_coord_z = reader.read_f32::<LittleEndian>().unwrap();
-    let min_coord_x = reader.read_f32::<LittleEndian>().unwrap();
-    let min_coord_y = reader.read_f32::<LittleEndian>().unwrap();
-    let min_coord_z = reader.read_f32::<LittleEndian>().unwrap();
-    let width = reader.read_u32::<LittleEndian>().unwrap();
-    let height = reader.read_u32::<LittleEndian>().unwrap();
+    let max_coord_x = reader.read_f32()?;
+    let max_coord_y = reader.read_f32()?;
+    let max_coord_z = reader.read_f32()?;
+    let min_coord_x = reader.read_f32()?;
+    let min_coord_y = reader.read_f32()?;
+    let min_coord_z = reader.read_f32()?;
+    let width = reader.read_u32()?;
+    let height = reader.read_u32()?;
     let mut map = vec![];
     for _ in 0..height {
         let mut tmp = vec![];
         for _ in 0..width {
-            let height = reader.read_f32::<LittleEndian>().unwrap();
-            let distance_to_border = reader.read_u32::<LittleEndian>().unwrap();
+            let height = reader.read_f32()?;
+            let distance_to_border = reader.read_u32()?;
             tmp.push(NavMapPoint {
                 height,
                 distance_to_border,
@@ -113,41 +147,161 @@ fn nav_read_unknown1(reader: &mut dyn Read, version: u8) -> NavMap {
         map.push(tmp);
     }
 
-    NavMap {
+    Ok(NavMap {
         unknown,
         max_coord: Vec3::new(max_coord_x, max_coord_y, max_coord_z),
         min_coord: Vec3::new(min_coord_x, min_coord_y, min_coord_z),
         width,
         height,
         map,
+    })
+}
+
+fn nav_read_mesh(reader: &mut BinaryReader) -> Result<NavMesh, LoaderError> {
+    let vertex_count = reader.read_u16()?;
+    let triangle_count = reader.read_u16()?;
+    let mut vertices = vec![];
+    for _ in 0..vertex_count {
+        let x = reader.read_f32()?;
+        let y = reader.read_f32()?;
+        let z = reader.read_f32()?;
+        vertices.push(Vec3::new(x, y, z))
+    }
+
+    let mut triangles = vec![];
+    for _ in 0..triangle_count {
+        let indices = [reader.read_u16()?, reader.read_u16()?, reader.read_u16()?];
+        triangles.push(NavTriangle { indices })
     }
+
+    Ok(NavMesh {
+        vertices,
+        triangles,
+    })
 }
 
-fn nav_read_unknown2(reader: &mut dyn Read, version: u8) -> NavUnknown2 {
-    let count1 = reader.read_u16::<LittleEndian>().unwrap();
-    let count2 = reader.read_u16::<LittleEndian>().unwrap();
-    let mut unknown21 = vec![];
-    for _ in 0..count1 {
-        let x = reader.read_f32::<LittleEndian>().unwrap();
-        let y = reader.read_f32::<LittleEndian>().unwrap();
-        let z = reader.read_f32::<LittleEndian>().unwrap();
-        unknown21.push(Vec3::new(x, y, z))
-    }
-
-    let mut unknown22 = vec![];
-    for _ in 0..count2 {
-        let unknown1 = reader.read_u16::<LittleEndian>().unwrap();
-        let unknown2 = reader.read_u16::<LittleEndian>().unwrap();
-        let unknown3 = reader.read_u16::<LittleEndian>().unwrap();
-        unknown22.push(NavUnknown22 {
-            unknown1,
-            unknown2,
-            unknown3,
-        })
-    }
-
-    NavUnknown2 {
-        unknown21,
-        unknown22,
+fn nav_write_map<W: Write>(map: &NavMap, version: u32, writer: &mut W) -> io::Result<()> {
+    if version == 2 {
+        let unknown = map.unknown.as_deref().unwrap_or(&[]);
+        check_len("NavMap::unknown", unknown, 32)?;
+        writer.write_dw_vec(unknown)?;
+    }
+
+    writer.write_f32::<LittleEndian>(map.max_coord.x)?;
+    writer.write_f32::<LittleEndian>(map.max_coord.y)?;
+    writer.write_f32::<LittleEndian>(map.max_coord.z)?;
+    writer.write_f32::<LittleEndian>(map.min_coord.x)?;
+    writer.write_f32::<LittleEndian>(map.min_coord.y)?;
+    writer.write_f32::<LittleEndian>(map.min_coord.z)?;
+    writer.write_u32::<LittleEndian>(map.width)?;
+    writer.write_u32::<LittleEndian>(map.height)?;
+
+    check_len("NavMap::map", &map.map, map.height as usize)?;
+    for row in &map.map {
+        check_len("NavMap::map row", row, map.width as usize)?;
+        for point in row {
+            writer.write_f32::<LittleEndian>(point.height)?;
+            writer.write_u32::<LittleEndian>(point.distance_to_border)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn nav_write_mesh<W: Write>(mesh: &NavMesh, writer: &mut W) -> io::Result<()> {
+    writer.write_u16::<LittleEndian>(mesh.vertices.len() as u16)?;
+    writer.write_u16::<LittleEndian>(mesh.triangles.len() as u16)?;
+    for v in &mesh.vertices {
+        writer.write_f32::<LittleEndian>(v.x)?;
+        writer.write_f32::<LittleEndian>(v.y)?;
+        writer.write_f32::<LittleEndian>(v.z)?;
+    }
+
+    for t in &mesh.triangles {
+        writer.write_w_vec(&t.indices)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{nav_load_from_data, nav_write, NavFile, NAV_HEADER_SIZE};
+    use byteorder::{LittleEndian, WriteBytesExt};
+
+    fn nav_data(version: u8) -> Vec<u8> {
+        let mut map = vec![];
+        if version == 2 {
+            for i in 0..32 {
+                map.write_u32::<LittleEndian>(i * 3).unwrap();
+            }
+        }
+
+        for v in &[100., 20., 100., -100., -5., -100.] {
+            map.write_f32::<LittleEndian>(*v).unwrap();
+        }
+
+        map.write_u32::<LittleEndian>(2).unwrap();
+        map.write_u32::<LittleEndian>(1).unwrap();
+        for (height, distance) in &[(1.5, 0), (-0.25, 3)] {
+            map.write_f32::<LittleEndian>(*height).unwrap();
+            map.write_u32::<LittleEndian>(*distance).unwrap();
+        }
+
+        let mut mesh = vec![];
+        mesh.write_u16::<LittleEndian>(3).unwrap();
+        mesh.write_u16::<LittleEndian>(1).unwrap();
+        for v in &[0., 0., 0., 10., 1., 0., 0., 2., 10.] {
+            mesh.write_f32::<LittleEndian>(*v).unwrap();
+        }
+
+        for i in &[0, 2, 1] {
+            mesh.write_u16::<LittleEndian>(*i).unwrap();
+        }
+
+        let mut data = b"NAV\0".to_vec();
+        data.push(version);
+        data.push(1);
+        data.write_u32::<LittleEndian>(NAV_HEADER_SIZE as u32)
+            .unwrap();
+        data.write_u32::<LittleEndian>((NAV_HEADER_SIZE + map.len()) as u32)
+            .unwrap();
+        data.extend_from_slice(&map);
+        data.extend_from_slice(&mesh);
+        data
+    }
+
+    fn write(nav: &NavFile) -> Vec<u8> {
+        let mut written = vec![];
+        nav_write(nav, &mut written).unwrap();
+        written
+    }
+
+    #[test]
+    fn unedited_nav_writes_back_the_same_bytes() {
+        for version in 1..=2 {
+            let data = nav_data(version);
+            let nav = nav_load_from_data(data.clone()).unwrap();
+            assert_eq!(write(&nav), data, "version {}", version);
+        }
+    }
+
+    #[test]
+    fn blocked_cell_reloads_blocked() {
+        let original = nav_load_from_data(nav_data(2)).unwrap();
+        let mut nav = nav_load_from_data(nav_data(2)).unwrap();
+        nav.maps[0].map[0][0].distance_to_border = 0;
+        nav.maps[0].map[0][0].height = 4.;
+
+        let edited = nav_load_from_data(write(&nav)).unwrap();
+        let map = &edited.maps[0];
+        assert_eq!(map.map[0][0].distance_to_border, 0);
+        assert_eq!(map.map[0][0].height, 4.);
+        assert_eq!(map.map[0][1].distance_to_border, 3);
+        assert_eq!(map.unknown, original.maps[0].unknown);
+        assert_eq!(
+            edited.meshes[0].vertices.len(),
+            original.meshes[0].vertices.len()
+        );
     }
 }
@@ -1,12 +1,8 @@
-use crate::utilities::ReadExt;
-use byteorder::{LittleEndian, ReadBytesExt};
-use encoding::{DecoderTrap, Encoding};
+use super::{BinaryReader, LoaderError};
 use mini_fs::{MiniFs, StoreExt};
 use radiance::math::Mat44;
 use serde::Serialize;
-use std::error::Error;
-use std::io::{BufReader, Read};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Serialize)]
 pub struct PolVertexComponents(u32);
@@ -107,21 +103,12 @@ pub struct PolFile {
     pub meshes: Vec<PolMesh>,
 }
 
-pub fn pol_load_from_file<P: AsRef<Path>>(
-    vfs: &MiniFs,
-    path: P,
-) -> Result<PolFile, Box<dyn Error>> {
-    let mut reader = BufReader::new(vfs.open(path).unwrap());
-    let mut magic = [0u8; 4];
-    reader.read_exact(&mut magic)?;
-
-    match magic {
-        [0x50, 0x4f, 0x4c, 0x59] => (), // "POLY"
-        _ => panic!("Not a valid pol file"),
-    }
+pub fn pol_load_from_file<P: AsRef<Path>>(vfs: &MiniFs, path: P) -> Result<PolFile, LoaderError> {
+    let mut reader = BinaryReader::open(vfs, path)?;
+    let magic = reader.read_magic(&[b"POLY"])?;
 
-    let some_flag = reader.read_u32::<LittleEndian>()?;
-    let mesh_count = reader.read_u32::<LittleEndian>()?;
+    let some_flag = reader.read_u32()?;
+    let mesh_count = reader.read_u32()?;
     let mut geom_node_descs = vec![];
     for _i in 0..mesh_count {
         let unknown = reader.read_w_vec(26)?;
@@ -131,16 +118,16 @@ pub fn pol_load_from_file<P: AsRef<Path>>(
     let mut unknown_count = 0;
     let mut unknown_data = vec![];
     if some_flag > 100 {
-        unknown_count = reader.read_u32::<LittleEndian>()?;
+        unknown_count = reader.read_u32()?;
         if unknown_count > 0 {
             for _i in 0..unknown_count {
                 let u = reader.read_u8_vec(32)?;
                 let mut mat = Mat44::new_zero();
-                reader.read_f32_into::<LittleEndian>(unsafe {
+                reader.read_f32_into(unsafe {
                     std::mem::transmute::<&mut [[f32; 4]; 4], &mut [f32; 16]>(mat.floats_mut())
                 })?;
-                let u2 = reader.read_u32::<LittleEndian>()?;
-                let str_len = reader.read_u32::<LittleEndian>()?;
+                let u2 = reader.read_u32()?;
+                let str_len = reader.read_u32()?;
                 let ddd_str = reader.read_u8_vec(str_len as usize)?;
                 unknown_data.push(UnknownData {
                     unknown: u,
@@ -169,37 +156,58 @@ pub fn pol_load_from_file<P: AsRef<Path>>(
     })
 }
 
-fn read_pol_mesh(reader: &mut dyn Read) -> Result<PolMesh, Box<dyn Error>> {
+/// Textures are looked up next to the pol file, and the dds version is
+/// preferred when it exists.
+pub fn pol_texture_path<P: AsRef<Path>>(vfs: &MiniFs, pol_path: P, texture_name: &str) -> PathBuf {
+    texture_name
+        .split_terminator('.')
+        .next()
+        .and_then(|n| Some(n.to_owned() + ".dds"))
+        .and_then(|dds_name| {
+            let mut texture_path = pol_path.as_ref().to_owned();
+            texture_path.pop();
+            texture_path.push(dds_name);
+            if !vfs.open(&texture_path).is_ok() {
+                texture_path.pop();
+                texture_path.push(texture_name);
+            }
+
+            Some(texture_path)
+        })
+        .or(Some(PathBuf::from(texture_name)))
+        .unwrap()
+}
+
+fn read_pol_mesh(reader: &mut BinaryReader) -> Result<PolMesh, LoaderError> {
     let mut aabb_min = [0f32; 3];
     let mut aabb_max = [0f32; 3];
-    reader.read_f32_into::<LittleEndian>(&mut aabb_min)?;
-    reader.read_f32_into::<LittleEndian>(&mut aabb_max)?;
+    reader.read_f32_into(&mut aabb_min)?;
+    reader.read_f32_into(&mut aabb_max)?;
+    let vertex_type_offset = reader.position();
     let vertex_type = PolVertexComponents {
-        0: reader.read_i32::<LittleEndian>()? as u32,
+        0: reader.read_i32()? as u32,
     };
-    let vertex_count = reader.read_u32::<LittleEndian>()?;
+    let vertex_count = reader.read_u32()?;
     let _size = super::calc_vertex_size(vertex_type.0 as i32);
-    let mut vertices = vec![];
-    for _i in 0..vertex_count {
-        if !vertex_type.has(PolVertexComponents::POSITION) {
-            panic!("This POL file doesn't have position info, which doesn't support currently.");
-        }
+    if vertex_count > 0 && !vertex_type.has(PolVertexComponents::POSITION) {
+        return Err(reader.unsupported_feature(vertex_type_offset, "vertices without position"));
+    }
 
-        if !vertex_type.has(PolVertexComponents::TEXCOORD) {
-            panic!(
-                "This POL file doesn't have texture coord info, which doesn't support currently."
-            );
-        }
+    if vertex_count > 0 && !vertex_type.has(PolVertexComponents::TEXCOORD) {
+        return Err(reader.unsupported_feature(vertex_type_offset, "vertices without texcoord"));
+    }
 
+    let mut vertices = vec![];
+    for _i in 0..vertex_count {
         let position = PolVertexPosition {
-            x: reader.read_f32::<LittleEndian>()?,
-            y: reader.read_f32::<LittleEndian>()?,
-            z: reader.read_f32::<LittleEndian>()?,
+            x: reader.read_f32()?,
+            y: reader.read_f32()?,
+            z: reader.read_f32()?,
         };
 
         let normal = if vertex_type.has(PolVertexComponents::NORMAL) {
             let mut arr = [0.; 3];
-            reader.read_f32_into::<LittleEndian>(&mut arr).unwrap();
+            reader.read_f32_into(&mut arr)?;
             Some(arr)
         } else {
             None
@@ -207,7 +215,7 @@ fn read_pol_mesh(reader: &mut dyn Read) -> Result<PolMesh, Box<dyn Error>> {
 
         let unknown4 = if vertex_type.has(PolVertexComponents::UNKNOWN4) {
             let mut arr = [0.; 1];
-            reader.read_f32_into::<LittleEndian>(&mut arr)?;
+            reader.read_f32_into(&mut arr)?;
             Some(arr)
         } else {
             None
@@ -215,21 +223,21 @@ fn read_pol_mesh(reader: &mut dyn Read) -> Result<PolMesh, Box<dyn Error>> {
 
         let unknown8 = if vertex_type.has(PolVertexComponents::UNKNOWN8) {
             let mut arr = [0.; 1];
-            reader.read_f32_into::<LittleEndian>(&mut arr)?;
+            reader.read_f32_into(&mut arr)?;
             Some(arr)
         } else {
             None
         };
 
         let tex_coord = PolVertexTexCoord {
-            u: reader.read_f32::<LittleEndian>()?,
-            v: reader.read_f32::<LittleEndian>()?,
+            u: reader.read_f32()?,
+            v: reader.read_f32()?,
         };
 
         let tex_coord2 = if vertex_type.has(PolVertexComponents::TEXCOORD2) {
             Some(PolVertexTexCoord {
-                u: reader.read_f32::<LittleEndian>()?,
-                v: reader.read_f32::<LittleEndian>()?,
+                u: reader.read_f32()?,
+                v: reader.read_f32()?,
             })
         } else {
             None
@@ -237,7 +245,7 @@ fn read_pol_mesh(reader: &mut dyn Read) -> Result<PolMesh, Box<dyn Error>> {
 
         let unknown40 = if vertex_type.has(PolVertexComponents::UNKNOWN40) {
             let mut arr = [0.; 2];
-            reader.read_f32_into::<LittleEndian>(&mut arr)?;
+            reader.read_f32_into(&mut arr)?;
             Some(arr)
         } else {
             None
@@ -245,7 +253,7 @@ fn read_pol_mesh(reader: &mut dyn Read) -> Result<PolMesh, Box<dyn Error>> {
 
         let unknown80 = if vertex_type.has(PolVertexComponents::UNKNOWN80) {
             let mut arr = [0.; 2];
-            reader.read_f32_into::<LittleEndian>(&mut arr)?;
+            reader.read_f32_into(&mut arr)?;
             Some(arr)
         } else {
             None
@@ -253,7 +261,7 @@ fn read_pol_mesh(reader: &mut dyn Read) -> Result<PolMesh, Box<dyn Error>> {
 
         let unknown100 = if vertex_type.has(PolVertexComponents::UNKNOWN100) {
             let mut arr = [0.; 4];
-            reader.read_f32_into::<LittleEndian>(&mut arr)?;
+            reader.read_f32_into(&mut arr)?;
             Some(arr)
         } else {
             None
@@ -272,36 +280,26 @@ fn read_pol_mesh(reader: &mut dyn Read) -> Result<PolMesh, Box<dyn Error>> {
         });
     }
 
-    let material_info_count = reader.read_u32::<LittleEndian>()?;
+    let material_info_count = reader.read_u32()?;
     let mut material_info = vec![];
     for _i in 0..material_info_count {
-        let has_alpha = reader.read_u32::<LittleEndian>()?;
+        let has_alpha = reader.read_u32()?;
         let unknown_68 = reader.read_f32_vec(16)?;
-        let unknown_float = reader.read_f32::<LittleEndian>()?.min(128.).max(0.);
-        let texture_count = reader.read_u32::<LittleEndian>()?;
+        let unknown_float = reader.read_f32()?.min(128.).max(0.);
+        let texture_count = reader.read_u32()?;
         let mut texture_names = vec![];
         for _j in 0..texture_count {
-            let name = reader.read_u8_vec(64).unwrap();
-            let name_s = encoding::all::GBK
-                .decode(
-                    &name
-                        .into_iter()
-                        .take_while(|&c| c != 0)
-                        .collect::<Vec<u8>>(),
-                    DecoderTrap::Ignore,
-                )
-                .unwrap();
-            texture_names.push(name_s);
+            texture_names.push(reader.read_string(64)?);
         }
 
-        let unknown2 = reader.read_u32::<LittleEndian>()?;
-        let unknown3 = reader.read_u32::<LittleEndian>()?;
-        let unknown4 = reader.read_u32::<LittleEndian>()?;
-        let triangle_count = reader.read_u32::<LittleEndian>()?;
+        let unknown2 = reader.read_u32()?;
+        let unknown3 = reader.read_u32()?;
+        let unknown4 = reader.read_u32()?;
+        let triangle_count = reader.read_u32()?;
         let mut triangles = vec![];
         for _i in 0..triangle_count {
             let mut indices = [0u16; 3];
-            reader.read_u16_into::<LittleEndian>(&mut indices)?;
+            reader.read_u16_into(&mut indices)?;
             triangles.push(PolTriangle { indices })
         }
 
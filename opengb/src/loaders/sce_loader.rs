@@ -1,9 +1,10 @@
-use crate::utilities::ReadExt;
-use byteorder::{LittleEndian, ReadBytesExt};
-use mini_fs::{MiniFs, StoreExt};
+use super::{BinaryReader, LoaderError};
+use crate::utilities::{check_len, decode_gbk};
+use byteorder::{LittleEndian, WriteBytesExt};
+use mini_fs::MiniFs;
 use serde::Serialize;
 use std::collections::HashMap;
-use std::io::{BufReader, Read};
+use std::io::{self, Write};
 use std::path::Path;
 
 #[derive(Debug, Serialize)]
@@ -12,21 +13,31 @@ pub struct SceLocalVar {
     pub unknown_vec: Vec<u8>,
 }
 
+/// `name` is decoded from `raw_name`, which holds the `name_len` bytes of
+/// the file and is what `sce_write` writes back.
 #[derive(Debug, Serialize)]
 pub struct SceProc {
     pub id: u32,
     pub name: String,
+    #[serde(skip)]
+    pub raw_name: Vec<u8>,
     pub local_vars: Vec<SceLocalVar>,
     pub inst: Vec<u8>,
 }
 
+/// `raw_name` is the 64 byte name field, including whatever follows the
+/// `\0` in the file.
 #[derive(Debug, Serialize)]
 pub struct SceProcHeader {
     pub id: u32,
     pub offset: u32,
     pub name: String,
+    #[serde(skip)]
+    pub raw_name: Vec<u8>,
 }
 
+pub const SCE_PROC_NAME_SIZE: usize = 64;
+
 #[derive(Debug, Serialize)]
 pub struct SceFile {
     pub proc_num: u16,
@@ -34,73 +45,145 @@ pub struct SceFile {
     pub procs: HashMap<u32, SceProc>,
 }
 
-pub fn sce_load_from_file<P: AsRef<Path>>(vfs: &MiniFs, path: P) -> SceFile {
-    let mut reader = BufReader::new(vfs.open(path).unwrap());
-    let mut magic = [0u8; 4];
-    reader.read_exact(&mut magic).unwrap();
+pub fn sce_load_from_file<P: AsRef<Path>>(vfs: &MiniFs, path: P) -> Result<SceFile, LoaderError> {
+    let mut reader = BinaryReader::open(vfs, path)?;
+    sce_load(&mut reader)
+}
 
-    match magic {
-        [0x53, 0x43, 0x45, 0x00] => (), // "SCE"
-        _ => panic!("Not a valid sce file"),
-    }
+pub fn sce_load_from_data(data: Vec<u8>) -> Result<SceFile, LoaderError> {
+    let mut reader = BinaryReader::new(data, "");
+    sce_load(&mut reader)
+}
+
+pub fn sce_load(reader: &mut BinaryReader) -> Result<SceFile, LoaderError> {
+    reader.read_magic(&[b"SCE\0"])?;
 
-    let magic2 = reader.read_u8().unwrap();
-    if magic2 != 1 {
-        panic!("Not a valid sce file");
+    let version_offset = reader.position();
+    let version = reader.read_u8()?;
+    if version != 1 {
+        return Err(reader.unsupported_version(version_offset, version as u32));
     }
 
-    let proc_num = reader.read_u16::<LittleEndian>().unwrap();
+    let proc_num = reader.read_u16()?;
     let mut proc_headers = vec![];
     for _ in 0..proc_num {
-        let header = read_sce_proc_header(&mut reader);
+        let header = read_sce_proc_header(reader)?;
         proc_headers.push(header);
     }
 
     let mut procs = HashMap::new();
     for _ in 0..proc_num {
-        let proc = read_sce_proc(&mut reader);
+        let proc = read_sce_proc(reader)?;
         procs.insert(proc.id, proc);
     }
 
-    SceFile {
+    Ok(SceFile {
         proc_num,
         proc_headers,
         procs,
+    })
+}
+
+/// Writes the procs in the order of `proc_headers`. The header offsets are
+/// recalculated from the layout, so `SceProcHeader::offset` is ignored, and
+/// the names are written from `raw_name`.
+pub fn sce_write<W: Write>(sce: &SceFile, writer: &mut W) -> io::Result<()> {
+    let mut bodies = vec![];
+    for header in &sce.proc_headers {
+        let proc = sce.procs.get(&header.id).ok_or(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Proc {} is missing", header.id),
+        ))?;
+
+        let mut body = vec![];
+        write_sce_proc(proc, &mut body)?;
+        bodies.push(body);
+    }
+
+    writer.write_all(b"SCE\0")?;
+    writer.write_u8(1)?;
+    writer.write_u16::<LittleEndian>(sce.proc_headers.len() as u16)?;
+
+    let mut offset = 7 + sce.proc_headers.len() * (8 + SCE_PROC_NAME_SIZE);
+    for (header, body) in sce.proc_headers.iter().zip(&bodies) {
+        check_len(
+            "SceProcHeader::raw_name",
+            &header.raw_name,
+            SCE_PROC_NAME_SIZE,
+        )?;
+        writer.write_u32::<LittleEndian>(header.id)?;
+        writer.write_u32::<LittleEndian>(offset as u32)?;
+        writer.write_all(&header.raw_name)?;
+        offset += body.len();
+    }
+
+    for body in &bodies {
+        writer.write_all(body)?;
+    }
+
+    Ok(())
+}
+
+fn write_sce_proc<W: Write>(proc: &SceProc, writer: &mut W) -> io::Result<()> {
+    if proc.raw_name.len() > std::u16::MAX as usize {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("The name of proc {} is too long", proc.id),
+        ));
     }
+
+    writer.write_u32::<LittleEndian>(proc.id)?;
+    writer.write_u16::<LittleEndian>(proc.raw_name.len() as u16)?;
+    writer.write_all(&proc.raw_name)?;
+    writer.write_u16::<LittleEndian>(proc.local_vars.len() as u16)?;
+    for var in &proc.local_vars {
+        writer.write_u8(var.unknown)?;
+        writer.write_u16::<LittleEndian>(var.unknown_vec.len() as u16)?;
+        writer.write_all(&var.unknown_vec)?;
+    }
+
+    writer.write_u32::<LittleEndian>(proc.inst.len() as u32)?;
+    writer.write_all(&proc.inst)
 }
 
-fn read_sce_proc_header(reader: &mut dyn Read) -> SceProcHeader {
-    let id = reader.read_u32::<LittleEndian>().unwrap();
-    let offset = reader.read_u32::<LittleEndian>().unwrap();
-    let name = reader.read_string(64).unwrap();
+fn read_sce_proc_header(reader: &mut BinaryReader) -> Result<SceProcHeader, LoaderError> {
+    let id = reader.read_u32()?;
+    let offset = reader.read_u32()?;
+    let raw_name = reader.read_u8_vec(SCE_PROC_NAME_SIZE)?;
 
-    SceProcHeader { id, offset, name }
+    Ok(SceProcHeader {
+        id,
+        offset,
+        name: decode_gbk(&raw_name),
+        raw_name,
+    })
 }
 
-fn read_sce_proc(reader: &mut dyn Read) -> SceProc {
-    let id = reader.read_u32::<LittleEndian>().unwrap();
-    let name_len = reader.read_u16::<LittleEndian>().unwrap();
-    let name = reader.read_string(name_len as usize).unwrap();
-    let local_var_num = reader.read_u16::<LittleEndian>().unwrap();
+fn read_sce_proc(reader: &mut BinaryReader) -> Result<SceProc, LoaderError> {
+    let id = reader.read_u32()?;
+    let name_len = reader.read_u16()?;
+    let raw_name = reader.read_u8_vec(name_len as usize)?;
+    let local_var_num = reader.read_u16()?;
 
     let mut local_vars = vec![];
     for _ in 0..local_var_num {
-        let u = reader.read_u8().unwrap();
-        let size = reader.read_u16::<LittleEndian>().unwrap();
-        let unknown_vec = reader.read_u8_vec(size as usize).unwrap();
+        let u = reader.read_u8()?;
+        let size = reader.read_u16()?;
+        let unknown_vec = reader.read_u8_vec(size as usize)?;
         local_vars.push(SceLocalVar {
             unknown: u,
             unknown_vec,
         });
     }
 
-    let inst_size = reader.read_u32::<LittleEndian>().unwrap();
-    let inst = reader.read_u8_vec(inst_size as usize).unwrap();
+    let inst_size = reader.read_u32()?;
+    let inst = reader.read_u8_vec(inst_size as usize)?;
 
-    SceProc {
+    Ok(SceProc {
         id,
-        name,
+        name: decode_gbk(&raw_name),
+        raw_name,
         local_vars,
         inst,
-    }
+    })
 }
@@ -1,18 +1,24 @@
-use crate::utilities::ReadExt;
-use byteorder::{LittleEndian, ReadBytesExt};
-use mini_fs::{MiniFs, StoreExt};
+use super::{BinaryReader, LoaderError};
+use crate::utilities::{check_len, decode_gbk, WriteExt};
+use byteorder::{LittleEndian, WriteBytesExt};
+use mini_fs::MiniFs;
 use radiance::math::Vec3;
 use serde::Serialize;
-use std::{
-    io::{BufReader, Read, Seek, SeekFrom},
-    path::Path,
-};
+use std::io::{self, Write};
+use std::path::Path;
 
+const SCN_HEADER_SIZE: usize = 114;
+const SCN_ROLE_SIZE: usize = 456;
+
+/// The strings are decoded from the `raw_` fields, which hold the whole
+/// fixed size fields of the file and are what `scn_write` writes back.
 #[derive(Debug, Clone, Serialize)]
 pub struct ScnNode {
     pub index: u16,
     pub w2: u16,
     pub name: String,
+    #[serde(skip)]
+    pub raw_name: Vec<u8>,
     pub w24: u16,
     pub w26: u16,
     pub position: Vec3,
@@ -34,11 +40,14 @@ pub struct ScnNode {
     pub b: Vec<u8>,
 }
 
+/// See `ScnNode` for the `raw_` fields.
 #[derive(Debug, Clone, Serialize)]
 pub struct ScnRole {
     pub index: u8,
     pub b1: u8,
     pub name: String,
+    #[serde(skip)]
+    pub raw_name: Vec<u8>,
     pub w42: u16,
     pub dw44: f32,
     pub dw48: u32,
@@ -49,6 +58,8 @@ pub struct ScnRole {
     pub sce_proc_id: u32,
     pub dw60: u32,
     pub action_name: String,
+    #[serde(skip)]
+    pub raw_action_name: Vec<u8>,
     pub dw74: u32,
     pub dw78: u32,
     pub dw7c: u32,
@@ -60,90 +71,261 @@ pub struct ScnRole {
     pub dw154: Vec<u32>,
 }
 
+/// See `ScnNode` for the `raw_` fields.
 #[derive(Debug, Clone, Serialize)]
 pub struct ScnFile {
     pub cpk_name: String,
     pub scn_name: String,
     pub scn_base_name: String,
+    #[serde(skip)]
+    pub raw_cpk_name: Vec<u8>,
+    #[serde(skip)]
+    pub raw_scn_name: Vec<u8>,
+    #[serde(skip)]
+    pub raw_scn_base_name: Vec<u8>,
     pub roles: Vec<ScnRole>,
     pub nodes: Vec<ScnNode>,
 }
 
-pub fn scn_load_from_file<P: AsRef<Path>>(vfs: &MiniFs, path: P) -> ScnFile {
-    let mut reader = BufReader::new(vfs.open(path).unwrap());
-    let mut magic = [0u8; 4];
-    reader.read_exact(&mut magic).unwrap();
+/// What a node does in the scene, decoded from `ScnNode::node_type`. The
+/// engine doesn't read `b49`, `w66`, `b88` or `dw184` and their meaning is
+/// still unknown, so they stay raw in `ScnNode`. Every kind is written back
+/// by `scn_write` without loss.
+#[derive(Debug, Clone, Serialize)]
+pub enum ScnNodeKind {
+    /// The player picks up an item at `position` and `sce_proc_id` runs.
+    ItemTrigger {
+        position: Vec3,
+        sce_proc_id: u32,
+    },
+    /// `sce_proc_id` runs when the player enters the box.
+    AabbTrigger {
+        coord1: Vec3,
+        coord2: Vec3,
+        sce_proc_id: u32,
+    },
+    /// A model that stays hidden until a script shows it.
+    Invisible {
+        node_type: u8,
+    },
+    /// Neither rendered nor triggered.
+    Ignored {
+        node_type: u8,
+    },
+    Unknown {
+        node_type: u8,
+    },
+}
+
+/// A nav map range that runs `sce_proc_id` when the player enters it. Any
+/// kind of node may have one.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ScnNavTrigger {
+    pub coord_min: (i32, i32),
+    pub coord_max: (i32, i32),
+    pub sce_proc_id: u32,
+}
+
+/// The model of a node as `ScnScene` loads it, decoded from the prefix and
+/// the extension of `ScnNode::name`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum ScnNodeModel {
+    None,
+    /// A pol or cvd file in the folder of the scene, named with a `_` prefix.
+    Scene(String),
+    /// A cvd file in the object folder.
+    ObjectCvd(String),
+    /// A pol file in the item folder, named without the extension.
+    ItemPol(String),
+    /// Named with a `+` prefix. What it refers to is unknown.
+    Unknown(String),
+}
 
-    match magic {
-        [0x53, 0x43, 0x4e, 0x00] => (), // "SCN"
-        _ => panic!("Not a valid scn file"),
+pub fn scn_node_kind(node: &ScnNode) -> ScnNodeKind {
+    match node.node_type {
+        16 => ScnNodeKind::ItemTrigger {
+            position: node.position,
+            sce_proc_id: node.sce_proc_id,
+        },
+        20 => ScnNodeKind::AabbTrigger {
+            coord1: node.aabb_trigger_coord1,
+            coord2: node.aabb_trigger_coord2,
+            sce_proc_id: node.sce_proc_id,
+        },
+        17 | 25 => ScnNodeKind::Invisible {
+            node_type: node.node_type,
+        },
+        37 | 43 => ScnNodeKind::Ignored {
+            node_type: node.node_type,
+        },
+        node_type => ScnNodeKind::Unknown { node_type },
     }
+}
 
-    let magic2 = reader.read_u16::<LittleEndian>().unwrap();
-    if magic2 != 1 {
-        panic!("Not a valid scn file");
+/// Ignored nodes have no model even if they are named.
+pub fn scn_node_model(node: &ScnNode) -> ScnNodeModel {
+    let name = node.name.clone();
+    if name.is_empty() {
+        return ScnNodeModel::None;
     }
 
-    let role_num = reader.read_u16::<LittleEndian>().unwrap();
-    let role_offset = reader.read_u32::<LittleEndian>().unwrap();
-    let node_num = reader.read_u16::<LittleEndian>().unwrap();
-    let node_offset = reader.read_u32::<LittleEndian>().unwrap();
+    if let ScnNodeKind::Ignored { .. } = scn_node_kind(node) {
+        return ScnNodeModel::None;
+    }
 
-    let cpk_name = reader.read_string(32).unwrap();
-    let scn_name = reader.read_string(32).unwrap();
-    let scn_base_name = reader.read_string(32).unwrap();
+    if name.starts_with('_') {
+        ScnNodeModel::Scene(name)
+    } else if name.to_lowercase().ends_with(".cvd") {
+        ScnNodeModel::ObjectCvd(name)
+    } else if name.starts_with('+') {
+        ScnNodeModel::Unknown(name)
+    } else {
+        ScnNodeModel::ItemPol(name)
+    }
+}
+
+/// A node without a nav trigger has both of its coords at `(0, 0)`.
+pub fn scn_node_nav_trigger(node: &ScnNode) -> Option<ScnNavTrigger> {
+    if node.nav_trigger_coord_min == (0, 0) && node.nav_trigger_coord_max == (0, 0) {
+        None
+    } else {
+        Some(ScnNavTrigger {
+            coord_min: node.nav_trigger_coord_min,
+            coord_max: node.nav_trigger_coord_max,
+            sce_proc_id: node.sce_proc_id,
+        })
+    }
+}
+
+/// Dumps the scn file as Json, with the kind, the model and the nav trigger of
+/// every node next to its raw fields.
+pub fn scn_to_json(scn_file: &ScnFile) -> serde_json::Result<String> {
+    let mut json = serde_json::to_value(scn_file)?;
+    if let Some(nodes) = json["nodes"].as_array_mut() {
+        for (node, scn_node) in nodes.iter_mut().zip(&scn_file.nodes) {
+            node["kind"] = serde_json::to_value(scn_node_kind(scn_node))?;
+            node["model"] = serde_json::to_value(scn_node_model(scn_node))?;
+            node["nav_trigger"] = serde_json::to_value(scn_node_nav_trigger(scn_node))?;
+        }
+    }
+
+    serde_json::to_string_pretty(&json)
+}
+
+pub fn scn_load_from_file<P: AsRef<Path>>(vfs: &MiniFs, path: P) -> Result<ScnFile, LoaderError> {
+    let mut reader = BinaryReader::open(vfs, path)?;
+    scn_load(&mut reader)
+}
+
+pub fn scn_load_from_data(data: Vec<u8>) -> Result<ScnFile, LoaderError> {
+    let mut reader = BinaryReader::new(data, "");
+    scn_load(&mut reader)
+}
+
+pub fn scn_load(reader: &mut BinaryReader) -> Result<ScnFile, LoaderError> {
+    reader.read_magic(&[b"SCN\0"])?;
+
+    let version_offset = reader.position();
+    let version = reader.read_u16()?;
+    if version != 1 {
+        return Err(reader.unsupported_version(version_offset, version as u32));
+    }
+
+    let role_num = reader.read_u16()?;
+    let role_offset = reader.read_u32()?;
+    let node_num = reader.read_u16()?;
+    let node_offset = reader.read_u32()?;
+
+    let raw_cpk_name = reader.read_u8_vec(32)?;
+    let raw_scn_name = reader.read_u8_vec(32)?;
+    let raw_scn_base_name = reader.read_u8_vec(32)?;
 
     let mut roles = vec![];
-    reader.seek(SeekFrom::Start(role_offset as u64)).unwrap();
+    reader.seek(role_offset as u64)?;
     for _i in 0..role_num {
-        let v = read_scn_role(&mut reader);
+        let v = read_scn_role(reader)?;
         roles.push(v);
     }
 
     let mut nodes = vec![];
-    reader.seek(SeekFrom::Start(node_offset as u64)).unwrap();
+    reader.seek(node_offset as u64)?;
     for _i in 0..node_num {
-        let node = read_scn_node(&mut reader);
+        let node = read_scn_node(reader)?;
         nodes.push(node);
     }
 
-    ScnFile {
-        cpk_name,
-        scn_name,
-        scn_base_name,
+    Ok(ScnFile {
+        cpk_name: decode_gbk(&raw_cpk_name),
+        scn_name: decode_gbk(&raw_scn_name),
+        scn_base_name: decode_gbk(&raw_scn_base_name),
+        raw_cpk_name,
+        raw_scn_name,
+        raw_scn_base_name,
         roles,
         nodes,
+    })
+}
+
+/// Writes the roles right after the header and the nodes after the roles.
+/// The offsets in the header are recalculated from this layout.
+pub fn scn_write<W: Write>(scn: &ScnFile, writer: &mut W) -> io::Result<()> {
+    check_len("ScnFile::raw_cpk_name", &scn.raw_cpk_name, 32)?;
+    check_len("ScnFile::raw_scn_name", &scn.raw_scn_name, 32)?;
+    check_len("ScnFile::raw_scn_base_name", &scn.raw_scn_base_name, 32)?;
+
+    let role_offset = SCN_HEADER_SIZE;
+    let node_offset = role_offset + scn.roles.len() * SCN_ROLE_SIZE;
+
+    writer.write_all(b"SCN\0")?;
+    writer.write_u16::<LittleEndian>(1)?;
+    writer.write_u16::<LittleEndian>(scn.roles.len() as u16)?;
+    writer.write_u32::<LittleEndian>(role_offset as u32)?;
+    writer.write_u16::<LittleEndian>(scn.nodes.len() as u16)?;
+    writer.write_u32::<LittleEndian>(node_offset as u32)?;
+    writer.write_all(&scn.raw_cpk_name)?;
+    writer.write_all(&scn.raw_scn_name)?;
+    writer.write_all(&scn.raw_scn_base_name)?;
+
+    for role in &scn.roles {
+        write_scn_role(role, writer)?;
     }
+
+    for node in &scn.nodes {
+        write_scn_node(node, writer)?;
+    }
+
+    Ok(())
 }
 
-fn read_scn_role(reader: &mut dyn Read) -> ScnRole {
-    let index = reader.read_u8().unwrap();
-    let b1 = reader.read_u8().unwrap();
-    let name = reader.read_string(64).unwrap();
-    let w42 = reader.read_u16::<LittleEndian>().unwrap();
-    let dw44 = reader.read_f32::<LittleEndian>().unwrap();
-    let dw48 = reader.read_u32::<LittleEndian>().unwrap();
-    let position_x = reader.read_f32::<LittleEndian>().unwrap();
-    let position_z = reader.read_f32::<LittleEndian>().unwrap();
-    let position_y = reader.read_f32::<LittleEndian>().unwrap();
-    let dw58 = reader.read_u32::<LittleEndian>().unwrap();
-    let sce_proc_id = reader.read_u32::<LittleEndian>().unwrap();
-    let dw60 = reader.read_u32::<LittleEndian>().unwrap();
-    let action_name = reader.read_string(16).unwrap();
-    let dw74 = reader.read_u32::<LittleEndian>().unwrap();
-    let dw78 = reader.read_u32::<LittleEndian>().unwrap();
-    let dw7c = reader.read_u32::<LittleEndian>().unwrap();
-    let b80 = reader.read_u8_vec(4).unwrap();
-    let dw84 = reader.read_dw_vec(49).unwrap();
-    let dw148 = reader.read_u32::<LittleEndian>().unwrap();
-    let dw14c = reader.read_u32::<LittleEndian>().unwrap();
-    let dw150 = reader.read_u32::<LittleEndian>().unwrap();
-    let dw154 = reader.read_dw_vec(29).unwrap();
-
-    ScnRole {
+fn read_scn_role(reader: &mut BinaryReader) -> Result<ScnRole, LoaderError> {
+    let index = reader.read_u8()?;
+    let b1 = reader.read_u8()?;
+    let raw_name = reader.read_u8_vec(64)?;
+    let w42 = reader.read_u16()?;
+    let dw44 = reader.read_f32()?;
+    let dw48 = reader.read_u32()?;
+    let position_x = reader.read_f32()?;
+    let position_z = reader.read_f32()?;
+    let position_y = reader.read_f32()?;
+    let dw58 = reader.read_u32()?;
+    let sce_proc_id = reader.read_u32()?;
+    let dw60 = reader.read_u32()?;
+    let raw_action_name = reader.read_u8_vec(16)?;
+    let dw74 = reader.read_u32()?;
+    let dw78 = reader.read_u32()?;
+    let dw7c = reader.read_u32()?;
+    let b80 = reader.read_u8_vec(4)?;
+    let dw84 = reader.read_dw_vec(49)?;
+    let dw148 = reader.read_u32()?;
+    let dw14c = reader.read_u32()?;
+    let dw150 = reader.read_u32()?;
+    let dw154 = reader.read_dw_vec(29)?;
+
+    Ok(ScnRole {
         index,
         b1,
-        name,
+        name: decode_gbk(&raw_name),
+        raw_name,
         w42,
         dw44,
         dw48,
@@ -153,7 +335,8 @@ fn read_scn_role(reader: &mut dyn Read) -> ScnRole {
         dw58,
         sce_proc_id,
         dw60,
-        action_name,
+        action_name: decode_gbk(&raw_action_name),
+        raw_action_name,
         dw74,
         dw78,
         dw7c,
@@ -163,45 +346,46 @@ fn read_scn_role(reader: &mut dyn Read) -> ScnRole {
         dw14c,
         dw150,
         dw154,
-    }
+    })
 }
 
-fn read_scn_node(reader: &mut dyn Read) -> ScnNode {
-    let index = reader.read_u16::<LittleEndian>().unwrap();
-    let w2 = reader.read_u16::<LittleEndian>().unwrap();
-    let name = reader.read_string(32).unwrap();
-    let w24 = reader.read_u16::<LittleEndian>().unwrap();
-    let w26 = reader.read_u16::<LittleEndian>().unwrap();
-    let position_x = reader.read_f32::<LittleEndian>().unwrap();
-    let position_y = reader.read_f32::<LittleEndian>().unwrap();
-    let position_z = reader.read_f32::<LittleEndian>().unwrap();
-    let rotation = reader.read_f32::<LittleEndian>().unwrap();
-    let nav_coord_min_x = reader.read_i32::<LittleEndian>().unwrap();
-    let nav_coord_min_z = reader.read_i32::<LittleEndian>().unwrap();
-    let nav_coord_max_x = reader.read_i32::<LittleEndian>().unwrap();
-    let nav_coord_max_z = reader.read_i32::<LittleEndian>().unwrap();
-    let node_type = reader.read_u8().unwrap();
-    let b49 = reader.read_u8_vec(29).unwrap();
-    let w66 = reader.read_w_vec(4).unwrap();
-    let b6e = reader.read_u8_vec(18).unwrap();
-    let sce_proc_id = reader.read_u32::<LittleEndian>().unwrap();
-    let d84 = reader.read_u32::<LittleEndian>().unwrap();
-    let b88 = reader.read_u8_vec(192).unwrap();
-    let w148 = reader.read_u16::<LittleEndian>().unwrap();
-    let b14a = reader.read_u8_vec(34).unwrap();
-    let vec1_x = reader.read_f32::<LittleEndian>().unwrap();
-    let vec1_y = reader.read_f32::<LittleEndian>().unwrap();
-    let vec1_z = reader.read_f32::<LittleEndian>().unwrap();
-    let vec2_x = reader.read_f32::<LittleEndian>().unwrap();
-    let vec2_y = reader.read_f32::<LittleEndian>().unwrap();
-    let vec2_z = reader.read_f32::<LittleEndian>().unwrap();
-    let dw184 = reader.read_dw_vec(6).unwrap();
-    let b = reader.read_u8_vec(208).unwrap();
-
-    ScnNode {
+fn read_scn_node(reader: &mut BinaryReader) -> Result<ScnNode, LoaderError> {
+    let index = reader.read_u16()?;
+    let w2 = reader.read_u16()?;
+    let raw_name = reader.read_u8_vec(32)?;
+    let w24 = reader.read_u16()?;
+    let w26 = reader.read_u16()?;
+    let position_x = reader.read_f32()?;
+    let position_y = reader.read_f32()?;
+    let position_z = reader.read_f32()?;
+    let rotation = reader.read_f32()?;
+    let nav_coord_min_x = reader.read_i32()?;
+    let nav_coord_min_z = reader.read_i32()?;
+    let nav_coord_max_x = reader.read_i32()?;
+    let nav_coord_max_z = reader.read_i32()?;
+    let node_type = reader.read_u8()?;
+    let b49 = reader.read_u8_vec(29)?;
+    let w66 = reader.read_w_vec(4)?;
+    let b6e = reader.read_u8_vec(18)?;
+    let sce_proc_id = reader.read_u32()?;
+    let d84 = reader.read_u32()?;
+    let b88 = reader.read_u8_vec(192)?;
+    let w148 = reader.read_u16()?;
+    let b14a = reader.read_u8_vec(34)?;
+    let vec1_x = reader.read_f32()?;
+    let vec1_y = reader.read_f32()?;
+    let vec1_z = reader.read_f32()?;
+    let vec2_x = reader.read_f32()?;
+    let vec2_y = reader.read_f32()?;
+    let vec2_z = reader.read_f32()?;
+    let dw184 = reader.read_dw_vec(6)?;
+    let b = reader.read_u8_vec(208)?;
+
+    Ok(ScnNode {
         index,
         w2,
-        name,
+        name: decode_gbk(&raw_name),
+        raw_name,
         w24,
         w26,
         position: Vec3::new(position_x, position_y, position_z),
@@ -221,5 +405,248 @@ fn read_scn_node(reader: &mut dyn Read) -> ScnNode {
         aabb_trigger_coord2: Vec3::new(vec2_x, vec2_y, vec2_z),
         dw184,
         b,
+    })
+}
+
+fn write_scn_role<W: Write>(role: &ScnRole, writer: &mut W) -> io::Result<()> {
+    check_len("ScnRole::raw_name", &role.raw_name, 64)?;
+    check_len("ScnRole::raw_action_name", &role.raw_action_name, 16)?;
+    check_len("ScnRole::b80", &role.b80, 4)?;
+    check_len("ScnRole::dw84", &role.dw84, 49)?;
+    check_len("ScnRole::dw154", &role.dw154, 29)?;
+
+    writer.write_u8(role.index)?;
+    writer.write_u8(role.b1)?;
+    writer.write_all(&role.raw_name)?;
+    writer.write_u16::<LittleEndian>(role.w42)?;
+    writer.write_f32::<LittleEndian>(role.dw44)?;
+    writer.write_u32::<LittleEndian>(role.dw48)?;
+    writer.write_f32::<LittleEndian>(role.position_x)?;
+    writer.write_f32::<LittleEndian>(role.position_z)?;
+    writer.write_f32::<LittleEndian>(role.position_y)?;
+    writer.write_u32::<LittleEndian>(role.dw58)?;
+    writer.write_u32::<LittleEndian>(role.sce_proc_id)?;
+    writer.write_u32::<LittleEndian>(role.dw60)?;
+    writer.write_all(&role.raw_action_name)?;
+    writer.write_u32::<LittleEndian>(role.dw74)?;
+    writer.write_u32::<LittleEndian>(role.dw78)?;
+    writer.write_u32::<LittleEndian>(role.dw7c)?;
+    writer.write_all(&role.b80)?;
+    writer.write_dw_vec(&role.dw84)?;
+    writer.write_u32::<LittleEndian>(role.dw148)?;
+    writer.write_u32::<LittleEndian>(role.dw14c)?;
+    writer.write_u32::<LittleEndian>(role.dw150)?;
+    writer.write_dw_vec(&role.dw154)
+}
+
+fn write_scn_node<W: Write>(node: &ScnNode, writer: &mut W) -> io::Result<()> {
+    check_len("ScnNode::raw_name", &node.raw_name, 32)?;
+    check_len("ScnNode::b49", &node.b49, 29)?;
+    check_len("ScnNode::w66", &node.w66, 4)?;
+    check_len("ScnNode::b6e", &node.b6e, 18)?;
+    check_len("ScnNode::b88", &node.b88, 192)?;
+    check_len("ScnNode::b14a", &node.b14a, 34)?;
+    check_len("ScnNode::dw184", &node.dw184, 6)?;
+    check_len("ScnNode::b", &node.b, 208)?;
+
+    writer.write_u16::<LittleEndian>(node.index)?;
+    writer.write_u16::<LittleEndian>(node.w2)?;
+    writer.write_all(&node.raw_name)?;
+    writer.write_u16::<LittleEndian>(node.w24)?;
+    writer.write_u16::<LittleEndian>(node.w26)?;
+    writer.write_f32::<LittleEndian>(node.position.x)?;
+    writer.write_f32::<LittleEndian>(node.position.y)?;
+    writer.write_f32::<LittleEndian>(node.position.z)?;
+    writer.write_f32::<LittleEndian>(node.rotation)?;
+    writer.write_i32::<LittleEndian>(node.nav_trigger_coord_min.0)?;
+    writer.write_i32::<LittleEndian>(node.nav_trigger_coord_min.1)?;
+    writer.write_i32::<LittleEndian>(node.nav_trigger_coord_max.0)?;
+    writer.write_i32::<LittleEndian>(node.nav_trigger_coord_max.1)?;
+    writer.write_u8(node.node_type)?;
+    writer.write_all(&node.b49)?;
+    writer.write_w_vec(&node.w66)?;
+    writer.write_all(&node.b6e)?;
+    writer.write_u32::<LittleEndian>(node.sce_proc_id)?;
+    writer.write_u32::<LittleEndian>(node.d84)?;
+    writer.write_all(&node.b88)?;
+    writer.write_u16::<LittleEndian>(node.w148)?;
+    writer.write_all(&node.b14a)?;
+    writer.write_f32::<LittleEndian>(node.aabb_trigger_coord1.x)?;
+    writer.write_f32::<LittleEndian>(node.aabb_trigger_coord1.y)?;
+    writer.write_f32::<LittleEndian>(node.aabb_trigger_coord1.z)?;
+    writer.write_f32::<LittleEndian>(node.aabb_trigger_coord2.x)?;
+    writer.write_f32::<LittleEndian>(node.aabb_trigger_coord2.y)?;
+    writer.write_f32::<LittleEndian>(node.aabb_trigger_coord2.z)?;
+    writer.write_dw_vec(&node.dw184)?;
+    writer.write_all(&node.b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        scn_load_from_data, scn_node_kind, scn_node_model, scn_node_nav_trigger, scn_write,
+        ScnFile, ScnNavTrigger, ScnNodeKind, ScnNodeModel, SCN_HEADER_SIZE, SCN_ROLE_SIZE,
+    };
+    use byteorder::{LittleEndian, WriteBytesExt};
+    use radiance::math::Vec3;
+
+    const SCN_NODE_SIZE: usize = 620;
+
+    // Small bytes so that none of the floats is a NaN
+    fn pattern(size: usize, seed: usize) -> Vec<u8> {
+        (0..size).map(|i| ((i + seed) * 7 % 0x7f) as u8).collect()
+    }
+
+    // A `\0` terminated name followed by the kind of garbage the game files
+    // have in their fixed size fields
+    fn name(text: &[u8], garbage: &[u8], size: usize) -> Vec<u8> {
+        let mut name = text.to_vec();
+        name.push(0);
+        name.extend_from_slice(garbage);
+        name.resize(size, 0xcd);
+        name
+    }
+
+    fn scn_with_garbage_in_names() -> Vec<u8> {
+        let mut role = pattern(SCN_ROLE_SIZE, 1);
+        role[2..66].copy_from_slice(&name(b"101", b"\x01\x02", 64));
+        role[100..116].copy_from_slice(&name(b"C01", b"", 16));
+
+        let mut node = pattern(SCN_NODE_SIZE, 2);
+        node[4..36].copy_from_slice(&name(&[0xc4, 0xe3], b"\xff", 32));
+
+        let mut data = b"SCN\0".to_vec();
+        data.write_u16::<LittleEndian>(1).unwrap();
+        data.write_u16::<LittleEndian>(2).unwrap();
+        data.write_u32::<LittleEndian>(SCN_HEADER_SIZE as u32)
+            .unwrap();
+        data.write_u16::<LittleEndian>(1).unwrap();
+        data.write_u32::<LittleEndian>((SCN_HEADER_SIZE + 2 * SCN_ROLE_SIZE) as u32)
+            .unwrap();
+        data.extend_from_slice(&name(b"q01", b"garbage", 32));
+        data.extend_from_slice(&[b'a'; 32]);
+        data.extend_from_slice(&name(b"q01a", b"", 32));
+        data.extend_from_slice(&role);
+        data.extend_from_slice(&pattern(SCN_ROLE_SIZE, 3));
+        data.extend_from_slice(&node);
+        data
+    }
+
+    fn write(scn: &ScnFile) -> Vec<u8> {
+        let mut written = vec![];
+        scn_write(scn, &mut written).unwrap();
+        written
+    }
+
+    #[test]
+    fn unedited_scn_writes_back_the_same_bytes() {
+        let data = scn_with_garbage_in_names();
+        let scn = scn_load_from_data(data.clone()).unwrap();
+        assert_eq!(scn.cpk_name, "q01");
+        assert_eq!(scn.roles[0].name, "101");
+        assert_eq!(scn.nodes[0].name, "你");
+        assert_eq!(write(&scn), data);
+    }
+
+    #[test]
+    fn moved_node_reloads_at_its_new_place() {
+        let original = scn_load_from_data(scn_with_garbage_in_names()).unwrap();
+        let mut scn = original.clone();
+        scn.nodes[0].position = Vec3::new(120., -3.5, 48.25);
+        scn.nodes[0].rotation = 90.;
+        scn.roles.remove(1);
+
+        // Dropping a role moves the nodes, so the node offset is rewritten
+        let edited = scn_load_from_data(write(&scn)).unwrap();
+        assert_eq!(edited.roles.len(), 1);
+        assert_eq!(edited.roles[0].raw_name, original.roles[0].raw_name);
+
+        let node = &edited.nodes[0];
+        assert_eq!(
+            (node.position.x, node.position.y, node.position.z),
+            (120., -3.5, 48.25)
+        );
+        assert_eq!(node.rotation, 90.);
+        assert_eq!(node.raw_name, original.nodes[0].raw_name);
+        assert_eq!(node.b, original.nodes[0].b);
+    }
+
+    #[test]
+    fn node_kinds_are_decoded_from_the_node_fields() {
+        let scn = scn_load_from_data(scn_with_garbage_in_names()).unwrap();
+        let mut node = scn.nodes[0].clone();
+        node.sce_proc_id = 1201;
+
+        node.node_type = 16;
+        node.position = Vec3::new(1., 2., 3.);
+        match scn_node_kind(&node) {
+            ScnNodeKind::ItemTrigger {
+                position,
+                sce_proc_id,
+            } => assert_eq!(
+                (position.x, position.y, position.z, sce_proc_id),
+                (1., 2., 3., 1201)
+            ),
+            kind => panic!("{:?}", kind),
+        }
+
+        node.node_type = 20;
+        node.aabb_trigger_coord2 = Vec3::new(4., 5., 6.);
+        match scn_node_kind(&node) {
+            ScnNodeKind::AabbTrigger {
+                coord2,
+                sce_proc_id,
+                ..
+            } => assert_eq!(
+                (coord2.x, coord2.y, coord2.z, sce_proc_id),
+                (4., 5., 6., 1201)
+            ),
+            kind => panic!("{:?}", kind),
+        }
+
+        node.node_type = 25;
+        assert!(matches!(
+            scn_node_kind(&node),
+            ScnNodeKind::Invisible { node_type: 25 }
+        ));
+        assert_eq!(
+            scn_node_model(&node),
+            ScnNodeModel::ItemPol("你".to_string())
+        );
+
+        node.node_type = 43;
+        assert!(matches!(
+            scn_node_kind(&node),
+            ScnNodeKind::Ignored { node_type: 43 }
+        ));
+        assert_eq!(scn_node_model(&node), ScnNodeModel::None);
+
+        node.node_type = 99;
+        assert!(matches!(
+            scn_node_kind(&node),
+            ScnNodeKind::Unknown { node_type: 99 }
+        ));
+    }
+
+    #[test]
+    fn nav_trigger_needs_a_range() {
+        let scn = scn_load_from_data(scn_with_garbage_in_names()).unwrap();
+        let mut node = scn.nodes[0].clone();
+        node.sce_proc_id = 7;
+
+        node.nav_trigger_coord_min = (0, 0);
+        node.nav_trigger_coord_max = (0, 0);
+        assert_eq!(scn_node_nav_trigger(&node), None);
+
+        node.nav_trigger_coord_min = (10, 20);
+        node.nav_trigger_coord_max = (12, 24);
+        assert_eq!(
+            scn_node_nav_trigger(&node),
+            Some(ScnNavTrigger {
+                coord_min: (10, 20),
+                coord_max: (12, 24),
+                sce_proc_id: 7,
+            })
+        );
     }
 }
@@ -0,0 +1,611 @@
+use super::{SceOpcode, SceParamType};
+use crate::loaders::sce_loader::{
+    SceFile, SceLocalVar, SceProc, SceProcHeader, SCE_PROC_NAME_SIZE,
+};
+use crate::utilities::{decode_gbk, encode_gbk};
+use byteorder::{LittleEndian, WriteBytesExt};
+use std::{
+    collections::HashMap,
+    error::Error,
+    fmt::{Display, Formatter},
+};
+
+#[derive(Debug)]
+pub struct SceAsmError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl SceAsmError {
+    fn new<S: Into<String>>(line: usize, message: S) -> Self {
+        Self {
+            line,
+            message: message.into(),
+        }
+    }
+}
+
+impl Display for SceAsmError {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl Error for SceAsmError {}
+
+/// Assembles a listing in the syntax produced by `sce_disassemble`.
+/// The header offsets are left as 0 and are filled in by `sce_write`.
+pub fn sce_assemble(listing: &str) -> Result<SceFile, SceAsmError> {
+    let mut proc_headers = vec![];
+    let mut procs = HashMap::new();
+    let mut current: Option<ProcBuilder> = None;
+
+    for (index, text) in listing.lines().enumerate() {
+        let line = index + 1;
+        let tokens = tokenize(text, line)?;
+        let mut cursor = Cursor {
+            tokens: &tokens,
+            position: 0,
+            line,
+        };
+
+        // Skip the offset annotation, e.g. `[001c]`
+        if cursor.peek() == Some(&Token::Punct('[')) {
+            cursor.next();
+            cursor.expect_word()?;
+            cursor.expect_punct(']')?;
+        }
+
+        let first = match cursor.next() {
+            Some(Token::Word(w)) => w.clone(),
+            Some(_) => return Err(SceAsmError::new(line, "Syntax error")),
+            None => continue,
+        };
+
+        if first == ".proc" {
+            let id = parse_int(&cursor.expect_word()?, 0, std::u32::MAX as i64, line)? as u32;
+            let mut raw_name = cursor.expect_string()?;
+            cursor.expect_end()?;
+            if raw_name.len() > SCE_PROC_NAME_SIZE {
+                return Err(SceAsmError::new(line, "The proc name is too long"));
+            }
+
+            raw_name.resize(SCE_PROC_NAME_SIZE, 0);
+
+            if let Some(builder) = current.take() {
+                let proc = builder.finish()?;
+                procs.insert(proc.id, proc);
+            }
+
+            if proc_headers.iter().any(|h: &SceProcHeader| h.id == id) {
+                return Err(SceAsmError::new(line, format!("Duplicate proc {}", id)));
+            }
+
+            // Without `.name`, the proc is named after the header
+            let name = decode_gbk(&raw_name);
+            let name_len = raw_name
+                .iter()
+                .position(|&c| c == 0)
+                .map_or(raw_name.len(), |end| end + 1);
+            current = Some(ProcBuilder::new(
+                id,
+                name.clone(),
+                raw_name[..name_len].to_vec(),
+            ));
+            proc_headers.push(SceProcHeader {
+                id,
+                offset: 0,
+                name,
+                raw_name,
+            });
+            continue;
+        }
+
+        let builder = current
+            .as_mut()
+            .ok_or(SceAsmError::new(line, "Expected .proc"))?;
+        match first.as_str() {
+            ".name" => {
+                let raw_name = cursor.expect_string()?;
+                cursor.expect_end()?;
+                if raw_name.len() > std::u16::MAX as usize {
+                    return Err(SceAsmError::new(line, "The proc name is too long"));
+                }
+
+                builder.proc.name = decode_gbk(&raw_name);
+                builder.proc.raw_name = raw_name;
+            }
+            ".local" => {
+                let unknown = parse_int(&cursor.expect_word()?, 0, 255, line)? as u8;
+                let unknown_vec = match cursor.next() {
+                    Some(Token::Hex(bytes)) => bytes.clone(),
+                    _ => return Err(SceAsmError::new(line, "Expected a hex literal")),
+                };
+                cursor.expect_end()?;
+                builder.proc.local_vars.push(SceLocalVar {
+                    unknown,
+                    unknown_vec,
+                });
+            }
+            ".byte" => {
+                while cursor.peek().is_some() {
+                    let word = cursor.expect_word()?;
+                    let byte = u8::from_str_radix(&word, 16)
+                        .map_err(|_| SceAsmError::new(line, format!("Bad byte {}", word)))?;
+                    builder.proc.inst.push(byte);
+                }
+            }
+            _ if first.starts_with('.') => {
+                return Err(SceAsmError::new(
+                    line,
+                    format!("Unknown directive {}", first),
+                ))
+            }
+            _ if cursor.peek() == Some(&Token::Punct(':'))
+                && tokens.len() == cursor.position + 1 =>
+            {
+                if builder
+                    .labels
+                    .insert(first.clone(), builder.proc.inst.len())
+                    .is_some()
+                {
+                    return Err(SceAsmError::new(line, format!("Duplicate label {}", first)));
+                }
+            }
+            _ => builder.assemble_instruction(&first, &mut cursor)?,
+        }
+    }
+
+    if let Some(builder) = current.take() {
+        let proc = builder.finish()?;
+        procs.insert(proc.id, proc);
+    }
+
+    Ok(SceFile {
+        proc_num: proc_headers.len() as u16,
+        proc_headers,
+        procs,
+    })
+}
+
+struct ProcBuilder {
+    proc: SceProc,
+    labels: HashMap<String, usize>,
+    fixups: Vec<(usize, String, usize)>,
+}
+
+impl ProcBuilder {
+    fn new(id: u32, name: String, raw_name: Vec<u8>) -> Self {
+        Self {
+            proc: SceProc {
+                id,
+                name,
+                raw_name,
+                local_vars: vec![],
+                inst: vec![],
+            },
+            labels: HashMap::new(),
+            fixups: vec![],
+        }
+    }
+
+    fn assemble_instruction(&mut self, name: &str, cursor: &mut Cursor) -> Result<(), SceAsmError> {
+        let line = cursor.line;
+        let opcode = SceOpcode::find_by_name(name)
+            .ok_or(SceAsmError::new(line, format!("Unknown opcode {}", name)))?;
+
+        let mut code = opcode.code as u32;
+        if cursor.peek() == Some(&Token::Punct('/')) {
+            cursor.next();
+            code |= (parse_int(&cursor.expect_word()?, 0, 0xffff, line)? as u32) << 16;
+        }
+
+        let inst = &mut self.proc.inst;
+        inst.write_u32::<LittleEndian>(code).unwrap();
+        for (i, param) in opcode.params.iter().enumerate() {
+            if i > 0 {
+                cursor.expect_punct(',')?;
+            }
+
+            match param {
+                SceParamType::I32 => {
+                    let v = parse_int(
+                        &cursor.expect_word()?,
+                        std::i32::MIN as i64,
+                        std::u32::MAX as i64,
+                        line,
+                    )?;
+                    inst.write_i32::<LittleEndian>(v as i32).unwrap();
+                }
+                SceParamType::U32 => {
+                    let v = parse_int(&cursor.expect_word()?, 0, std::u32::MAX as i64, line)?;
+                    inst.write_u32::<LittleEndian>(v as u32).unwrap();
+                }
+                SceParamType::F32 => {
+                    let word = cursor.expect_word()?;
+                    let v = if word == "nan" {
+                        cursor.expect_punct('(')?;
+                        let bits =
+                            parse_int(&cursor.expect_word()?, 0, std::u32::MAX as i64, line)?;
+                        cursor.expect_punct(')')?;
+                        f32::from_bits(bits as u32)
+                    } else {
+                        word.parse::<f32>()
+                            .map_err(|_| SceAsmError::new(line, format!("Bad float {}", word)))?
+                    };
+                    inst.write_f32::<LittleEndian>(v).unwrap();
+                }
+                SceParamType::Var => {
+                    let mut word = cursor.expect_word()?;
+                    if word == "local" || word == "global" {
+                        cursor.expect_punct(':')?;
+                        word = cursor.expect_word()?;
+                    }
+
+                    let v = parse_int(&word, std::i16::MIN as i64, std::i16::MAX as i64, line)?;
+                    inst.write_i16::<LittleEndian>(v as i16).unwrap();
+                }
+                SceParamType::Addr => {
+                    let word = cursor.expect_word()?;
+                    let addr = match parse_int(&word, 0, std::u32::MAX as i64, line) {
+                        Ok(addr) => addr as u32,
+                        Err(_) => {
+                            self.fixups.push((inst.len(), word, line));
+                            0
+                        }
+                    };
+                    inst.write_u32::<LittleEndian>(addr).unwrap();
+                }
+                SceParamType::String => {
+                    let s = cursor.expect_string()?;
+                    write_string(inst, &s, line)?;
+                }
+                SceParamType::List => {
+                    cursor.expect_punct('[')?;
+                    let mut items = vec![];
+                    while cursor.peek() != Some(&Token::Punct(']')) {
+                        if !items.is_empty() {
+                            cursor.expect_punct(',')?;
+                        }
+
+                        let prefix = match cursor.peek() {
+                            Some(Token::Word(_)) => {
+                                let prefix = parse_int(&cursor.expect_word()?, 0, 255, line)?;
+                                cursor.expect_punct(':')?;
+                                prefix as u8
+                            }
+                            _ => 0,
+                        };
+                        items.push((prefix, cursor.expect_string()?));
+                    }
+
+                    cursor.expect_punct(']')?;
+                    inst.write_u16::<LittleEndian>(items.len() as u16).unwrap();
+                    for (prefix, s) in items {
+                        inst.push(prefix);
+                        write_string(inst, &s, line)?;
+                    }
+                }
+            }
+        }
+
+        cursor.expect_end()
+    }
+
+    fn finish(mut self) -> Result<SceProc, SceAsmError> {
+        for (position, label, line) in &self.fixups {
+            let addr = self
+                .labels
+                .get(label)
+                .ok_or(SceAsmError::new(*line, format!("Unknown label {}", label)))?;
+            self.proc.inst[*position..*position + 4].copy_from_slice(&(*addr as u32).to_le_bytes());
+        }
+
+        Ok(self.proc)
+    }
+}
+
+fn write_string(inst: &mut Vec<u8>, s: &[u8], line: usize) -> Result<(), SceAsmError> {
+    if s.len() > std::u16::MAX as usize {
+        return Err(SceAsmError::new(line, "String is too long"));
+    }
+
+    inst.write_u16::<LittleEndian>(s.len() as u16).unwrap();
+    inst.extend_from_slice(s);
+    Ok(())
+}
+
+fn parse_int(word: &str, min: i64, max: i64, line: usize) -> Result<i64, SceAsmError> {
+    let (negative, digits) = match word.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, word),
+    };
+
+    let value = match digits.strip_prefix("0x") {
+        Some(hex) => i64::from_str_radix(hex, 16),
+        None => digits.parse::<i64>(),
+    }
+    .map(|v| if negative { -v } else { v })
+    .map_err(|_| SceAsmError::new(line, format!("Bad number {}", word)))?;
+
+    if value < min || value > max {
+        return Err(SceAsmError::new(line, format!("{} is out of range", word)));
+    }
+
+    Ok(value)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    Text(String),
+    Hex(Vec<u8>),
+    Punct(char),
+}
+
+struct Cursor<'a> {
+    tokens: &'a [Token],
+    position: usize,
+    line: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn peek(&self) -> Option<&'a Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<&'a Token> {
+        let token = self.tokens.get(self.position);
+        self.position += 1;
+        token
+    }
+
+    fn expect_word(&mut self) -> Result<String, SceAsmError> {
+        match self.next() {
+            Some(Token::Word(w)) => Ok(w.clone()),
+            _ => Err(SceAsmError::new(self.line, "Expected a word or a number")),
+        }
+    }
+
+    // Returns the raw bytes including the terminating `\0`
+    fn expect_string(&mut self) -> Result<Vec<u8>, SceAsmError> {
+        match self.next() {
+            Some(Token::Text(t)) => {
+                let mut bytes = encode_gbk(t)
+                    .map_err(|_| SceAsmError::new(self.line, "Cannot encode the string as GBK"))?;
+                bytes.push(0);
+                Ok(bytes)
+            }
+            Some(Token::Hex(bytes)) => Ok(bytes.clone()),
+            _ => Err(SceAsmError::new(self.line, "Expected a string")),
+        }
+    }
+
+    fn expect_punct(&mut self, c: char) -> Result<(), SceAsmError> {
+        match self.next() {
+            Some(Token::Punct(p)) if *p == c => Ok(()),
+            _ => Err(SceAsmError::new(self.line, format!("Expected '{}'", c))),
+        }
+    }
+
+    fn expect_end(&self) -> Result<(), SceAsmError> {
+        if self.position < self.tokens.len() {
+            Err(SceAsmError::new(self.line, "Unexpected trailing tokens"))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '.' || c == '-' || c == '+'
+}
+
+fn tokenize(text: &str, line: usize) -> Result<Vec<Token>, SceAsmError> {
+    let mut tokens = vec![];
+    let mut chars = text.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c == ';' {
+            break;
+        } else if c == '"' {
+            chars.next();
+            tokens.push(Token::Text(read_quoted(&mut chars, line)?));
+        } else if is_word_char(c) {
+            let mut word = String::new();
+            while let Some(&c) = chars.peek() {
+                if !is_word_char(c) {
+                    break;
+                }
+
+                word.push(c);
+                chars.next();
+            }
+
+            if word == "h" && chars.peek() == Some(&'"') {
+                chars.next();
+                let hex = read_quoted(&mut chars, line)?;
+                tokens.push(Token::Hex(parse_hex(&hex, line)?));
+            } else {
+                tokens.push(Token::Word(word));
+            }
+        } else if ",:[]/()".contains(c) {
+            chars.next();
+            tokens.push(Token::Punct(c));
+        } else {
+            return Err(SceAsmError::new(
+                line,
+                format!("Unexpected character {}", c),
+            ));
+        }
+    }
+
+    Ok(tokens)
+}
+
+fn read_quoted<I: Iterator<Item = char>>(
+    chars: &mut std::iter::Peekable<I>,
+    line: usize,
+) -> Result<String, SceAsmError> {
+    let mut text = String::new();
+    loop {
+        match chars.next() {
+            Some('"') => return Ok(text),
+            Some('\\') => match chars.next() {
+                Some('"') => text.push('"'),
+                Some('\\') => text.push('\\'),
+                Some('n') => text.push('\n'),
+                Some('r') => text.push('\r'),
+                Some('t') => text.push('\t'),
+                Some('x') => {
+                    let hex: String = chars.by_ref().take(2).collect();
+                    match u8::from_str_radix(&hex, 16) {
+                        Ok(b) if b < 0x80 => text.push(b as char),
+                        _ => return Err(SceAsmError::new(line, format!("Bad escape \\x{}", hex))),
+                    }
+                }
+                _ => return Err(SceAsmError::new(line, "Bad escape")),
+            },
+            Some(c) => text.push(c),
+            None => return Err(SceAsmError::new(line, "Unterminated string")),
+        }
+    }
+}
+
+fn parse_hex(hex: &str, line: usize) -> Result<Vec<u8>, SceAsmError> {
+    if hex.len() % 2 != 0 || !hex.is_ascii() {
+        return Err(SceAsmError::new(line, "Bad hex literal"));
+    }
+
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| {
+            u8::from_str_radix(&hex[i..i + 2], 16)
+                .map_err(|_| SceAsmError::new(line, "Bad hex literal"))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::loaders::sce_loader::{sce_load_from_data, sce_write, SceFile};
+    use crate::sce::{sce_assemble, sce_disassemble, SceArg, SceInstruction};
+    use byteorder::{LittleEndian, WriteBytesExt};
+    use encoding::{EncoderTrap, Encoding};
+
+    fn proc_body(id: u32, raw_name: &[u8], inst: &[u8]) -> Vec<u8> {
+        let mut body = vec![];
+        body.write_u32::<LittleEndian>(id).unwrap();
+        body.write_u16::<LittleEndian>(raw_name.len() as u16)
+            .unwrap();
+        body.extend_from_slice(raw_name);
+        body.write_u16::<LittleEndian>(1).unwrap();
+        body.push(3);
+        body.write_u16::<LittleEndian>(2).unwrap();
+        body.extend_from_slice(&[0xab, 0xcd]);
+        body.write_u32::<LittleEndian>(inst.len() as u32).unwrap();
+        body.extend_from_slice(inst);
+        body
+    }
+
+    fn header_name(text: &[u8], garbage: &[u8]) -> Vec<u8> {
+        let mut name = text.to_vec();
+        name.push(0);
+        name.extend_from_slice(garbage);
+        name.resize(64, 0);
+        name
+    }
+
+    // The names carry the kind of stray bytes the game files have: garbage
+    // after the `\0`, a field without a terminator and text that isn't GBK
+    fn sce_with_stray_name_bytes() -> Vec<u8> {
+        let mut inst = vec![];
+        // Let local:1, 5
+        inst.write_u32::<LittleEndian>(13).unwrap();
+        inst.write_i16::<LittleEndian>(1).unwrap();
+        inst.write_i32::<LittleEndian>(5).unwrap();
+        // Dlg "\xc4\xe3\xba\xc3"
+        inst.write_u32::<LittleEndian>(62).unwrap();
+        inst.write_u16::<LittleEndian>(5).unwrap();
+        inst.extend_from_slice(&[0xc4, 0xe3, 0xba, 0xc3, 0]);
+        // Goto 0
+        inst.write_u32::<LittleEndian>(3).unwrap();
+        inst.write_u32::<LittleEndian>(0).unwrap();
+        // An unknown opcode with a trailing byte
+        inst.extend_from_slice(&[0xff, 0xff, 0, 0, 0x42]);
+
+        let procs = vec![
+            (
+                1,
+                header_name(b"_main", b"\x12\x34"),
+                proc_body(1, b"_main\0xy", &inst),
+            ),
+            (2, vec![b'a'; 64], proc_body(2, b"noterm", &[])),
+            (
+                3,
+                header_name(&[0xc4, 0xe3], &[]),
+                proc_body(3, &[0xff, 0xfe, 0], &inst[..10]),
+            ),
+        ];
+
+        let mut data = b"SCE\0".to_vec();
+        data.push(1);
+        data.write_u16::<LittleEndian>(procs.len() as u16).unwrap();
+        let mut offset = 7 + procs.len() * 72;
+        for (id, name, body) in &procs {
+            data.write_u32::<LittleEndian>(*id).unwrap();
+            data.write_u32::<LittleEndian>(offset as u32).unwrap();
+            data.extend_from_slice(name);
+            offset += body.len();
+        }
+
+        for (_, _, body) in &procs {
+            data.extend_from_slice(body);
+        }
+
+        data
+    }
+
+    fn assemble_and_write(listing: &str) -> Vec<u8> {
+        let assembled = sce_assemble(listing).unwrap();
+        let mut written = vec![];
+        sce_write(&assembled, &mut written).unwrap();
+        written
+    }
+
+    #[test]
+    fn unedited_listing_assembles_to_the_same_bytes() {
+        let data = sce_with_stray_name_bytes();
+        let sce = sce_load_from_data(data.clone()).unwrap();
+        assert_eq!(sce.proc_headers[0].name, "_main");
+        assert_eq!(sce.procs[&2].name, "noterm");
+
+        let listing = sce_disassemble(&sce);
+        assert_eq!(assemble_and_write(&listing), data, "{}", listing);
+    }
+
+    #[test]
+    fn edited_dialog_reloads_with_the_new_text() {
+        let original = sce_load_from_data(sce_with_stray_name_bytes()).unwrap();
+        let listing = sce_disassemble(&original).replace("Dlg \"你好\"", "Dlg \"好久不见\"");
+        let edited: SceFile = sce_load_from_data(assemble_and_write(&listing)).unwrap();
+
+        // The longer text moves the Goto and the procs that follow
+        let main = &edited.procs[&1];
+        let dlg = SceInstruction::decode(&main.inst, 10).unwrap();
+        let mut text = encoding::all::GBK
+            .encode("好久不见", EncoderTrap::Strict)
+            .unwrap();
+        text.push(0);
+        assert_eq!(dlg.args, vec![SceArg::String(text)]);
+        let goto = SceInstruction::decode(&main.inst, dlg.next_offset()).unwrap();
+        assert_eq!(goto.opcode.name, "Goto");
+        assert_eq!(goto.args, vec![SceArg::Addr(0)]);
+
+        for id in &[2, 3] {
+            assert_eq!(edited.procs[id].raw_name, original.procs[id].raw_name);
+            assert_eq!(edited.procs[id].inst, original.procs[id].inst);
+        }
+    }
+}
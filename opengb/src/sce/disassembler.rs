@@ -0,0 +1,183 @@
+use super::{SceArg, SceInstruction};
+use crate::loaders::sce_loader::{SceFile, SceProc};
+use encoding::{DecoderTrap, EncoderTrap, Encoding};
+use std::{collections::BTreeSet, fmt::Write};
+
+pub fn sce_disassemble(sce: &SceFile) -> String {
+    let mut listing = String::new();
+    writeln!(listing, "; proc_num: {}", sce.proc_num).unwrap();
+    for header in &sce.proc_headers {
+        writeln!(listing).unwrap();
+        writeln!(
+            listing,
+            ".proc {} {} ; offset {:#x}",
+            header.id,
+            format_header_name(&header.raw_name),
+            header.offset
+        )
+        .unwrap();
+
+        match sce.procs.get(&header.id) {
+            Some(proc) => listing.push_str(&sce_disassemble_proc(proc)),
+            None => writeln!(listing, "; proc body is missing").unwrap(),
+        }
+    }
+
+    listing
+}
+
+pub fn sce_disassemble_proc(proc: &SceProc) -> String {
+    let mut listing = String::new();
+    writeln!(listing, ".name {}", format_sce_string(&proc.raw_name)).unwrap();
+    for var in &proc.local_vars {
+        writeln!(
+            listing,
+            ".local {} {}",
+            var.unknown,
+            hex_literal(&var.unknown_vec)
+        )
+        .unwrap();
+    }
+
+    let mut instructions = vec![];
+    let mut offset = 0;
+    let mut error = None;
+    while offset < proc.inst.len() {
+        match SceInstruction::decode(&proc.inst, offset) {
+            Ok(inst) => {
+                offset = inst.next_offset();
+                instructions.push(inst);
+            }
+            Err(e) => {
+                error = Some(e);
+                break;
+            }
+        }
+    }
+
+    let starts: BTreeSet<usize> = instructions.iter().map(|i| i.offset).collect();
+    let labels: BTreeSet<usize> = instructions
+        .iter()
+        .flat_map(|i| i.args.iter())
+        .filter_map(|arg| match arg {
+            SceArg::Addr(addr) if starts.contains(&(*addr as usize)) => Some(*addr as usize),
+            _ => None,
+        })
+        .collect();
+
+    for inst in &instructions {
+        if labels.contains(&inst.offset) {
+            writeln!(listing, "{}:", label_name(inst.offset)).unwrap();
+        }
+
+        let mut line = format!("    [{:04x}] {}", inst.offset, inst.opcode.name);
+        if inst.variant() != 0 {
+            write!(line, "/{}", inst.variant()).unwrap();
+        }
+
+        let args: Vec<String> = inst
+            .args
+            .iter()
+            .map(|arg| format_arg(arg, &labels))
+            .collect();
+        if !args.is_empty() {
+            write!(line, " {}", args.join(", ")).unwrap();
+        }
+
+        writeln!(listing, "{}", line).unwrap();
+    }
+
+    // Keep the bytes that cannot be decoded so that nothing is lost
+    if let Some(e) = error {
+        writeln!(listing, "    ; {}", e).unwrap();
+        for chunk in proc.inst[offset..].chunks(16) {
+            let bytes: Vec<String> = chunk.iter().map(|b| format!("{:02x}", b)).collect();
+            writeln!(listing, "    [{:04x}] .byte {}", offset, bytes.join(" ")).unwrap();
+            offset += chunk.len();
+        }
+    }
+
+    listing
+}
+
+fn format_arg(arg: &SceArg, labels: &BTreeSet<usize>) -> String {
+    match arg {
+        SceArg::I32(v) => v.to_string(),
+        SceArg::U32(v) => v.to_string(),
+        SceArg::F32(v) if v.is_nan() => format!("nan({:#x})", v.to_bits()),
+        SceArg::F32(v) => format!("{:?}", v),
+        SceArg::Var(v) if *v < 0 => format!("global:{}", v),
+        SceArg::Var(v) => format!("local:{}", v),
+        SceArg::Addr(addr) if labels.contains(&(*addr as usize)) => label_name(*addr as usize),
+        SceArg::Addr(addr) => format!("{:#x}", addr),
+        SceArg::String(s) => format_sce_string(s),
+        SceArg::List(list) => {
+            let items: Vec<String> = list
+                .iter()
+                .map(|(prefix, s)| match prefix {
+                    0 => format_sce_string(s),
+                    _ => format!("{}:{}", prefix, format_sce_string(s)),
+                })
+                .collect();
+            format!("[{}]", items.join(", "))
+        }
+    }
+}
+
+fn label_name(offset: usize) -> String {
+    format!("label_{:04x}", offset)
+}
+
+// Strings are shown as text when they can be encoded back to the same bytes,
+// otherwise as a hex literal.
+fn format_sce_string(s: &[u8]) -> String {
+    if let Some((&0, text)) = s.split_last() {
+        if !text.contains(&0) {
+            if let Ok(decoded) = encoding::all::GBK.decode(text, DecoderTrap::Strict) {
+                let encoded = encoding::all::GBK.encode(&decoded, EncoderTrap::Strict);
+                if encoded.ok().as_deref() == Some(text) {
+                    return quote(&decoded);
+                }
+            }
+        }
+    }
+
+    hex_literal(s)
+}
+
+// The header name is a `\0` padded field, which is shown as text only when
+// the padding is all zeros
+fn format_header_name(raw: &[u8]) -> String {
+    if let Some(end) = raw.iter().position(|&c| c == 0) {
+        if raw[end..].iter().all(|&c| c == 0) {
+            return format_sce_string(&raw[..=end]);
+        }
+    }
+
+    hex_literal(raw)
+}
+
+fn hex_literal(bytes: &[u8]) -> String {
+    let hex: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+    format!("h\"{}\"", hex)
+}
+
+fn quote(text: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if (c as u32) < 0x20 || c as u32 == 0x7f => {
+                write!(quoted, "\\x{:02x}", c as u32).unwrap()
+            }
+            c => quoted.push(c),
+        }
+    }
+
+    quoted.push('"');
+    quoted
+}
@@ -0,0 +1,204 @@
+use super::{SceOpcode, SceParamType};
+use byteorder::{LittleEndian, ReadBytesExt};
+use encoding::{DecoderTrap, Encoding};
+use std::{
+    error::Error,
+    fmt::{Display, Formatter},
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SceArg {
+    I32(i32),
+    U32(u32),
+    F32(f32),
+    Var(i16),
+    Addr(u32),
+    // The raw GBK bytes including the terminating `\0`
+    String(Vec<u8>),
+    // Every item is prefixed with a byte whose meaning is unknown
+    List(Vec<(u8, Vec<u8>)>),
+}
+
+#[derive(Debug, Clone)]
+pub struct SceInstruction {
+    pub offset: usize,
+    pub code: i32,
+    pub opcode: &'static SceOpcode,
+    pub args: Vec<SceArg>,
+    pub size: usize,
+}
+
+impl SceInstruction {
+    pub fn decode(inst: &[u8], offset: usize) -> Result<Self, SceDecodeError> {
+        let mut reader = InstReader {
+            inst,
+            position: offset,
+        };
+
+        let code = reader.read(4)?.read_i32::<LittleEndian>().unwrap();
+        let opcode =
+            SceOpcode::find(code as u16).ok_or(SceDecodeError::UnknownOpcode { offset, code })?;
+
+        let mut args = vec![];
+        for param in opcode.params {
+            let arg = match param {
+                SceParamType::I32 => {
+                    SceArg::I32(reader.read(4)?.read_i32::<LittleEndian>().unwrap())
+                }
+                SceParamType::U32 => {
+                    SceArg::U32(reader.read(4)?.read_u32::<LittleEndian>().unwrap())
+                }
+                SceParamType::F32 => {
+                    SceArg::F32(reader.read(4)?.read_f32::<LittleEndian>().unwrap())
+                }
+                SceParamType::Var => {
+                    SceArg::Var(reader.read(2)?.read_i16::<LittleEndian>().unwrap())
+                }
+                SceParamType::Addr => {
+                    SceArg::Addr(reader.read(4)?.read_u32::<LittleEndian>().unwrap())
+                }
+                SceParamType::String => SceArg::String(reader.read_string()?),
+                SceParamType::List => {
+                    let count = reader.read(2)?.read_u16::<LittleEndian>().unwrap();
+                    let mut list = vec![];
+                    for _ in 0..count {
+                        let prefix = reader.read(1)?[0];
+                        list.push((prefix, reader.read_string()?));
+                    }
+
+                    SceArg::List(list)
+                }
+            };
+
+            args.push(arg);
+        }
+
+        Ok(Self {
+            offset,
+            code,
+            opcode,
+            args,
+            size: reader.position - offset,
+        })
+    }
+
+    // Some opcodes have variants with a non-zero high word, e.g. 65542 for Gt
+    pub fn variant(&self) -> u16 {
+        (self.code as u32 >> 16) as u16
+    }
+
+    pub fn next_offset(&self) -> usize {
+        self.offset + self.size
+    }
+}
+
+pub trait FromSceArg: Sized {
+    fn from_arg(arg: &SceArg) -> Option<Self>;
+}
+
+impl FromSceArg for i32 {
+    fn from_arg(arg: &SceArg) -> Option<Self> {
+        match arg {
+            SceArg::I32(v) => Some(*v),
+            _ => None,
+        }
+    }
+}
+
+impl FromSceArg for u32 {
+    fn from_arg(arg: &SceArg) -> Option<Self> {
+        match arg {
+            SceArg::U32(v) | SceArg::Addr(v) => Some(*v),
+            _ => None,
+        }
+    }
+}
+
+impl FromSceArg for f32 {
+    fn from_arg(arg: &SceArg) -> Option<Self> {
+        match arg {
+            SceArg::F32(v) => Some(*v),
+            _ => None,
+        }
+    }
+}
+
+impl FromSceArg for i16 {
+    fn from_arg(arg: &SceArg) -> Option<Self> {
+        match arg {
+            SceArg::Var(v) => Some(*v),
+            _ => None,
+        }
+    }
+}
+
+impl FromSceArg for String {
+    fn from_arg(arg: &SceArg) -> Option<Self> {
+        match arg {
+            SceArg::String(s) => Some(decode_sce_string(s)),
+            _ => None,
+        }
+    }
+}
+
+impl FromSceArg for Vec<String> {
+    fn from_arg(arg: &SceArg) -> Option<Self> {
+        match arg {
+            SceArg::List(list) => Some(list.iter().map(|(_, s)| decode_sce_string(s)).collect()),
+            _ => None,
+        }
+    }
+}
+
+pub fn decode_sce_string(s: &[u8]) -> String {
+    let len = s.len().saturating_sub(1);
+    encoding::all::GBK
+        .decode(&s[..len], DecoderTrap::Ignore)
+        .unwrap()
+}
+
+#[derive(Debug)]
+pub enum SceDecodeError {
+    UnknownOpcode { offset: usize, code: i32 },
+    TruncatedData { offset: usize },
+}
+
+impl Display for SceDecodeError {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            SceDecodeError::UnknownOpcode { offset, code } => {
+                write!(f, "{:#x}: unknown opcode {}", offset, code)
+            }
+            SceDecodeError::TruncatedData { offset } => {
+                write!(f, "{:#x}: unexpected end of instructions", offset)
+            }
+        }
+    }
+}
+
+impl Error for SceDecodeError {}
+
+struct InstReader<'a> {
+    inst: &'a [u8],
+    position: usize,
+}
+
+impl<'a> InstReader<'a> {
+    fn read(&mut self, count: usize) -> Result<&'a [u8], SceDecodeError> {
+        let end = self.position + count;
+        if end > self.inst.len() {
+            return Err(SceDecodeError::TruncatedData {
+                offset: self.position,
+            });
+        }
+
+        let ret = &self.inst[self.position..end];
+        self.position = end;
+        Ok(ret)
+    }
+
+    fn read_string(&mut self) -> Result<Vec<u8>, SceDecodeError> {
+        let len = self.read(2)?.read_u16::<LittleEndian>().unwrap();
+        Ok(self.read(len as usize)?.to_vec())
+    }
+}
@@ -0,0 +1,9 @@
+pub use assembler::{sce_assemble, SceAsmError};
+pub use disassembler::{sce_disassemble, sce_disassemble_proc};
+pub use instruction::{decode_sce_string, FromSceArg, SceArg, SceDecodeError, SceInstruction};
+pub use opcodes::{SceOpcode, SceParamType, SCE_OPCODES};
+
+mod assembler;
+mod disassembler;
+mod instruction;
+mod opcodes;
@@ -0,0 +1,116 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SceParamType {
+    I32,
+    U32,
+    F32,
+    // A variable id. Negative ids are globals and the others are locals.
+    Var,
+    // An offset into the instructions of the current proc
+    Addr,
+    String,
+    List,
+}
+
+#[derive(Debug)]
+pub struct SceOpcode {
+    pub code: u16,
+    pub name: &'static str,
+    pub params: &'static [SceParamType],
+}
+
+impl SceOpcode {
+    pub fn find(code: u16) -> Option<&'static SceOpcode> {
+        SCE_OPCODES.iter().find(|op| op.code == code)
+    }
+
+    pub fn find_by_name(name: &str) -> Option<&'static SceOpcode> {
+        SCE_OPCODES
+            .iter()
+            .find(|op| op.name.eq_ignore_ascii_case(name))
+    }
+}
+
+macro_rules! opcodes {
+    ($($code: expr => $name: ident ($($param: ident),*)),* $(,)*) => {
+        pub static SCE_OPCODES: &[SceOpcode] = &[
+            $(SceOpcode {
+                code: $code,
+                name: stringify!($name),
+                params: &[$(SceParamType::$param),*],
+            },)*
+        ];
+    };
+}
+
+opcodes! {
+    1 => Idle(F32),
+    2 => ScriptRunMode(I32),
+    3 => Goto(Addr),
+    5 => Fop(I32),
+    6 => Gt(Var, I32),
+    7 => Ls(Var, I32),
+    8 => Eq(Var, I32),
+    9 => Neq(Var, I32),
+    10 => Geq(Var, I32),
+    11 => Leq(Var, I32),
+    12 => TestGoto(Addr),
+    13 => Let(Var, I32),
+    16 => Call(U32),
+    17 => Rnd(Var, I32),
+    20 => RolePathTo(I32, I32, I32, I32),
+    21 => RoleSetPos(I32, I32, I32),
+    22 => RoleShowAction(I32, String, I32),
+    23 => RoleSetFace(I32, I32),
+    24 => RoleTurnFace(I32, F32),
+    27 => RoleInput(I32),
+    28 => RoleActive(I32, I32),
+    32 => CameraPush(F32, F32, I32),
+    33 => CameraRotate(F32, F32, F32, I32),
+    34 => CameraMove(F32, F32, F32, F32, F32),
+    35 => CameraWag(F32, F32, F32, I32),
+    36 => CameraSet(F32, F32, F32, F32, F32, F32),
+    37 => CameraDefault(I32),
+    46 => AddItem(I32, I32),
+    62 => Dlg(String),
+    63 => LoadScene(String, String),
+    65 => DlgSel(List),
+    66 => GetDlgSel(Var),
+    67 => DlgFace(I32, String, I32),
+    68 => Note(String),
+    69 => FadeOut(),
+    70 => FadeIn(),
+    71 => RoleStop(I32),
+    72 => RoleEmote(I32, I32),
+    78 => HaveItem(I32),
+    79 => PlaySound(String, I32),
+    85 => ObjectActive(I32, I32),
+    86 => Caption(String, I32),
+    87 => OpenDoor(I32),
+    88 => HyMode(I32),
+    89 => HyFly(F32, F32, F32),
+    90 => ObjectMove(I32, F32, F32, F32, F32),
+    104 => ApprEntry(),
+    108 => GetAppr(Var),
+    115 => Movie(String),
+    116 => SetRoleTexture(I32, String),
+    118 => Quake(F32, F32),
+    124 => Trigger(I32),
+    133 => Music(String, I32),
+    134 => StopMusic(),
+    142 => CEftPos(F32, F32, F32),
+    143 => CEft(I32),
+    148 => CEftLoad(I32),
+    150 => LoadAct(I32, String),
+    201 => RolePathOut(I32, I32, I32, I32),
+    202 => InTeam(I32, I32),
+    204 => RoleCtrl(I32),
+    207 => RoleActAutoStand(I32, I32),
+    208 => RoleMoveBack(I32, F32),
+    209 => RoleFaceRole(I32, I32),
+    210 => RoleTurnFaceA(I32, I32),
+    211 => TeamOpenA(),
+    212 => TeamCloseA(),
+    214 => RoleMoveTo(I32, I32, I32, I32),
+    221 => RoleEndAction(I32),
+    250 => CameraFree(I32),
+}
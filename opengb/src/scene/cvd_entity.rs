@@ -1,37 +1,104 @@
-use crate::loaders::cvd_loader::*;
+use crate::loaders::{ani_loader::*, cvd_loader::*, LoaderError};
 use mini_fs::{MiniFs, StoreExt};
-use radiance::math::{Vec2, Vec3};
+use radiance::math::{Mat44, Quaternion, Vec2, Vec3};
 use radiance::scene::{CoreEntity, EntityExtension};
 use radiance::{
-    rendering::{ComponentFactory, MaterialDef, SimpleMaterialDef, VertexBuffer, VertexComponents},
+    rendering::{
+        ComponentFactory, MaterialDef, RenderingComponent, SimpleMaterialDef, VertexBuffer,
+        VertexComponents,
+    },
     scene::Entity,
 };
-use std::{path::Path, rc::Rc};
+use std::{cell::RefCell, path::Path, rc::Rc};
+
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum CvdAnimationState {
+    Stopped,
+    Playing,
+    Paused,
+    Finished,
+}
+
+// Shared by all the nodes of a cvd model so that they play in sync
+struct CvdAnimation {
+    state: CvdAnimationState,
+    looping: bool,
+    time: f32,
+    duration: f32,
+}
+
+impl CvdAnimation {
+    fn new(duration: f32) -> Self {
+        Self {
+            state: CvdAnimationState::Stopped,
+            looping: false,
+            time: 0.,
+            duration,
+        }
+    }
+
+    fn update(&mut self, delta_sec: f32) {
+        if self.state != CvdAnimationState::Playing {
+            return;
+        }
+
+        self.time += delta_sec;
+        if self.time >= self.duration {
+            if self.looping && self.duration > 0. {
+                self.time %= self.duration;
+            } else {
+                self.time = self.duration;
+                self.state = CvdAnimationState::Finished;
+            }
+        }
+    }
+}
 
 pub struct CvdModelEntity {
     component_factory: Rc<dyn ComponentFactory>,
+    animation: Rc<RefCell<CvdAnimation>>,
+    is_root: bool,
+    last_anim_time: f32,
+    scale_factor: f32,
     position_keyframes: Option<CvdPositionKeyFrames>,
     rotation_keyframes: Option<CvdRotationKeyFrames>,
     scale_keyframes: Option<CvdScaleKeyFrames>,
+    morph_timestamps: Vec<f32>,
     meshes: Vec<CvdMesh>,
+    ani: Option<AniFile>,
 }
 
 impl CvdModelEntity {
+    /// The animation stays at its first frame until `play` is called.
     pub fn create<P: AsRef<Path>>(
         component_factory: Rc<dyn ComponentFactory>,
         vfs: &MiniFs,
         path: P,
         name: String,
         visible: bool,
-    ) -> CoreEntity<Self> {
-        let cvd = cvd_load_from_file(vfs, path.as_ref()).unwrap();
+    ) -> Result<CoreEntity<Self>, LoaderError> {
+        let cvd = cvd_load_from_file(vfs, path.as_ref())?;
+        let duration = cvd
+            .models
+            .iter()
+            .map(Self::node_duration)
+            .fold(0., f32::max);
+        let animation = Rc::new(RefCell::new(CvdAnimation::new(duration)));
+        let ani = Self::load_ani(vfs, path.as_ref());
+
         let mut entity = CoreEntity::new(
             Self {
                 component_factory: component_factory.clone(),
+                animation: animation.clone(),
+                is_root: true,
+                last_anim_time: 0.,
+                scale_factor: 1.,
                 position_keyframes: None,
                 rotation_keyframes: None,
                 scale_keyframes: None,
+                morph_timestamps: vec![],
                 meshes: vec![],
+                ani,
             },
             name,
             visible
@@ -40,6 +107,7 @@ impl CvdModelEntity {
         for (i, node) in cvd.models.iter().enumerate() {
             entity.attach(Box::new(Self::new_from_cvd_model_node(
                 component_factory.clone(),
+                &animation,
                 vfs,
                 path.as_ref(),
                 node,
@@ -47,11 +115,82 @@ impl CvdModelEntity {
             )));
         }
 
-        entity
+        Ok(entity)
+    }
+
+    /// Plays the animation from the beginning.
+    pub fn play(&mut self, looping: bool) {
+        let mut animation = self.animation.borrow_mut();
+        animation.time = 0.;
+        animation.looping = looping;
+        animation.state = CvdAnimationState::Playing;
+    }
+
+    pub fn pause(&mut self) {
+        let mut animation = self.animation.borrow_mut();
+        if animation.state == CvdAnimationState::Playing {
+            animation.state = CvdAnimationState::Paused;
+        }
+    }
+
+    pub fn resume(&mut self) {
+        let mut animation = self.animation.borrow_mut();
+        if animation.state == CvdAnimationState::Paused {
+            animation.state = CvdAnimationState::Playing;
+        }
+    }
+
+    /// Stops the animation and goes back to the first frame.
+    pub fn stop(&mut self) {
+        let mut animation = self.animation.borrow_mut();
+        animation.time = 0.;
+        animation.state = CvdAnimationState::Stopped;
+    }
+
+    /// Jumps to the last frame of the animation.
+    pub fn finish(&mut self) {
+        let mut animation = self.animation.borrow_mut();
+        animation.time = animation.duration;
+        animation.state = CvdAnimationState::Finished;
+    }
+
+    pub fn set_looping(&mut self, looping: bool) {
+        self.animation.borrow_mut().looping = looping;
+    }
+
+    pub fn animation_state(&self) -> CvdAnimationState {
+        self.animation.borrow().state
+    }
+
+    pub fn animation_duration(&self) -> f32 {
+        self.animation.borrow().duration
+    }
+
+    /// The `.ani` file next to the cvd file, if there is one. Its layout isn't
+    /// decoded yet, so the animation is still driven by the cvd keyframes.
+    pub fn ani(&self) -> Option<&AniFile> {
+        self.ani.as_ref()
+    }
+
+    fn load_ani(vfs: &MiniFs, cvd_path: &Path) -> Option<AniFile> {
+        let ani_path = ani_path_for_cvd(cvd_path);
+        match ani_load_from_file(vfs, &ani_path) {
+            Ok(ani) => Some(ani),
+            Err(LoaderError::IoError { error, .. })
+                if error.kind() == std::io::ErrorKind::NotFound =>
+            {
+                None
+            }
+            Err(e) => {
+                log::error!("Cannot load ani file {:?}: {}", ani_path, e);
+                None
+            }
+        }
     }
 
     fn new_from_cvd_model_node<P: AsRef<Path>>(
         component_factory: Rc<dyn ComponentFactory>,
+        animation: &Rc<RefCell<CvdAnimation>>,
         vfs: &MiniFs,
         path: P,
         node: &CvdModelNode,
@@ -61,6 +200,7 @@ impl CvdModelEntity {
         let mut position_keyframes = None;
         let mut rotation_keyframes = None;
         let mut scale_keyframes = None;
+        let mut morph_timestamps = vec![];
         let mut meshes = vec![];
         if let Some(model) = &node.model {
             position_keyframes = model.position_keyframes.clone();
@@ -68,21 +208,20 @@ impl CvdModelEntity {
             scale_keyframes = model.scale_keyframes.clone();
 
             for material in &model.mesh.materials {
-                if material.triangles.is_none() {
+                if material.triangles.is_none() || model.mesh.frames.is_empty() {
                     continue;
                 }
 
-                for v in &model.mesh.frames {
-                    let mesh = CvdMesh::new(
-                        v,
-                        material,
-                        Self::load_texture(material, vfs, path.as_ref()),
-                    );
-                    meshes.push(mesh);
+                let mesh = CvdMesh::new(
+                    &model.mesh.frames,
+                    material,
+                    Self::load_texture(material, vfs, path.as_ref()),
+                );
+                meshes.push(mesh);
+            }
 
-                    // TODO: Support multiple frames
-                    break;
-                }
+            if model.mesh.frames.len() > 1 {
+                morph_timestamps = model.mesh.frame_timestamps.clone();
             }
 
             scale_factor = model.scale_factor;
@@ -91,21 +230,28 @@ impl CvdModelEntity {
         let mut entity = CoreEntity::new(
             Self {
                 component_factory: component_factory.clone(),
+                animation: animation.clone(),
+                is_root: false,
+                last_anim_time: 0.,
+                scale_factor,
                 position_keyframes,
                 rotation_keyframes,
                 scale_keyframes,
+                morph_timestamps,
                 meshes,
+                ani: None,
             },
             "cvd_obj".to_string(),
             true
         );
 
-        entity.setup_transform(scale_factor);
+        entity.setup_transform(0.);
 
         if let Some(children) = &node.children {
             for child in children {
                 entity.attach(Box::new(Self::new_from_cvd_model_node(
                     component_factory.clone(),
+                    animation,
                     vfs,
                     path.as_ref(),
                     &child,
@@ -117,45 +263,119 @@ impl CvdModelEntity {
         entity
     }
 
-    pub fn setup_transform(self: &mut CoreEntity<Self>, scale_factor: f32) {
-        if let Some(p) = self
-            .position_keyframes
-            .as_ref()
-            .and_then(|frame| frame.frames.get(0))
-            .and_then(|f| Some(f.position))
-        {
-            self.transform_mut().translate_local(&p);
+    fn node_duration(node: &CvdModelNode) -> f32 {
+        let mut duration = 0f32;
+        if let Some(model) = &node.model {
+            let timestamps = [
+                model
+                    .position_keyframes
+                    .as_ref()
+                    .and_then(|k| k.frames.last())
+                    .map(|f| f.timestamp),
+                model
+                    .rotation_keyframes
+                    .as_ref()
+                    .and_then(|k| k.frames.last())
+                    .map(|f| f.timestamp),
+                model
+                    .scale_keyframes
+                    .as_ref()
+                    .and_then(|k| k.frames.last())
+                    .map(|f| f.timestamp),
+                model
+                    .mesh
+                    .frame_timestamps
+                    .last()
+                    .copied()
+                    .filter(|_| model.mesh.frames.len() > 1),
+            ];
+
+            duration = timestamps.iter().flatten().fold(duration, |d, t| d.max(*t));
         }
 
-        self.transform_mut()
-            .scale_local(&Vec3::new(scale_factor, scale_factor, scale_factor));
+        node.children
+            .iter()
+            .flatten()
+            .map(Self::node_duration)
+            .fold(duration, f32::max)
+    }
+
+    pub fn setup_transform(self: &mut CoreEntity<Self>, anim_time: f32) {
+        let position = self
+            .position_keyframes
+            .as_ref()
+            .and_then(|k| keyframe_position(&k.frames, anim_time, |f| f.timestamp))
+            .map(|(prev, next, ratio)| {
+                let frames = &self.position_keyframes.as_ref().unwrap().frames;
+                Vec3::lerp(&frames[prev].position, &frames[next].position, ratio)
+            });
 
-        if let Some(q) = self
+        let rotation = self
             .rotation_keyframes
             .as_ref()
-            .and_then(|frame| frame.frames.get(0))
-            .and_then(|f| Some(f.quaternion))
-        {
-            self.transform_mut().rotate_quaternion_local(&q);
-        }
+            .and_then(|k| keyframe_position(&k.frames, anim_time, |f| f.timestamp))
+            .map(|(prev, next, ratio)| {
+                let frames = &self.rotation_keyframes.as_ref().unwrap().frames;
+                Quaternion::slerp(&frames[prev].quaternion, &frames[next].quaternion, ratio)
+            });
 
-        if let Some(frame) = self
+        let scale = self
             .scale_keyframes
             .as_ref()
-            .and_then(|frame| frame.frames.get(0))
-        {
-            let scale = frame.scale;
-            let q2 = frame.quaternion;
+            .and_then(|k| keyframe_position(&k.frames, anim_time, |f| f.timestamp))
+            .map(|(prev, next, ratio)| {
+                let frames = &self.scale_keyframes.as_ref().unwrap().frames;
+                (
+                    Quaternion::slerp(&frames[prev].quaternion, &frames[next].quaternion, ratio),
+                    Vec3::lerp(&frames[prev].scale, &frames[next].scale, ratio),
+                )
+            });
+
+        let scale_factor = self.scale_factor;
+        let transform = self.transform_mut();
+        transform.set_matrix(Mat44::new_identity());
+
+        if let Some(p) = position {
+            transform.translate_local(&p);
+        }
+
+        transform.scale_local(&Vec3::new(scale_factor, scale_factor, scale_factor));
+
+        if let Some(q) = rotation {
+            transform.rotate_quaternion_local(&q);
+        }
+
+        if let Some((q2, scale)) = scale {
             let mut q3 = q2;
             q3.inverse();
 
-            self.transform_mut()
+            transform
                 .rotate_quaternion_local(&q2)
                 .scale_local(&scale)
                 .rotate_quaternion_local(&q3);
         }
     }
 
+    fn update_morph(self: &mut CoreEntity<Self>, anim_time: f32) {
+        let position = keyframe_position(&self.morph_timestamps, anim_time, |t| *t);
+        let (prev, next, ratio) = match position {
+            Some(p) => p,
+            None => return,
+        };
+
+        let blended: Vec<VertexBuffer> = self
+            .meshes
+            .iter()
+            .map(|mesh| mesh.blend(prev, next, ratio))
+            .collect();
+
+        if let Some(rc) = self.get_component_mut::<RenderingComponent>() {
+            for (ro, vertices) in rc.render_objects_mut().iter_mut().zip(&blended) {
+                ro.update_vertices(&mut |vb: &mut VertexBuffer| *vb = vertices.clone());
+            }
+        }
+    }
+
     fn load_texture<P: AsRef<Path>>(
         material: &CvdMaterial,
         vfs: &MiniFs,
@@ -185,10 +405,10 @@ impl EntityExtension for CvdModelEntity {
         let mut objects = vec![];
         for mesh in &self.meshes {
             let ro = self.component_factory.create_render_object(
-                mesh.vertices.clone(),
+                mesh.frames[0].clone(),
                 mesh.indices.clone(),
                 &mesh.material,
-                false,
+                mesh.frames.len() > 1,
             );
 
             objects.push(ro);
@@ -197,17 +417,60 @@ impl EntityExtension for CvdModelEntity {
         let component = self.component_factory.create_rendering_component(objects);
         self.add_component(Box::new(component));
     }
+
+    fn on_updating(self: &mut CoreEntity<Self>, delta_sec: f32) {
+        if self.is_root {
+            self.animation.borrow_mut().update(delta_sec);
+            return;
+        }
+
+        let anim_time = self.animation.borrow().time;
+        if anim_time != self.last_anim_time {
+            self.last_anim_time = anim_time;
+            self.setup_transform(anim_time);
+            self.update_morph(anim_time);
+        }
+    }
+}
+
+// Finds the keyframes around `time` and how far `time` is from the first one
+// to the second one. The first and the last keyframes are held outside of
+// the track.
+fn keyframe_position<T, F: Fn(&T) -> f32>(
+    frames: &[T],
+    time: f32,
+    timestamp: F,
+) -> Option<(usize, usize, f32)> {
+    let first = frames.first()?;
+    if frames.len() == 1 || time <= timestamp(first) {
+        return Some((0, 0, 0.));
+    }
+
+    match frames.iter().position(|f| timestamp(f) > time) {
+        None => Some((frames.len() - 1, frames.len() - 1, 0.)),
+        Some(next) => {
+            let prev = next - 1;
+            let span = timestamp(&frames[next]) - timestamp(&frames[prev]);
+            let ratio = if span > 0. {
+                (time - timestamp(&frames[prev])) / span
+            } else {
+                0.
+            };
+
+            Some((prev, next, ratio))
+        }
+    }
 }
 
 struct CvdMesh {
     material: MaterialDef,
-    vertices: VertexBuffer,
+    frames: Vec<VertexBuffer>,
     indices: Vec<u32>,
 }
 
 impl CvdMesh {
     pub fn new(
-        all_vertices: &Vec<CvdVertex>,
+        all_frames: &Vec<Vec<CvdVertex>>,
         cvd_material: &CvdMaterial,
         material: MaterialDef,
     ) -> Self {
@@ -234,26 +497,119 @@ impl CvdMesh {
             indices.push(get_new_index(t.indices[2]));
         }
 
-        let mut vertices = VertexBuffer::new(components, reversed_index.len());
-        for i in 0..reversed_index.len() {
-            let vert = &all_vertices[reversed_index[i]];
-            vertices.set_data(
-                i,
-                Some(&Vec3::new(
-                    vert.position.x,
-                    vert.position.y,
-                    vert.position.z,
-                )),
-                None,
-                Some(&Vec2::new(vert.tex_coord.x, vert.tex_coord.y)),
-                None,
-            );
+        let mut frames = vec![];
+        for all_vertices in all_frames {
+            let mut vertices = VertexBuffer::new(components, reversed_index.len());
+            for i in 0..reversed_index.len() {
+                let vert = &all_vertices[reversed_index[i]];
+                vertices.set_data(
+                    i,
+                    Some(&Vec3::new(
+                        vert.position.x,
+                        vert.position.y,
+                        vert.position.z,
+                    )),
+                    None,
+                    Some(&Vec2::new(vert.tex_coord.x, vert.tex_coord.y)),
+                    None,
+                );
+            }
+
+            frames.push(vertices);
         }
 
         CvdMesh {
             material,
-            vertices,
+            frames,
             indices,
         }
     }
+
+    fn blend(&self, prev: usize, next: usize, ratio: f32) -> VertexBuffer {
+        let mut vertices = self.frames[prev].clone();
+        let next_frame = &self.frames[next];
+        for i in 0..vertices.count() {
+            let next_position = *next_frame.position(i).unwrap();
+            vertices.set_component(i, VertexComponents::POSITION, |p: &mut Vec3| {
+                *p = Vec3::lerp(p, &next_position, ratio);
+            });
+        }
+
+        vertices
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{keyframe_position, CvdMesh};
+    use radiance::math::{Vec2, Vec3};
+    use radiance::rendering::{SimpleMaterialDef, VertexBuffer, VertexComponents};
+    use std::fs::File;
+
+    const TIMESTAMPS: [f32; 4] = [1., 2., 2., 4.];
+
+    fn position(time: f32) -> Option<(usize, usize, f32)> {
+        keyframe_position(&TIMESTAMPS, time, |t| *t)
+    }
+
+    fn frame(positions: &[Vec3]) -> VertexBuffer {
+        let mut vertices = VertexBuffer::new(
+            VertexComponents::POSITION | VertexComponents::TEXCOORD,
+            positions.len(),
+        );
+        for (i, p) in positions.iter().enumerate() {
+            vertices.set_data(i, Some(p), None, Some(&Vec2::new(0., 0.)), None);
+        }
+
+        vertices
+    }
+
+    #[test]
+    fn keyframe_position_finds_the_frames_around_the_time() {
+        assert_eq!(position(1.5), Some((0, 1, 0.5)));
+        assert_eq!(position(3.), Some((2, 3, 0.5)));
+        assert_eq!(position(3.5), Some((2, 3, 0.75)));
+    }
+
+    #[test]
+    fn keyframe_position_holds_the_ends_of_the_track() {
+        assert_eq!(position(0.), Some((0, 0, 0.)));
+        assert_eq!(position(1.), Some((0, 0, 0.)));
+        assert_eq!(position(4.), Some((3, 3, 0.)));
+        assert_eq!(position(10.), Some((3, 3, 0.)));
+        assert_eq!(keyframe_position(&[5f32], 7., |t| *t), Some((0, 0, 0.)));
+        assert_eq!(keyframe_position(&[] as &[f32], 1., |t| *t), None);
+    }
+
+    #[test]
+    fn keyframe_position_skips_repeated_timestamps() {
+        assert_eq!(position(2.), Some((2, 3, 0.)));
+        assert_eq!(position(1.75), Some((0, 1, 0.75)));
+        assert_eq!(
+            keyframe_position(&[0f32, 1., 1.], 1., |t| *t),
+            Some((2, 2, 0.))
+        );
+    }
+
+    #[test]
+    fn blend_moves_the_vertices_between_two_frames() {
+        let mesh = CvdMesh {
+            material: SimpleMaterialDef::create::<File>(None, false),
+            frames: vec![
+                frame(&[Vec3::new(0., 0., 0.), Vec3::new(1., 1., 1.)]),
+                frame(&[Vec3::new(2., 0., -4.), Vec3::new(1., 3., 1.)]),
+            ],
+            indices: vec![0, 1, 0],
+        };
+
+        let blended = mesh.blend(0, 1, 0.25);
+        let first = blended.position(0).unwrap();
+        let second = blended.position(1).unwrap();
+        assert_eq!((first.x, first.y, first.z), (0.5, 0., -1.));
+        assert_eq!((second.x, second.y, second.z), (1., 1.5, 1.));
+        assert_eq!(blended.tex_coord(1).unwrap().x, 0.);
+
+        let held = mesh.blend(1, 1, 0.);
+        assert_eq!(held.position(0).unwrap().x, 2.);
+    }
 }
@@ -1,10 +1,14 @@
 mod cvd_entity;
 mod error;
+mod nav;
+mod pathfinding;
 mod pol_entity;
 mod role_entity;
 mod scene;
 
-pub use cvd_entity::CvdModelEntity;
+pub use cvd_entity::{CvdAnimationState, CvdModelEntity};
+pub use nav::Nav;
+pub use pathfinding::RolePath;
 pub use pol_entity::PolModelEntity;
-pub use role_entity::{RoleAnimation, RoleAnimationRepeatMode, RoleEntity, RoleState};
+pub use role_entity::{RoleAction, RoleAnimation, RoleAnimationRepeatMode, RoleEntity, RoleState};
 pub use scene::ScnScene;
@@ -0,0 +1,425 @@
+use crate::loaders::nav_loader::{NavFile, NavMap, NavMesh};
+use radiance::math::Vec3;
+
+// The highest ledge between two neighbouring cells that a role can climb up
+// or step down
+const MAX_STEP_HEIGHT: f32 = 30.;
+
+// The steepest ground a role can walk up, as the rise over the run
+const MAX_SLOPE: f32 = 2.5;
+
+/// The navigation maps of a scene. Scenes with bridges or upper floors have
+/// a map for every floor, which is called a layer here. The nav coordinates
+/// are the cells of the map of a layer.
+pub struct Nav {
+    nav_file: NavFile,
+    block_sizes: Vec<(f32, f32)>,
+}
+
+impl Nav {
+    pub fn new(nav_file: NavFile) -> Self {
+        let block_sizes = nav_file
+            .maps
+            .iter()
+            .map(|map| {
+                let area = Vec3::sub(&map.max_coord, &map.min_coord);
+                (area.x / map.width as f32, area.z / map.height as f32)
+            })
+            .collect();
+
+        Self {
+            nav_file,
+            block_sizes,
+        }
+    }
+
+    pub fn layer_count(&self) -> usize {
+        self.nav_file.maps.len()
+    }
+
+    pub fn min_coord(&self, layer: usize) -> Vec3 {
+        self.map(layer).min_coord
+    }
+
+    pub fn block_size(&self, layer: usize) -> (f32, f32) {
+        self.block_sizes[layer]
+    }
+
+    pub fn mesh(&self, layer: usize) -> Option<&NavMesh> {
+        self.nav_file.meshes.get(layer)
+    }
+
+    pub fn scene_coord_to_nav_coord(&self, layer: usize, coord: &Vec3) -> (f32, f32) {
+        let min_coord = self.min_coord(layer);
+        let block_size = self.block_size(layer);
+        (
+            (coord.x - min_coord.x) / block_size.0,
+            (coord.z - min_coord.z) / block_size.1,
+        )
+    }
+
+    pub fn nav_coord_to_scene_coord(&self, layer: usize, nav_x: f32, nav_z: f32) -> Vec3 {
+        let min_coord = self.min_coord(layer);
+        let block_size = self.block_size(layer);
+        Vec3::new(
+            nav_x * block_size.0 + min_coord.x,
+            self.get_height(layer, (nav_x, nav_z)),
+            nav_z * block_size.1 + min_coord.z,
+        )
+    }
+
+    /// The height of the ground at the nav coordinate. It's taken from the
+    /// nav mesh when there is a triangle under the coordinate, and otherwise
+    /// interpolated between the heights of the four cells around it.
+    pub fn get_height(&self, layer: usize, nav_coord: (f32, f32)) -> f32 {
+        let min_coord = self.min_coord(layer);
+        let block_size = self.block_size(layer);
+        let x = nav_coord.0 * block_size.0 + min_coord.x;
+        let z = nav_coord.1 * block_size.1 + min_coord.z;
+        if let Some(height) = self.get_mesh_height(layer, x, z) {
+            return height;
+        }
+
+        let map = self.map(layer);
+        if Self::is_empty(map) {
+            return min_coord.y;
+        }
+
+        let (x0, y0) = self.clamp_nav_coord(layer, nav_coord.0.floor(), nav_coord.1.floor());
+        let (x1, y1) = self.clamp_nav_coord(layer, nav_coord.0.ceil(), nav_coord.1.ceil());
+        let tx = (nav_coord.0 - x0 as f32).clamp(0., 1.);
+        let ty = (nav_coord.1 - y0 as f32).clamp(0., 1.);
+
+        let lerp = |a: f32, b: f32, t: f32| a + (b - a) * t;
+        let top = lerp(map.map[y0][x0].height, map.map[y0][x1].height, tx);
+        let bottom = lerp(map.map[y1][x0].height, map.map[y1][x1].height, tx);
+        lerp(top, bottom, ty)
+    }
+
+    pub fn get_distance_to_border(&self, layer: usize, nav_coord: (f32, f32)) -> f32 {
+        let map = self.map(layer);
+        if Self::is_empty(map) {
+            return 0.;
+        }
+
+        let floor = self.clamp_nav_coord(layer, nav_coord.0.floor(), nav_coord.1.floor());
+        let ceil = self.clamp_nav_coord(layer, nav_coord.0.ceil(), nav_coord.1.ceil());
+        std::cmp::min(
+            map.map[floor.1][floor.0].distance_to_border,
+            map.map[ceil.1][ceil.0].distance_to_border,
+        ) as f32
+    }
+
+    /// The number of cells along the x and the z axis.
+    pub fn map_size(&self, layer: usize) -> (usize, usize) {
+        let map = self.map(layer);
+        (map.width as usize, map.height as usize)
+    }
+
+    pub fn is_walkable(&self, layer: usize, x: usize, y: usize) -> bool {
+        let (width, height) = self.map_size(layer);
+        x < width && y < height && self.map(layer).map[y][x].distance_to_border > 0
+    }
+
+    /// Whether a role can step from a cell to a neighbouring cell without
+    /// climbing up or dropping off a ledge that is too high.
+    pub fn can_step(&self, layer: usize, from: (usize, usize), to: (usize, usize)) -> bool {
+        let map = self.map(layer);
+        (map.map[to.1][to.0].height - map.map[from.1][from.0].height).abs() <= MAX_STEP_HEIGHT
+    }
+
+    /// Whether a role on the layer can move from `from` to `to`. The cell
+    /// under `to` has to be walkable, and the move can neither cross a ledge
+    /// that is too high nor walk up a slope that is too steep.
+    pub fn can_move(&self, layer: usize, from: &Vec3, to: &Vec3) -> bool {
+        let from_coord = self.scene_coord_to_nav_coord(layer, from);
+        let to_coord = self.scene_coord_to_nav_coord(layer, to);
+        let from_cell = self.clamp_nav_coord(layer, from_coord.0.round(), from_coord.1.round());
+        let to_cell = self.clamp_nav_coord(layer, to_coord.0.round(), to_coord.1.round());
+        if self.get_distance_to_border(layer, to_coord) <= 0.
+            || !self.can_step(layer, from_cell, to_cell)
+        {
+            return false;
+        }
+
+        let run = Vec3::new(to.x - from.x, 0., to.z - from.z).norm();
+        let rise = self.get_height(layer, to_coord) - self.get_height(layer, from_coord);
+        run <= std::f32::EPSILON || rise <= MAX_SLOPE * run
+    }
+
+    /// Picks the layer whose ground is the closest to the height of `coord`.
+    /// The layers that don't have `coord` in their walkable area are only
+    /// picked when no layer has it.
+    pub fn pick_layer(&self, coord: &Vec3) -> usize {
+        let mut best_layer = 0;
+        let mut best_score = (true, std::f32::MAX);
+        for layer in 0..self.layer_count() {
+            let map = self.map(layer);
+            let nav_coord = self.scene_coord_to_nav_coord(layer, coord);
+            let inside = nav_coord.0 >= 0.
+                && nav_coord.1 >= 0.
+                && nav_coord.0 <= map.width as f32 - 1.
+                && nav_coord.1 <= map.height as f32 - 1.;
+            let walkable = inside && self.get_distance_to_border(layer, nav_coord) > 0.;
+            let score = (
+                !walkable,
+                (self.get_height(layer, nav_coord) - coord.y).abs(),
+            );
+
+            if score < best_score {
+                best_layer = layer;
+                best_score = score;
+            }
+        }
+
+        best_layer
+    }
+
+    /// The height of the nav mesh of the layer under the scene coordinate,
+    /// if there is a triangle under it.
+    pub fn get_mesh_height(&self, layer: usize, x: f32, z: f32) -> Option<f32> {
+        let mesh = self.mesh(layer)?;
+        for triangle in &mesh.triangles {
+            let mut vertices = triangle
+                .indices
+                .iter()
+                .filter_map(|&i| mesh.vertices.get(i as usize));
+            let (a, b, c) = match (vertices.next(), vertices.next(), vertices.next()) {
+                (Some(a), Some(b), Some(c)) => (a, b, c),
+                _ => continue,
+            };
+
+            let det = (b.z - c.z) * (a.x - c.x) + (c.x - b.x) * (a.z - c.z);
+            if det.abs() < std::f32::EPSILON {
+                continue;
+            }
+
+            let u = ((b.z - c.z) * (x - c.x) + (c.x - b.x) * (z - c.z)) / det;
+            let v = ((c.z - a.z) * (x - c.x) + (a.x - c.x) * (z - c.z)) / det;
+            let w = 1. - u - v;
+            if u >= 0. && v >= 0. && w >= 0. {
+                return Some(u * a.y + v * b.y + w * c.y);
+            }
+        }
+
+        None
+    }
+
+    fn map(&self, layer: usize) -> &NavMap {
+        &self.nav_file.maps[layer]
+    }
+
+    fn is_empty(map: &NavMap) -> bool {
+        map.width == 0 || map.height == 0
+    }
+
+    // Empty maps have no cell to clamp to, so they give (0, 0)
+    fn clamp_nav_coord(&self, layer: usize, x: f32, y: f32) -> (usize, usize) {
+        let map = self.map(layer);
+        (
+            (x as usize).min((map.width as usize).saturating_sub(1)),
+            (y as usize).min((map.height as usize).saturating_sub(1)),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Nav;
+    use crate::loaders::nav_loader::{NavFile, NavMap, NavMapPoint, NavMesh, NavTriangle};
+    use radiance::math::Vec3;
+
+    // Every cell is walkable and `block_size` wide
+    fn nav_map(min_coord: Vec3, block_size: f32, heights: Vec<Vec<f32>>) -> NavMap {
+        let width = heights.first().map_or(0, |row| row.len());
+        let height = heights.len();
+        NavMap {
+            unknown: None,
+            max_coord: Vec3::new(
+                min_coord.x + block_size * width as f32,
+                min_coord.y,
+                min_coord.z + block_size * height as f32,
+            ),
+            min_coord,
+            width: width as u32,
+            height: height as u32,
+            map: heights
+                .into_iter()
+                .map(|row| {
+                    row.into_iter()
+                        .map(|height| NavMapPoint {
+                            height,
+                            distance_to_border: 1,
+                        })
+                        .collect()
+                })
+                .collect(),
+        }
+    }
+
+    fn no_mesh() -> NavMesh {
+        NavMesh {
+            vertices: vec![],
+            triangles: vec![],
+        }
+    }
+
+    fn nav(maps: Vec<NavMap>) -> Nav {
+        let meshes = maps.iter().map(|_| no_mesh()).collect();
+        Nav::new(NavFile {
+            version: 2,
+            maps,
+            meshes,
+        })
+    }
+
+    // A 4x4 ground at the height of 0 and a bridge at the height of 50 over
+    // it, which can only be walked on in the two middle columns
+    fn bridge() -> Nav {
+        let ground = nav_map(Vec3::new(0., 0., 0.), 10., vec![vec![0.; 4]; 4]);
+        let mut bridge = nav_map(Vec3::new(0., 50., 0.), 10., vec![vec![50.; 4]; 4]);
+        for row in &mut bridge.map {
+            row[0].distance_to_border = 0;
+            row[3].distance_to_border = 0;
+        }
+
+        nav(vec![ground, bridge])
+    }
+
+    #[test]
+    fn pick_layer_picks_the_ground_under_the_bridge() {
+        let nav = bridge();
+        assert_eq!(nav.pick_layer(&Vec3::new(15., 0., 15.)), 0);
+        assert_eq!(nav.pick_layer(&Vec3::new(15., 10., 25.)), 0);
+    }
+
+    #[test]
+    fn pick_layer_picks_the_bridge_on_the_bridge() {
+        let nav = bridge();
+        assert_eq!(nav.pick_layer(&Vec3::new(15., 50., 15.)), 1);
+        assert_eq!(nav.pick_layer(&Vec3::new(20., 40., 5.)), 1);
+    }
+
+    #[test]
+    fn pick_layer_skips_a_layer_that_cannot_be_walked_on_there() {
+        let nav = bridge();
+        assert_eq!(nav.pick_layer(&Vec3::new(30., 50., 15.)), 0);
+    }
+
+    #[test]
+    fn empty_map_is_at_the_height_of_its_min_coord() {
+        let empty = NavMap {
+            unknown: None,
+            max_coord: Vec3::new(0., 7., 0.),
+            min_coord: Vec3::new(0., 7., 0.),
+            width: 0,
+            height: 0,
+            map: vec![],
+        };
+        let nav = nav(vec![empty]);
+
+        assert_eq!(nav.get_height(0, (0., 0.)), 7.);
+        assert_eq!(nav.get_height(0, (3., 2.)), 7.);
+        assert_eq!(nav.get_distance_to_border(0, (1., 1.)), 0.);
+        assert_eq!(nav.pick_layer(&Vec3::new(1., 7., 1.)), 0);
+    }
+
+    #[test]
+    fn mesh_height_is_taken_from_the_triangle_under_the_point() {
+        let mut nav = bridge();
+        nav.nav_file.meshes[1] = NavMesh {
+            vertices: vec![
+                Vec3::new(0., 40., 0.),
+                Vec3::new(40., 60., 0.),
+                Vec3::new(0., 40., 40.),
+            ],
+            triangles: vec![NavTriangle { indices: [0, 1, 2] }],
+        };
+
+        assert_eq!(nav.get_mesh_height(1, 20., 10.), Some(50.));
+        assert_eq!(nav.get_mesh_height(1, 30., 30.), None);
+        assert_eq!(nav.get_mesh_height(0, 20., 10.), None);
+        assert_eq!(nav.get_height(1, (2., 1.)), 50.);
+        assert_eq!(nav.get_height(1, (3., 3.)), 50.);
+        assert_eq!(nav.get_height(1, (0., 0.)), 40.);
+    }
+
+    // Four cells at the heights of 0, 10, 20 and 30
+    fn slope() -> Nav {
+        nav(vec![nav_map(
+            Vec3::new(0., 0., 0.),
+            10.,
+            vec![vec![0., 10.], vec![20., 30.]],
+        )])
+    }
+
+    // One row of cells that are `block_size` apart and at the given heights
+    fn row(block_size: f32, heights: Vec<f32>) -> Nav {
+        nav(vec![nav_map(
+            Vec3::new(0., 0., 0.),
+            block_size,
+            vec![heights],
+        )])
+    }
+
+    #[test]
+    fn height_is_interpolated_between_the_cells_around() {
+        let nav = slope();
+        assert_eq!(nav.get_height(0, (0., 0.)), 0.);
+        assert_eq!(nav.get_height(0, (1., 1.)), 30.);
+        assert_eq!(nav.get_height(0, (0.25, 0.)), 2.5);
+        assert_eq!(nav.get_height(0, (0., 0.5)), 10.);
+        assert_eq!(nav.get_height(0, (0.5, 0.5)), 15.);
+        assert_eq!(nav.get_height(0, (0.75, 0.25)), 12.5);
+    }
+
+    #[test]
+    fn height_outside_the_map_is_the_height_of_the_border() {
+        let nav = slope();
+        assert_eq!(nav.get_height(0, (5., 0.)), 10.);
+        assert_eq!(nav.get_height(0, (5., 5.)), 30.);
+        assert_eq!(nav.nav_coord_to_scene_coord(0, 0.5, 0.5).y, 15.);
+    }
+
+    #[test]
+    fn can_move_refuses_a_ledge_higher_than_the_step_height() {
+        // The cells are far apart so that the slope is gentle
+        let nav = row(100., vec![0., 30., 61.]);
+        let ground = Vec3::new(0., 0., 0.);
+        let low_ledge = Vec3::new(100., 30., 0.);
+        let high_ledge = Vec3::new(200., 61., 0.);
+
+        assert!(nav.can_step(0, (0, 0), (1, 0)));
+        assert!(nav.can_move(0, &ground, &low_ledge));
+        assert!(nav.can_move(0, &low_ledge, &ground));
+
+        assert!(!nav.can_step(0, (1, 0), (2, 0)));
+        assert!(!nav.can_move(0, &low_ledge, &high_ledge));
+        assert!(!nav.can_move(0, &high_ledge, &low_ledge));
+    }
+
+    #[test]
+    fn can_move_refuses_a_slope_steeper_than_the_max_slope() {
+        // Rises of 24 and 26 over a run of 10, on both sides of 2.5
+        let nav = row(10., vec![0., 24., 50.]);
+        let bottom = Vec3::new(0., 0., 0.);
+        let middle = Vec3::new(10., 24., 0.);
+        let top = Vec3::new(20., 50., 0.);
+
+        assert!(nav.can_move(0, &bottom, &middle));
+        assert!(!nav.can_move(0, &middle, &top));
+
+        // Going down is never too steep
+        assert!(nav.can_move(0, &top, &middle));
+        assert!(nav.can_move(0, &middle, &middle));
+    }
+
+    #[test]
+    fn can_move_refuses_a_cell_off_the_walkable_area() {
+        let mut nav = row(10., vec![0., 0., 0.]);
+        nav.nav_file.maps[0].map[0][2].distance_to_border = 0;
+
+        assert!(nav.can_move(0, &Vec3::new(0., 0., 0.), &Vec3::new(10., 0., 0.)));
+        assert!(!nav.can_move(0, &Vec3::new(10., 0., 0.), &Vec3::new(20., 0., 0.)));
+    }
+}
@@ -0,0 +1,356 @@
+use super::Nav;
+use radiance::math::Vec3;
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap},
+};
+
+// The costs of the moves between cells, scaled to integers
+const STRAIGHT_COST: u32 = 10;
+const DIAGONAL_COST: u32 = 14;
+
+// How far apart the samples are when testing a straight line, in cells
+const LINE_SAMPLE_STEP: f32 = 0.25;
+
+/// Finds a path on the walkable cells of the nav layer under `from` with A*,
+/// and smooths it by skipping the waypoints that can be reached in a
+/// straight line. The path ends at `to` and doesn't include `from`.
+///
+/// Returns `None` if `to` can't be reached from `from`.
+pub fn find_path(nav: &Nav, from: &Vec3, to: &Vec3) -> Option<Vec<Vec3>> {
+    if nav.layer_count() == 0 {
+        return None;
+    }
+
+    let layer = nav.pick_layer(from);
+    let start = to_cell(nav, layer, from)?;
+    let goal = to_cell(nav, layer, to)?;
+    if !nav.is_walkable(layer, goal.0, goal.1) {
+        return None;
+    }
+
+    let cells = find_cells(nav, layer, start, goal)?;
+    let mut path: Vec<Vec3> = smooth(nav, layer, &cells)
+        .into_iter()
+        .skip(1)
+        .map(|(x, y)| nav.nav_coord_to_scene_coord(layer, x as f32, y as f32))
+        .collect();
+
+    path.pop();
+    path.push(*to);
+    Some(path)
+}
+
+/// A path that a role is walking along.
+#[derive(Clone)]
+pub struct RolePath {
+    waypoints: Vec<Vec3>,
+    next: usize,
+}
+
+impl RolePath {
+    pub fn new(waypoints: Vec<Vec3>) -> Self {
+        Self { waypoints, next: 0 }
+    }
+
+    /// The waypoint that the role is heading to.
+    pub fn target(&self) -> Option<&Vec3> {
+        self.waypoints.get(self.next)
+    }
+
+    pub fn completed(&self) -> bool {
+        self.next >= self.waypoints.len()
+    }
+
+    /// Moves `distance` along the path from `position` and returns where the
+    /// role ends up. A step can pass several waypoints.
+    pub fn advance(&mut self, position: &Vec3, distance: f32) -> Vec3 {
+        let mut position = *position;
+        let mut distance = distance;
+        while let Some(target) = self.waypoints.get(self.next) {
+            let remain = Vec3::sub(target, &position);
+            let remain_distance = remain.norm();
+            if remain_distance > distance {
+                return Vec3::add(&position, &Vec3::dot(distance, &Vec3::normalized(&remain)));
+            }
+
+            position = *target;
+            distance -= remain_distance;
+            self.next += 1;
+        }
+
+        position
+    }
+}
+
+fn to_cell(nav: &Nav, layer: usize, coord: &Vec3) -> Option<(usize, usize)> {
+    let (width, height) = nav.map_size(layer);
+    let nav_coord = nav.scene_coord_to_nav_coord(layer, coord);
+    let x = nav_coord.0.round();
+    let y = nav_coord.1.round();
+    if x < 0. || y < 0. || x >= width as f32 || y >= height as f32 {
+        None
+    } else {
+        Some((x as usize, y as usize))
+    }
+}
+
+fn find_cells(
+    nav: &Nav,
+    layer: usize,
+    start: (usize, usize),
+    goal: (usize, usize),
+) -> Option<Vec<(usize, usize)>> {
+    let mut open = BinaryHeap::new();
+    let mut came_from = HashMap::new();
+    let mut costs = HashMap::new();
+    costs.insert(start, 0);
+    open.push(Reverse((heuristic(start, goal), start)));
+
+    while let Some(Reverse((_, cell))) = open.pop() {
+        if cell == goal {
+            let mut cells = vec![cell];
+            let mut cell = cell;
+            while let Some(&prev) = came_from.get(&cell) {
+                cells.push(prev);
+                cell = prev;
+            }
+
+            cells.reverse();
+            return Some(cells);
+        }
+
+        let cost = costs[&cell];
+        for (neighbour, move_cost) in neighbours(nav, layer, cell) {
+            let new_cost = cost + move_cost;
+            if costs.get(&neighbour).map_or(true, |&c| new_cost < c) {
+                costs.insert(neighbour, new_cost);
+                came_from.insert(neighbour, cell);
+                open.push(Reverse((new_cost + heuristic(neighbour, goal), neighbour)));
+            }
+        }
+    }
+
+    None
+}
+
+// Diagonal moves can't cut the corners of unwalkable cells
+fn neighbours(nav: &Nav, layer: usize, cell: (usize, usize)) -> Vec<((usize, usize), u32)> {
+    let walkable = |dx: i64, dy: i64| {
+        let x = cell.0 as i64 + dx;
+        let y = cell.1 as i64 + dy;
+        x >= 0
+            && y >= 0
+            && nav.is_walkable(layer, x as usize, y as usize)
+            && nav.can_step(layer, cell, (x as usize, y as usize))
+    };
+
+    let mut neighbours = vec![];
+    for dy in -1..=1 {
+        for dx in -1..=1 {
+            if (dx == 0 && dy == 0) || !walkable(dx, dy) {
+                continue;
+            }
+
+            let cost = if dx != 0 && dy != 0 {
+                if !walkable(dx, 0) || !walkable(0, dy) {
+                    continue;
+                }
+
+                DIAGONAL_COST
+            } else {
+                STRAIGHT_COST
+            };
+
+            let x = (cell.0 as i64 + dx) as usize;
+            let y = (cell.1 as i64 + dy) as usize;
+            neighbours.push(((x, y), cost));
+        }
+    }
+
+    neighbours
+}
+
+fn heuristic(from: (usize, usize), to: (usize, usize)) -> u32 {
+    let dx = (from.0 as i64 - to.0 as i64).abs() as u32;
+    let dy = (from.1 as i64 - to.1 as i64).abs() as u32;
+    STRAIGHT_COST * dx.max(dy) + (DIAGONAL_COST - STRAIGHT_COST) * dx.min(dy)
+}
+
+// Keeps only the cells where the path has to turn
+fn smooth(nav: &Nav, layer: usize, cells: &[(usize, usize)]) -> Vec<(usize, usize)> {
+    let mut smoothed = vec![];
+    let mut anchor = 0;
+    smoothed.push(cells[anchor]);
+    while anchor < cells.len() - 1 {
+        let mut next = anchor + 1;
+        for i in (anchor + 2..cells.len()).rev() {
+            if is_line_walkable(nav, layer, cells[anchor], cells[i]) {
+                next = i;
+                break;
+            }
+        }
+
+        smoothed.push(cells[next]);
+        anchor = next;
+    }
+
+    smoothed
+}
+
+// Like the diagonal moves of A*, the line can't cut the corners of
+// unwalkable cells
+fn is_line_walkable(nav: &Nav, layer: usize, from: (usize, usize), to: (usize, usize)) -> bool {
+    let can_enter = |from: (usize, usize), to: (usize, usize)| {
+        nav.is_walkable(layer, to.0, to.1) && nav.can_step(layer, from, to)
+    };
+
+    let dx = to.0 as f32 - from.0 as f32;
+    let dy = to.1 as f32 - from.1 as f32;
+    let samples = ((dx * dx + dy * dy).sqrt() / LINE_SAMPLE_STEP).ceil() as usize;
+    let mut prev = from;
+    (0..=samples).all(|i| {
+        let t = if samples == 0 {
+            0.
+        } else {
+            i as f32 / samples as f32
+        };
+        let x = (from.0 as f32 + dx * t).round() as usize;
+        let y = (from.1 as f32 + dy * t).round() as usize;
+        let cuts_corner = x != prev.0
+            && y != prev.1
+            && !(can_enter(prev, (x, prev.1)) && can_enter(prev, (prev.0, y)));
+        let walkable = !cuts_corner && can_enter(prev, (x, y));
+        prev = (x, y);
+        walkable
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{find_path, is_line_walkable, RolePath};
+    use crate::loaders::nav_loader::{NavFile, NavMap, NavMapPoint, NavMesh};
+    use crate::scene::Nav;
+    use radiance::math::Vec3;
+
+    const BLOCK_SIZE: f32 = 10.;
+
+    // `.` is walkable ground, `#` is blocked and `^` is walkable but higher
+    // than a role can climb from the ground
+    fn nav(rows: &[&str]) -> Nav {
+        let map: Vec<Vec<NavMapPoint>> = rows
+            .iter()
+            .map(|row| {
+                row.chars()
+                    .map(|c| NavMapPoint {
+                        height: if c == '^' { 40. } else { 0. },
+                        distance_to_border: if c == '#' { 0 } else { 1 },
+                    })
+                    .collect()
+            })
+            .collect();
+        let width = map[0].len();
+        let height = map.len();
+
+        Nav::new(NavFile {
+            version: 2,
+            maps: vec![NavMap {
+                unknown: None,
+                max_coord: Vec3::new(BLOCK_SIZE * width as f32, 0., BLOCK_SIZE * height as f32),
+                min_coord: Vec3::new(0., 0., 0.),
+                width: width as u32,
+                height: height as u32,
+                map,
+            }],
+            meshes: vec![NavMesh {
+                vertices: vec![],
+                triangles: vec![],
+            }],
+        })
+    }
+
+    fn cell(x: usize, y: usize) -> Vec3 {
+        Vec3::new(x as f32 * BLOCK_SIZE, 0., y as f32 * BLOCK_SIZE)
+    }
+
+    fn cells(nav: &Nav, from: &Vec3, path: &[Vec3]) -> Vec<(usize, usize)> {
+        std::iter::once(from)
+            .chain(path)
+            .map(|p| {
+                let (x, y) = nav.scene_coord_to_nav_coord(0, p);
+                (x.round() as usize, y.round() as usize)
+            })
+            .collect()
+    }
+
+    // Every leg of the path has to be walkable, not only its waypoints
+    fn assert_walkable(nav: &Nav, from: &Vec3, path: &[Vec3]) {
+        let cells = cells(nav, from, path);
+        for leg in cells.windows(2) {
+            assert!(is_line_walkable(nav, 0, leg[0], leg[1]), "{:?}", cells);
+        }
+    }
+
+    #[test]
+    fn path_goes_around_a_wall() {
+        let nav = nav(&["..#..", "..#..", "....."]);
+        let from = cell(0, 0);
+        let to = cell(4, 0);
+
+        let path = find_path(&nav, &from, &to).unwrap();
+        assert_walkable(&nav, &from, &path);
+        assert_eq!(path.last().map(|p| (p.x, p.z)), Some((to.x, to.z)));
+        assert!(cells(&nav, &from, &path).iter().any(|&(_, y)| y == 2));
+    }
+
+    #[test]
+    fn unreachable_goal_has_no_path() {
+        let nav = nav(&["..#..", "..#..", "..#.."]);
+        assert!(find_path(&nav, &cell(0, 1), &cell(4, 1)).is_none());
+        assert!(find_path(&nav, &cell(0, 1), &cell(2, 1)).is_none());
+        assert!(find_path(&nav, &cell(0, 1), &cell(9, 1)).is_none());
+    }
+
+    #[test]
+    fn path_does_not_cut_the_corner_of_a_blocked_cell() {
+        let nav = nav(&[".#", ".."]);
+        let from = cell(0, 0);
+
+        let path = find_path(&nav, &from, &cell(1, 1)).unwrap();
+        assert_eq!(cells(&nav, &from, &path), vec![(0, 0), (0, 1), (1, 1)]);
+        assert!(!is_line_walkable(&nav, 0, (0, 0), (1, 1)));
+    }
+
+    #[test]
+    fn path_does_not_climb_a_ledge_that_is_too_high() {
+        let ledge = nav(&[".^.", ".^.", "..."]);
+        let from = cell(0, 0);
+
+        let path = find_path(&ledge, &from, &cell(2, 0)).unwrap();
+        assert_walkable(&ledge, &from, &path);
+        let path_cells = cells(&ledge, &from, &path);
+        assert!(path_cells.iter().all(|&(x, y)| x != 1 || y == 2));
+
+        let walled = nav(&[".^.", ".^.", ".^."]);
+        assert!(find_path(&walled, &from, &cell(2, 0)).is_none());
+    }
+
+    #[test]
+    fn advance_can_pass_several_waypoints_in_one_step() {
+        let mut path = RolePath::new(vec![
+            Vec3::new(10., 0., 0.),
+            Vec3::new(10., 0., 10.),
+            Vec3::new(20., 0., 10.),
+        ]);
+
+        let position = path.advance(&Vec3::new(0., 0., 0.), 25.);
+        assert_eq!((position.x, position.y, position.z), (15., 0., 10.));
+        assert_eq!(path.target().map(|t| t.x), Some(20.));
+        assert!(!path.completed());
+
+        let position = path.advance(&position, 100.);
+        assert_eq!((position.x, position.y, position.z), (20., 0., 10.));
+        assert!(path.target().is_none());
+        assert!(path.completed());
+    }
+}
@@ -1,4 +1,7 @@
-use crate::{loaders::pol_loader::*, material::LightMapMaterialDef};
+use crate::{
+    loaders::{pol_loader::*, LoaderError},
+    material::LightMapMaterialDef,
+};
 use mini_fs::{MiniFs, StoreExt};
 use radiance::math::{Vec2, Vec3};
 use radiance::rendering::{
@@ -20,8 +23,8 @@ impl PolModelEntity {
         component_factory: &Rc<dyn ComponentFactory>,
         vfs: &MiniFs,
         path: P,
-    ) -> Self {
-        let pol = pol_load_from_file(vfs, path.as_ref()).unwrap();
+    ) -> Result<Self, LoaderError> {
+        let pol = pol_load_from_file(vfs, path.as_ref())?;
         let mut meshes = vec![];
         for mesh in &pol.meshes {
             for material in &mesh.material_info {
@@ -36,10 +39,10 @@ impl PolModelEntity {
             }
         }
 
-        PolModelEntity {
+        Ok(PolModelEntity {
             component_factory: component_factory.clone(),
             meshes,
-        }
+        })
     }
 
     fn load_material<P: AsRef<Path>>(
@@ -50,24 +53,7 @@ impl PolModelEntity {
         let texture_paths: Vec<PathBuf> = material
             .texture_names
             .iter()
-            .map(|name| {
-                name.split_terminator('.')
-                    .next()
-                    .and_then(|n| Some(n.to_owned() + ".dds"))
-                    .and_then(|dds_name| {
-                        let mut texture_path = path.as_ref().to_owned();
-                        texture_path.pop();
-                        texture_path.push(dds_name);
-                        if !vfs.open(&texture_path).is_ok() {
-                            texture_path.pop();
-                            texture_path.push(name);
-                        }
-
-                        Some(texture_path)
-                    })
-                    .or(Some(PathBuf::from(name)))
-                    .unwrap()
-            })
+            .map(|name| pol_texture_path(vfs, path.as_ref(), name))
             .collect();
 
         if texture_paths.len() == 1 {
@@ -1,8 +1,9 @@
 use crate::{asset_manager::AssetManager, loaders::mv3_loader::*};
+use ini::Ini;
 use radiance::scene::{CoreEntity, EntityExtension};
 use radiance::{
     math::{Vec2, Vec3},
-    rendering::RenderingComponent,
+    rendering::{RenderObject, RenderingComponent},
 };
 use radiance::{
     rendering::{ComponentFactory, MaterialDef, VertexBuffer, VertexComponents},
@@ -27,15 +28,65 @@ pub enum RoleState {
     Running,
 }
 
+/// The actions that are looked up by their meaning rather than by the name
+/// of the mv3 file.
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum RoleAction {
+    Idle,
+    Walk,
+    Run,
+    Talk,
+}
+
+impl RoleAction {
+    // The key in the ini file of the role that names the mv3 files, and the
+    // name of the action clip in an mv3 file
+    fn config_key(self) -> &'static str {
+        match self {
+            RoleAction::Idle => "idle",
+            RoleAction::Walk => "walk",
+            RoleAction::Run => "run",
+            RoleAction::Talk => "talk",
+        }
+    }
+
+    // Tried when neither the ini file nor the action clips have the action
+    fn default_anim_names(self) -> &'static [&'static str] {
+        match self {
+            RoleAction::Idle => &["c01", "z1"],
+            RoleAction::Walk => &["c02", "z3"],
+            RoleAction::Run => &["c03", "c02", "z3"],
+            RoleAction::Talk => &[],
+        }
+    }
+}
+
+// An mv3 file, and the action clip in it when only a part of it is played
+#[derive(Clone)]
+struct RoleActionAnim {
+    anim_name: String,
+    clip_name: Option<String>,
+}
+
+impl RoleActionAnim {
+    fn new(anim_name: &str) -> Self {
+        Self {
+            anim_name: anim_name.to_string(),
+            clip_name: None,
+        }
+    }
+}
+
 pub struct RoleEntity {
     model_name: String,
     asset_mgr: Rc<AssetManager>,
     component_factory: Rc<dyn ComponentFactory>,
     animations: HashMap<String, RoleAnimation>,
     active_anim_name: String,
-    idle_anim_name: String,
-    walking_anim_name: String,
-    running_anim_name: String,
+    idle_anim: RoleActionAnim,
+    walking_anim: RoleActionAnim,
+    running_anim: RoleActionAnim,
+    talking_anim: RoleActionAnim,
     anim_repeat_mode: RoleAnimationRepeatMode,
     is_active: bool,
     state: RoleState,
@@ -48,9 +99,22 @@ impl RoleEntity {
         role_name: &str,
         idle_anim: &str,
     ) -> Result<RoleEntity, EntityError> {
-        let idle_anim = idle_anim;
+        let config = asset_mgr.load_role_anim_config(role_name);
+        let mut anim_names = vec![idle_anim.to_string()];
+        anim_names.append(&mut role_config_anim_names(
+            config.as_ref(),
+            RoleAction::Idle,
+        ));
+        anim_names.extend(
+            RoleAction::Idle
+                .default_anim_names()
+                .iter()
+                .map(|n| n.to_string()),
+        );
+
+        let anim_names: Vec<&str> = anim_names.iter().map(|n| n.as_str()).collect();
         let anim = asset_mgr
-            .load_role_anim_first(role_name, &[idle_anim, "c01", "z1"])
+            .load_role_anim_first(role_name, &anim_names)
             .ok_or(EntityError::EntityAnimationNotFound)?;
 
         Ok(Self::new_from_idle_animation(
@@ -58,39 +122,68 @@ impl RoleEntity {
         ))
     }
 
+    /// The other actions are looked up in the ini file of the role first,
+    /// then in the action clips of the idle animation, and at last in the
+    /// mv3 files that most roles use for them.
     pub fn new_from_idle_animation(
         asset_mgr: Rc<AssetManager>,
         role_name: &str,
         idle_anim_name: &str,
         idle_anim: RoleAnimation,
     ) -> RoleEntity {
+        let config = asset_mgr.load_role_anim_config(role_name);
+        let load_first = |anim_names: &[String]| {
+            let anim_names: Vec<&str> = anim_names.iter().map(|n| n.as_str()).collect();
+            asset_mgr
+                .load_role_anim_first(role_name, &anim_names)
+                .map(|(name, _)| RoleActionAnim::new(name))
+        };
+        let find_action_anim = |action: RoleAction| {
+            load_first(&role_config_anim_names(config.as_ref(), action))
+                .or_else(|| {
+                    idle_anim
+                        .clip(action.config_key())
+                        .map(|clip| RoleActionAnim {
+                            anim_name: idle_anim_name.to_string(),
+                            clip_name: Some(clip.name.clone()),
+                        })
+                })
+                .or_else(|| {
+                    let anim_names: Vec<String> = action
+                        .default_anim_names()
+                        .iter()
+                        .map(|n| n.to_string())
+                        .collect();
+                    load_first(&anim_names)
+                })
+        };
+
+        let idle = RoleActionAnim {
+            anim_name: idle_anim_name.to_string(),
+            clip_name: idle_anim
+                .clip(RoleAction::Idle.config_key())
+                .map(|clip| clip.name.clone()),
+        };
+        let walking_anim = find_action_anim(RoleAction::Walk).unwrap_or_else(|| idle.clone());
+        let running_anim =
+            find_action_anim(RoleAction::Run).unwrap_or_else(|| walking_anim.clone());
+        let talking_anim = find_action_anim(RoleAction::Talk).unwrap_or_else(|| idle.clone());
+
         let mut animations = HashMap::new();
         if !idle_anim_name.trim().is_empty() {
             animations.insert(idle_anim_name.to_string(), idle_anim);
         }
 
-        let walking_anim = asset_mgr.load_role_anim_first(role_name, &["c02", "z3"]);
-        let running_anim = asset_mgr.load_role_anim_first(role_name, &["c03", "c02", "z3"]);
-
-        let walking_anim_name = walking_anim
-            .map(|(name, _)| name)
-            .unwrap_or(idle_anim_name)
-            .to_string();
-        
-        let running_anim_name = running_anim
-            .map(|(name, _)| name)
-            .unwrap_or(walking_anim_name.as_str())
-            .to_string();
-
         Self {
             model_name: role_name.to_string(),
             asset_mgr: asset_mgr.clone(),
             component_factory: asset_mgr.component_factory().clone(),
             animations,
             active_anim_name: idle_anim_name.to_string(),
-            idle_anim_name: idle_anim_name.to_string(),
-            walking_anim_name,
-            running_anim_name,
+            idle_anim: idle,
+            walking_anim,
+            running_anim,
+            talking_anim,
             anim_repeat_mode: RoleAnimationRepeatMode::Repeat,
             is_active: false,
             state: RoleState::Idle,
@@ -102,7 +195,12 @@ impl RoleEntity {
         self.is_active = active;
         if active {
             let anim_name = self.active_anim_name.clone();
-            self.play_anim(&anim_name, self.anim_repeat_mode);
+            let clip_name = self
+                .animations
+                .get(&anim_name)
+                .and_then(|anim| anim.active_clip())
+                .map(|clip| clip.name.clone());
+            self.play_anim_clip(&anim_name, clip_name.as_deref(), self.anim_repeat_mode);
         } else {
             self.remove_component::<RenderingComponent>();
         }
@@ -114,49 +212,41 @@ impl RoleEntity {
         anim_name: &str,
         repeat_mode: RoleAnimationRepeatMode,
     ) {
-        let anim_name = if anim_name.is_empty() {
-            self.idle_anim_name.to_lowercase()
-        } else {
-            anim_name.to_lowercase()
-        };
-
-        if self.animations.get(&anim_name).is_none() {
-            let anim = self
-                .asset_mgr
-                .load_role_anim(&self.model_name, &anim_name)
-                .unwrap();
-            self.animations.insert(anim_name.to_string(), anim);
-        }
+        self.play_anim_clip(anim_name, None, repeat_mode);
+    }
 
-        self.active_anim_name = anim_name.to_string();
-        self.anim_repeat_mode = repeat_mode;
-        self.active_anim_mut().reset(repeat_mode);
+    /// Plays the mv3 file, or the action clip in it, that the role uses for
+    /// the action.
+    pub fn play_action(
+        self: &mut CoreEntity<Self>,
+        action: RoleAction,
+        repeat_mode: RoleAnimationRepeatMode,
+    ) {
+        let anim = self.action_anim(action).clone();
+        self.play_anim_clip(&anim.anim_name, anim.clip_name.as_deref(), repeat_mode);
+    }
 
-        self.remove_component::<RenderingComponent>();
-        let rc = self.active_anim().create_rendering_component();
-        self.add_component(Box::new(rc));
+    pub fn action_anim_name(&self, action: RoleAction) -> &str {
+        &self.action_anim(action).anim_name
     }
 
     pub fn run(self: &mut CoreEntity<Self>) {
         if self.state != RoleState::Running {
-            let name = self.running_anim_name.clone();
-            self.play_anim(&name, RoleAnimationRepeatMode::Repeat);
+            self.play_action(RoleAction::Run, RoleAnimationRepeatMode::Repeat);
             self.state = RoleState::Running;
         }
     }
 
     pub fn idle(self: &mut CoreEntity<Self>) {
         if self.state != RoleState::Idle {
-            let name = self.idle_anim_name.clone();
-            self.play_anim(&name, RoleAnimationRepeatMode::Repeat);
+            self.play_action(RoleAction::Idle, RoleAnimationRepeatMode::Repeat);
             self.state = RoleState::Idle;
         }
     }
 
     pub fn walk(self: &mut CoreEntity<Self>) {
         if self.state != RoleState::Walking {
-            let name = self.walking_anim_name.clone();
-            self.play_anim(&name, RoleAnimationRepeatMode::Repeat);
+            self.play_action(RoleAction::Walk, RoleAnimationRepeatMode::Repeat);
             self.state = RoleState::Walking;
         }
     }
@@ -169,6 +259,44 @@ impl RoleEntity {
         self.state
     }
 
+    fn play_anim_clip(
+        self: &mut CoreEntity<Self>,
+        anim_name: &str,
+        clip_name: Option<&str>,
+        repeat_mode: RoleAnimationRepeatMode,
+    ) {
+        let anim_name = if anim_name.is_empty() {
+            self.idle_anim.anim_name.to_lowercase()
+        } else {
+            anim_name.to_lowercase()
+        };
+
+        if self.animations.get(&anim_name).is_none() {
+            let anim = self
+                .asset_mgr
+                .load_role_anim(&self.model_name, &anim_name)
+                .unwrap();
+            self.animations.insert(anim_name.to_string(), anim);
+        }
+
+        self.active_anim_name = anim_name.to_string();
+        self.anim_repeat_mode = repeat_mode;
+        self.active_anim_mut().reset_to_clip(repeat_mode, clip_name);
+
+        self.remove_component::<RenderingComponent>();
+        let rc = self.active_anim().create_rendering_component();
+        self.add_component(Box::new(rc));
+    }
+
+    fn action_anim(&self, action: RoleAction) -> &RoleActionAnim {
+        match action {
+            RoleAction::Idle => &self.idle_anim,
+            RoleAction::Walk => &self.walking_anim,
+            RoleAction::Run => &self.running_anim,
+            RoleAction::Talk => &self.talking_anim,
+        }
+    }
+
     fn active_anim(&self) -> &RoleAnimation {
         self.animations.get(&self.active_anim_name).unwrap()
     }
@@ -180,7 +308,7 @@ impl RoleEntity {
 
 impl EntityExtension for RoleEntity {
     fn on_loading(self: &mut CoreEntity<Self>) {
-        if !self.idle_anim_name.trim().is_empty() && self.is_active {
+        if !self.idle_anim.anim_name.trim().is_empty() && self.is_active {
             self.idle();
         }
     }
@@ -192,11 +320,8 @@ impl EntityExtension for RoleEntity {
                 &mut *(self.get_component_mut::<RenderingComponent>().unwrap()
                     as *mut RenderingComponent)
             };
-            let ro = rc.render_objects_mut().first_mut().unwrap();
-
-            ro.update_vertices(&mut |vb: &mut VertexBuffer| {
-                self.active_anim_mut().update(delta_sec, vb, false);
-            });
+            self.active_anim_mut()
+                .update(delta_sec, rc.render_objects_mut());
 
             if self.active_anim().anim_finished() {
                 self.state = RoleState::Idle;
@@ -211,31 +336,152 @@ impl EntityExtension for RoleEntity {
 
 pub struct RoleAnimation {
     component_factory: Rc<dyn ComponentFactory>,
-    frames: Vec<VertexBuffer>,
-    anim_timestamps: Vec<u32>,
+    meshes: Vec<RoleAnimationMesh>,
+    materials: Vec<MaterialDef>,
+    clips: Vec<Mv3ActionClip>,
+    active_clip: Option<Mv3ActionClip>,
+    anim_length: u32,
     last_anim_time: u32,
     repeat_mode: RoleAnimationRepeatMode,
     anim_finished: bool,
-    vertices: VertexBuffer,
-    indices: Vec<u32>,
-    material: MaterialDef,
 }
 
 impl RoleAnimation {
+    /// Every mesh of every model becomes a render object. The meshes use the
+    /// materials in order and fall back to the first one when there are
+    /// fewer materials than meshes.
     pub fn new(
         component_factory: &Rc<dyn ComponentFactory>,
         mv3file: &Mv3File,
-        material: MaterialDef,
+        materials: Vec<MaterialDef>,
         anim_repeat_mode: RoleAnimationRepeatMode,
     ) -> Self {
-        let model: &Mv3Model = &mv3file.models[0];
-        let mesh: &Mv3Mesh = &model.meshes[0];
+        let mut meshes = vec![];
+        for model in mv3file.models.iter().filter(|m| !m.frames.is_empty()) {
+            for mesh in model.meshes.iter().filter(|m| !m.triangles.is_empty()) {
+                let material_index = if meshes.len() < materials.len() {
+                    meshes.len()
+                } else {
+                    0
+                };
+
+                meshes.push(RoleAnimationMesh::new(model, mesh, material_index));
+            }
+        }
 
+        let anim_length = meshes
+            .iter()
+            .map(|m| *m.anim_timestamps.last().unwrap())
+            .max()
+            .unwrap_or(0);
+
+        Self {
+            component_factory: component_factory.clone(),
+            meshes,
+            materials,
+            clips: mv3_action_clips(mv3file),
+            active_clip: None,
+            anim_length,
+            last_anim_time: 0,
+            repeat_mode: anim_repeat_mode,
+            anim_finished: false,
+        }
+    }
+
+    pub fn reset(&mut self, repeat_mode: RoleAnimationRepeatMode) {
+        self.reset_to_clip(repeat_mode, None);
+    }
+
+    /// Plays only the ticks of the named action clip, or the whole file when
+    /// the clip is `None` or isn't in the file.
+    pub fn reset_to_clip(&mut self, repeat_mode: RoleAnimationRepeatMode, clip_name: Option<&str>) {
+        self.active_clip = clip_name.and_then(|name| self.clip(name)).cloned();
+        self.anim_finished = false;
+        self.last_anim_time = self.tick_range().0;
+        self.repeat_mode = repeat_mode;
+    }
+
+    /// Updates the render objects created by `create_rendering_component`.
+    pub fn update(&mut self, delta_sec: f32, objects: &mut [Box<dyn RenderObject>]) {
+        let (start_tick, end_tick) = self.tick_range();
+        let mut anim_time = (delta_sec * 4580.) as u32 + self.last_anim_time;
+        if anim_time >= end_tick && self.repeat_mode == RoleAnimationRepeatMode::NoRepeat {
+            self.anim_finished = true;
+            return;
+        }
+
+        if end_tick > start_tick {
+            anim_time = start_tick + (anim_time - start_tick) % (end_tick - start_tick);
+        }
+
+        for (mesh, ro) in self.meshes.iter().zip(objects.iter_mut()) {
+            ro.update_vertices(&mut |vb: &mut VertexBuffer| {
+                mesh.update(anim_time, vb);
+            });
+        }
+
+        self.last_anim_time = anim_time;
+    }
+
+    pub fn anim_finished(&self) -> bool {
+        self.anim_finished
+    }
+
+    pub fn clips(&self) -> &[Mv3ActionClip] {
+        &self.clips
+    }
+
+    /// The clip that `reset_to_clip` restricted the playback to.
+    pub fn active_clip(&self) -> Option<&Mv3ActionClip> {
+        self.active_clip.as_ref()
+    }
+
+    pub fn clip(&self, name: &str) -> Option<&Mv3ActionClip> {
+        self.clips
+            .iter()
+            .find(|c| c.name.eq_ignore_ascii_case(name))
+    }
+
+    fn tick_range(&self) -> (u32, u32) {
+        self.active_clip
+            .as_ref()
+            .map(|clip| (clip.start_tick, clip.end_tick))
+            .unwrap_or((0, self.anim_length))
+    }
+
+    pub fn create_rendering_component(&self) -> RenderingComponent {
+        let objects = self
+            .meshes
+            .iter()
+            .map(|mesh| {
+                self.component_factory.create_render_object(
+                    mesh.vertices.clone(),
+                    mesh.indices.clone(),
+                    &self.materials[mesh.material_index],
+                    true,
+                )
+            })
+            .collect();
+
+        self.component_factory.create_rendering_component(objects)
+    }
+}
+
+struct RoleAnimationMesh {
+    frames: Vec<VertexBuffer>,
+    anim_timestamps: Vec<u32>,
+    vertices: VertexBuffer,
+    indices: Vec<u32>,
+    material_index: usize,
+}
+
+impl RoleAnimationMesh {
+    fn new(model: &Mv3Model, mesh: &Mv3Mesh, material_index: usize) -> Self {
         let hash =
             |index, texcoord_index| index as u32 * model.texcoord_count + texcoord_index as u32;
 
         let mut indices: Vec<u32> = Vec::<u32>::with_capacity(model.vertex_per_frame as usize);
-        let mut vertices_data: Vec<Vec<(Vec3, Vec2)>> = vec![vec![]; model.frame_count as usize];
+        let mut vertices_data: Vec<Vec<(Vec3, Vec3, Vec2)>> = vec![vec![]; model.frames.len()];
         let mut index_map = HashMap::new();
 
         for t in &mesh.triangles {
@@ -244,14 +490,14 @@ impl RoleAnimation {
                 let index = match index_map.get(&h) {
                     None => {
                         let index = index_map.len();
-                        for k in 0..model.frame_count as usize {
-                            let frame = &model.frames[k];
+                        for (k, frame) in model.frames.iter().enumerate() {
                             vertices_data[k].push((
                                 Vec3::new(
                                     frame.vertices[i as usize].x as f32 * 0.01562,
                                     frame.vertices[i as usize].y as f32 * 0.01562,
                                     frame.vertices[i as usize].z as f32 * 0.01562,
                                 ),
+                                mv3_vertex_normal(&frame.vertices[i as usize]),
                                 Vec2::new(
                                     model.texcoords[j as usize].u,
                                     -model.texcoords[j as usize].v,
@@ -268,76 +514,64 @@ impl RoleAnimation {
             }
         }
 
-        let mut frames: Vec<VertexBuffer> =
-            Vec::<VertexBuffer>::with_capacity(model.frame_count as usize);
-        for i in 0..model.frame_count as usize {
-            frames.push(VertexBuffer::new(
-                VertexComponents::POSITION | VertexComponents::TEXCOORD,
+        let mut frames: Vec<VertexBuffer> = Vec::<VertexBuffer>::with_capacity(model.frames.len());
+        for vertex_data in &vertices_data {
+            let mut vert = VertexBuffer::new(
+                VertexComponents::POSITION | VertexComponents::NORMAL | VertexComponents::TEXCOORD,
                 index_map.len(),
-            ));
+            );
 
-            let vertex_data = &vertices_data[i];
-            let vert = frames.get_mut(i).unwrap();
             for j in 0..vertex_data.len() {
                 vert.set_component(j, VertexComponents::POSITION, |p: &mut Vec3| {
                     *p = vertex_data[j].0;
                 });
+                vert.set_component(j, VertexComponents::NORMAL, |n: &mut Vec3| {
+                    *n = vertex_data[j].1;
+                });
                 vert.set_component(j, VertexComponents::TEXCOORD, |t: &mut Vec2| {
-                    *t = vertex_data[j].1;
+                    *t = vertex_data[j].2;
                 });
             }
+
+            frames.push(vert);
         }
 
         let anim_timestamps = model.frames.iter().map(|f| f.timestamp).collect();
         let vertices = frames[0].clone();
 
         Self {
-            component_factory: component_factory.clone(),
             frames,
             anim_timestamps,
-            last_anim_time: 0,
-            repeat_mode: anim_repeat_mode,
-            anim_finished: false,
             vertices,
             indices,
-            material,
+            material_index,
         }
     }
 
-    pub fn reset(&mut self, repeat_mode: RoleAnimationRepeatMode) {
-        self.anim_finished = false;
-        self.last_anim_time = 0;
-        self.repeat_mode = repeat_mode;
-    }
-
-    pub fn update(&mut self, delta_sec: f32, vertices: &mut VertexBuffer, debug: bool) {
-        let mut anim_time = (delta_sec * 4580.) as u32 + self.last_anim_time;
-        let total_anim_length = *self.anim_timestamps.last().unwrap();
-        if anim_time >= total_anim_length && self.repeat_mode == RoleAnimationRepeatMode::NoRepeat {
-            self.anim_finished = true;
-            return;
-        }
-
-        anim_time %= total_anim_length;
+    // A model whose frames end earlier than the animation stays at its last frame
+    fn update(&self, anim_time: u32, vertices: &mut VertexBuffer) {
         let frame_index = self
             .anim_timestamps
             .iter()
-            .position(|&t| t > anim_time)
-            .unwrap_or(0)
-            - 1;
-        if debug {
-            println!("frame_index {}", frame_index);
-        }
-        let next_frame_index = (frame_index + 1) % self.anim_timestamps.len();
-        let percentile = (anim_time - self.anim_timestamps[frame_index]) as f32
-            / (self.anim_timestamps[next_frame_index] - self.anim_timestamps[frame_index]) as f32;
+            .rposition(|&t| t <= anim_time)
+            .unwrap_or(0);
+        let next_frame_index = (frame_index + 1).min(self.anim_timestamps.len() - 1);
+        let time = self.anim_timestamps[frame_index];
+        let next_time = self.anim_timestamps[next_frame_index];
+        let percentile = if next_time > time {
+            (anim_time - time) as f32 / (next_time - time) as f32
+        } else {
+            0.
+        };
 
-        let vertex_buffer = self.frames.get(frame_index).unwrap();
-        let next_vertex_buffer = self.frames.get(next_frame_index).unwrap();
+        let vertex_buffer = &self.frames[frame_index];
+        let next_vertex_buffer = &self.frames[next_frame_index];
 
-        for i in 0..self.frames.get(frame_index).unwrap().count() {
+        for i in 0..vertex_buffer.count() {
             let position = vertex_buffer.position(i).unwrap();
             let next_position = next_vertex_buffer.position(i).unwrap();
+            let normal = vertex_buffer.normal(i).unwrap();
+            let next_normal = next_vertex_buffer.normal(i).unwrap();
             let tex_coord = vertex_buffer.tex_coord(i).unwrap();
 
             vertices.set_component(i, VertexComponents::POSITION, |p: &mut Vec3| {
@@ -345,27 +579,42 @@ impl RoleAnimation {
                 p.y = position.y * (1. - percentile) + next_position.y * percentile;
                 p.z = position.z * (1. - percentile) + next_position.z * percentile;
             });
+            vertices.set_component(i, VertexComponents::NORMAL, |n: &mut Vec3| {
+                *n = Vec3::normalized(&Vec3::lerp(normal, next_normal, percentile));
+            });
             vertices.set_component(i, VertexComponents::TEXCOORD, |t: &mut Vec2| {
                 t.x = tex_coord.x;
                 t.y = tex_coord.y;
             });
         }
-
-        self.last_anim_time = anim_time;
     }
+}
 
-    pub fn anim_finished(&self) -> bool {
-        self.anim_finished
+// The ini file may name several mv3 files for an action, separated by commas
+fn role_config_anim_names(config: Option<&Ini>, action: RoleAction) -> Vec<String> {
+    let mut anim_names = vec![];
+    if let Some(config) = config {
+        for (_, properties) in config.iter() {
+            for (key, value) in properties.iter() {
+                if key.trim().eq_ignore_ascii_case(action.config_key()) {
+                    anim_names.extend(
+                        value
+                            .split(',')
+                            .map(|n| n.trim().to_lowercase())
+                            .filter(|n| !n.is_empty()),
+                    );
+                }
+            }
+        }
     }
 
-    pub fn create_rendering_component(&self) -> RenderingComponent {
-        let ro = self.component_factory.create_render_object(
-            self.vertices.clone(),
-            self.indices.clone(),
-            &self.material,
-            true,
-        );
+    anim_names
+}
 
-        self.component_factory.create_rendering_component(vec![ro])
-    }
+// The normals are stored in spherical coordinates: `normal_phi` is the
+// elevation in [-pi/2, pi/2] and `normal_theta` is the azimuth in [0, 2pi).
+fn mv3_vertex_normal(vertex: &Mv3Vertex) -> Vec3 {
+    let phi = vertex.normal_phi as f32 / 128. * std::f32::consts::FRAC_PI_2;
+    let theta = vertex.normal_theta as f32 / 256. * 2. * std::f32::consts::PI;
+    Vec3::new(phi.cos() * theta.cos(), phi.sin(), phi.cos() * theta.sin())
 }
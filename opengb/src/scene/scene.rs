@@ -4,7 +4,7 @@ use radiance::scene::{CoreEntity, CoreScene, Entity, SceneExtension};
 use radiance::{math::Vec3, scene::Scene};
 use std::rc::Rc;
 
-use super::RoleEntity;
+use super::{pathfinding, Nav, RoleEntity};
 
 pub struct ScnScene {
     asset_mgr: Rc<AssetManager>,
@@ -15,6 +15,7 @@ pub struct ScnScene {
     nav_triggers: Vec<SceNavTrigger>,
     aabb_triggers: Vec<SceAabbTrigger>,
     item_triggers: Vec<SceItemTrigger>,
+    party_members: Vec<i32>,
 }
 
 impl SceneExtension for ScnScene {
@@ -43,6 +44,7 @@ impl ScnScene {
             nav_triggers: vec![],
             aabb_triggers: vec![],
             item_triggers: vec![],
+            party_members: vec![],
         }
     }
 
@@ -54,31 +56,25 @@ impl ScnScene {
         &self.scn_name
     }
 
+    pub fn nav(&self) -> &Nav {
+        &self.nav
+    }
+
+    /// The scripts and the nav triggers use the nav coordinates of the first
+    /// layer.
     pub fn nav_min_coord(&self) -> Vec3 {
-        self.nav.nav_file.maps[0].min_coord
+        self.nav.min_coord(0)
     }
 
     pub fn nav_block_size(&self) -> (f32, f32) {
-        (self.nav.block_size_x, self.nav.block_size_z)
+        self.nav.block_size(0)
     }
 
+    /// The layer is picked from the height of `coord`.
     pub fn get_distance_to_border_by_scene_coord(&self, coord: &Vec3) -> f32 {
-        let nav_coord = self.scene_coord_to_nav_coord(coord);
-        let nav_coord_floor = (
-            (nav_coord.0.floor() as usize).clamp(0, self.nav.nav_file.maps[0].width as usize - 1),
-            (nav_coord.1.floor() as usize).clamp(0, self.nav.nav_file.maps[0].height as usize - 1),
-        );
-
-        let nav_coord_ceil = (
-            (nav_coord.0.ceil() as usize).clamp(0, self.nav.nav_file.maps[0].width as usize - 1),
-            (nav_coord.1.ceil() as usize).clamp(0, self.nav.nav_file.maps[0].height as usize - 1),
-        );
-        let distance_floor = &self.nav.nav_file.maps[0].map[nav_coord_floor.1][nav_coord_floor.0];
-        let distance_ceil = &self.nav.nav_file.maps[0].map[nav_coord_ceil.1][nav_coord_ceil.0];
-        std::cmp::min(
-            distance_floor.distance_to_border,
-            distance_ceil.distance_to_border,
-        ) as f32
+        let layer = self.nav.pick_layer(coord);
+        let nav_coord = self.nav.scene_coord_to_nav_coord(layer, coord);
+        self.nav.get_distance_to_border(layer, nav_coord)
     }
 
     pub fn test_nav_trigger(&self, coord: &Vec3) -> Option<u32> {
@@ -148,30 +144,34 @@ impl ScnScene {
         None
     }
 
-    pub fn get_height(&self, nav_coord: (f32, f32)) -> f32 {
-        let x =
-            (nav_coord.0.ceil() as usize).clamp(0, self.nav.nav_file.maps[0].width as usize - 1);
-        let y =
-            (nav_coord.1.ceil() as usize).clamp(0, self.nav.nav_file.maps[0].height as usize - 1);
-        self.nav.nav_file.maps[0].map[y][x].height
+    /// The height of the ground under `coord`, on the floor that is the
+    /// closest to the height of `coord`.
+    pub fn get_height(&self, coord: &Vec3) -> f32 {
+        let layer = self.nav.pick_layer(coord);
+        let nav_coord = self.nav.scene_coord_to_nav_coord(layer, coord);
+        self.nav.get_height(layer, nav_coord)
+    }
+
+    /// Whether a role can move from `from` to `to` on the floor under
+    /// `from`, without walking out of the walkable area or climbing too
+    /// steep.
+    pub fn can_move_to(&self, from: &Vec3, to: &Vec3) -> bool {
+        let layer = self.nav.pick_layer(from);
+        self.nav.can_move(layer, from, to)
+    }
+
+    /// A path from `from` to `to` that stays in the walkable area, or `None`
+    /// if there isn't one.
+    pub fn find_path(&self, from: &Vec3, to: &Vec3) -> Option<Vec<Vec3>> {
+        pathfinding::find_path(&self.nav, from, to)
     }
 
     pub fn scene_coord_to_nav_coord(&self, coord: &Vec3) -> (f32, f32) {
-        let min_coord = self.nav_min_coord();
-        (
-            (coord.x - min_coord.x) / self.nav.block_size_x,
-            (coord.z - min_coord.z) / self.nav.block_size_z,
-        )
+        self.nav.scene_coord_to_nav_coord(0, coord)
     }
 
     pub fn nav_coord_to_scene_coord(&self, nav_x: f32, nav_z: f32) -> Vec3 {
-        let min_coord = self.nav_min_coord();
-        let block_size = self.nav_block_size();
-        Vec3::new(
-            nav_x * block_size.0 + min_coord.x,
-            self.get_height((nav_x, nav_z)),
-            nav_z * block_size.1 + min_coord.z,
-        )
+        self.nav.nav_coord_to_scene_coord(0, nav_x, nav_z)
     }
 
     pub fn get_object<'a>(self: &'a mut CoreScene<Self>, id: i32) -> Option<&'a dyn Entity> {
@@ -191,6 +191,28 @@ impl ScnScene {
             .map(|e| &mut **e)
     }
 
+    /// The roles of the team, which are spawned when the scene is loaded.
+    pub fn set_party_members(&mut self, party_members: &[i32]) {
+        self.party_members = party_members.to_vec();
+    }
+
+    pub fn has_role_entity(self: &CoreScene<Self>, id: i32) -> bool {
+        let name = format!("ROLE_{}", id);
+        self.root_entities().iter().any(|e| e.name() == name)
+    }
+
+    /// Spawns a role that joins the team after the scene has been loaded.
+    pub fn spawn_party_role(self: &mut CoreScene<Self>, id: i32) {
+        if self.has_role_entity(id) {
+            return;
+        }
+
+        if let Some(mut entity) = self.create_party_role(id) {
+            entity.load();
+            self.add_entity(Box::new(entity));
+        }
+    }
+
     pub fn get_role_entity<'a>(
         self: &'a mut CoreScene<Self>,
         id: i32,
@@ -252,91 +274,96 @@ impl ScnScene {
         let ground_pol_name = self.scn_file.scn_base_name.clone() + ".pol";
         let mut entities: Vec<Box<dyn Entity>> = vec![];
 
-        let mut scn_object = self
-            .asset_mgr
-            .load_scn_pol(
-                &self.cpk_name,
-                &self.scn_file.scn_base_name,
-                &ground_pol_name,
-                std::u16::MAX,
-            )
-            .unwrap();
-        Self::apply_position_rotation(&mut scn_object, &Vec3::new(0., 0., 0.), 0.);
-        entities.push(Box::new(scn_object));
+        match self.asset_mgr.load_scn_pol(
+            &self.cpk_name,
+            &self.scn_file.scn_base_name,
+            &ground_pol_name,
+            std::u16::MAX,
+        ) {
+            Ok(mut scn_object) => {
+                Self::apply_position_rotation(&mut scn_object, &Vec3::new(0., 0., 0.), 0.);
+                entities.push(Box::new(scn_object));
+            }
+            Err(e) => log::error!("Cannot load scene ground: {}", e),
+        }
 
         let _self = self.extension_mut();
         for obj in &_self.scn_file.nodes {
             let mut entity: Option<Box<dyn Entity>> = None;
-            if obj.nav_trigger_coord_min.0 != 0
-                || obj.nav_trigger_coord_min.1 != 0
-                || obj.nav_trigger_coord_max.0 != 0
-                || obj.nav_trigger_coord_max.1 != 0
-            {
+            if let Some(trigger) = scn_node_nav_trigger(obj) {
                 _self.nav_triggers.push(SceNavTrigger {
-                    nav_coord_max: obj.nav_trigger_coord_max,
-                    nav_coord_min: obj.nav_trigger_coord_min,
-                    sce_proc_id: obj.sce_proc_id,
+                    nav_coord_max: trigger.coord_max,
+                    nav_coord_min: trigger.coord_min,
+                    sce_proc_id: trigger.sce_proc_id,
                 });
             }
 
-            if obj.node_type == 16 {
-                _self.item_triggers.push(SceItemTrigger {
-                    coord: obj.position,
-                    sce_proc_id: obj.sce_proc_id,
-                });
-            } else if obj.node_type == 20 {
-                _self.aabb_triggers.push(SceAabbTrigger {
-                    aabb_coord2: obj.aabb_trigger_coord2,
-                    aabb_coord1: obj.aabb_trigger_coord1,
-                    sce_proc_id: obj.sce_proc_id,
-                });
+            let kind = scn_node_kind(obj);
+            match kind {
+                ScnNodeKind::ItemTrigger {
+                    position,
+                    sce_proc_id,
+                } => _self.item_triggers.push(SceItemTrigger {
+                    coord: position,
+                    sce_proc_id,
+                }),
+                ScnNodeKind::AabbTrigger {
+                    coord1,
+                    coord2,
+                    sce_proc_id,
+                } => _self.aabb_triggers.push(SceAabbTrigger {
+                    aabb_coord2: coord2,
+                    aabb_coord1: coord1,
+                    sce_proc_id,
+                }),
+                _ => {}
             }
 
-            let visible = obj.node_type != 17 && obj.node_type != 25;
-            if obj.node_type != 37 && obj.node_type != 43 && obj.name.len() != 0 {
-                if obj.name.as_bytes()[0] as char == '_' {
-                    if let Some(p) = _self.asset_mgr.load_scn_pol(
+            let visible = match kind {
+                ScnNodeKind::Invisible { .. } => false,
+                _ => true,
+            };
+
+            match scn_node_model(obj) {
+                ScnNodeModel::Scene(name) => {
+                    if let Ok(p) = _self.asset_mgr.load_scn_pol(
                         &_self.cpk_name,
                         &_self.scn_name,
-                        &obj.name,
+                        &name,
                         obj.index,
                     ) {
                         entity = Some(Box::new(p));
-                    } else if let Some(c) = _self.asset_mgr.load_scn_cvd(
-                        &_self.cpk_name,
-                        &_self.scn_name,
-                        &obj.name,
-                        obj.index,
-                    ) {
-                        entity = Some(Box::new(c));
                     } else {
-                        log::error!("Cannot load object: {}", obj.name);
+                        match _self.asset_mgr.load_scn_cvd(
+                            &_self.cpk_name,
+                            &_self.scn_name,
+                            &name,
+                            obj.index,
+                        ) {
+                            Ok(c) => entity = Some(Box::new(c)),
+                            Err(e) => log::error!("Cannot load object {}: {}", name, e),
+                        }
                     }
-                } else if obj.name.to_lowercase().ends_with(".pol") {
-                    entity = Some(Box::new(
-                        _self
-                            .asset_mgr
-                            .load_object_item_pol(&obj.name, obj.index, visible)
-                            .unwrap(),
-                    ));
-                } else if obj.name.to_lowercase().ends_with(".cvd") {
-                    entity = Some(Box::new(
-                        _self
-                            .asset_mgr
-                            .load_object_item_cvd(&obj.name, obj.index, visible)
-                            .unwrap(),
-                    ));
-                } else if obj.name.as_bytes()[0] as char == '+' {
-                    // Unknown
-                    continue;
-                } else {
-                    entity = Some(Box::new(
-                        _self
-                            .asset_mgr
-                            .load_object_item_pol(&obj.name, obj.index, visible)
-                            .unwrap(),
-                    ));
                 }
+                ScnNodeModel::ObjectCvd(name) => {
+                    match _self
+                        .asset_mgr
+                        .load_object_item_cvd(&name, obj.index, visible)
+                    {
+                        Ok(c) => entity = Some(Box::new(c)),
+                        Err(e) => log::error!("Cannot load object {}: {}", name, e),
+                    }
+                }
+                ScnNodeModel::ItemPol(name) => {
+                    match _self
+                        .asset_mgr
+                        .load_object_item_pol(&name, obj.index, visible)
+                    {
+                        Ok(p) => entity = Some(Box::new(p)),
+                        Err(e) => log::error!("Cannot load object {}: {}", name, e),
+                    }
+                }
+                ScnNodeModel::Unknown(_) | ScnNodeModel::None => {}
             }
 
             if let Some(mut p) = entity {
@@ -366,13 +393,22 @@ impl ScnScene {
         }
     }
 
+    fn create_party_role(&self, id: i32) -> Option<CoreEntity<RoleEntity>> {
+        let model_name = Self::map_role_id(id).to_string();
+        match self.asset_mgr.load_role(&model_name, "C01") {
+            Some(role_entity) => Some(CoreEntity::new(role_entity, format!("ROLE_{}", id), false)),
+            None => {
+                log::error!("Cannot load the role {} of the team", id);
+                None
+            }
+        }
+    }
+
     fn load_roles(self: &mut CoreScene<ScnScene>) {
-        for i in &[0, 1, 5] {
-            let entity_name = format!("ROLE_{}", i);
-            let model_name = Self::map_role_id(*i).to_string();
-            let role_entity = self.asset_mgr.load_role(&model_name, "C01").unwrap();
-            let entity = CoreEntity::new(role_entity, entity_name, false);
-            self.add_entity(Box::new(entity));
+        for i in self.party_members.clone() {
+            if let Some(entity) = self.create_party_role(i) {
+                self.add_entity(Box::new(entity));
+            }
         }
 
         let mut entities = vec![];
@@ -403,25 +439,6 @@ impl ScnScene {
     }
 }
 
-pub struct Nav {
-    nav_file: NavFile,
-    block_size_x: f32,
-    block_size_z: f32,
-}
-
-impl Nav {
-    pub fn new(nav_file: NavFile) -> Self {
-        let area = Vec3::sub(&nav_file.maps[0].max_coord, &nav_file.maps[0].min_coord);
-        let width = nav_file.maps[0].width;
-        let height = nav_file.maps[0].height;
-        Self {
-            nav_file,
-            block_size_x: area.x / width as f32,
-            block_size_z: area.z / height as f32,
-        }
-    }
-}
-
 pub struct SceNavTrigger {
     nav_coord_min: (i32, i32),
     nav_coord_max: (i32, i32),
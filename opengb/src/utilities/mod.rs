@@ -1,5 +1,7 @@
-pub use read_ext::ReadExt;
+pub use read_ext::{decode_gbk, ReadExt};
 pub use store_ext::StoreExt2;
+pub use write_ext::{check_len, encode_gbk, WriteExt};
 
 mod read_ext;
 mod store_ext;
+mod write_ext;
@@ -45,3 +45,11 @@ pub trait ReadExt: Read {
 }
 
 impl<T: Read + ?Sized> ReadExt for T {}
+
+/// Decodes a GBK string up to the first `\0`, skipping the invalid bytes.
+pub fn decode_gbk(bytes: &[u8]) -> String {
+    let end = bytes.iter().position(|&c| c == 0).unwrap_or(bytes.len());
+    encoding::all::GBK
+        .decode(&bytes[..end], DecoderTrap::Ignore)
+        .unwrap_or_default()
+}
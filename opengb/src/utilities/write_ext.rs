@@ -0,0 +1,54 @@
+use byteorder::{LittleEndian, WriteBytesExt};
+use encoding::{types::Encoding, EncoderTrap};
+use std::io::{self, Write};
+
+pub trait WriteExt: Write {
+    fn write_dw_vec(&mut self, data: &[u32]) -> io::Result<()> {
+        for dw in data {
+            self.write_u32::<LittleEndian>(*dw)?;
+        }
+
+        Ok(())
+    }
+
+    fn write_w_vec(&mut self, data: &[u16]) -> io::Result<()> {
+        for w in data {
+            self.write_u16::<LittleEndian>(*w)?;
+        }
+
+        Ok(())
+    }
+
+    fn write_f32_vec(&mut self, data: &[f32]) -> io::Result<()> {
+        for f in data {
+            self.write_f32::<LittleEndian>(*f)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<T: Write + ?Sized> WriteExt for T {}
+
+pub fn encode_gbk(s: &str) -> io::Result<Vec<u8>> {
+    encoding::all::GBK
+        .encode(s, EncoderTrap::Strict)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e.to_string()))
+}
+
+/// Fails if a fixed size field does not hold exactly `len` items.
+pub fn check_len<T>(field: &str, data: &[T], len: usize) -> io::Result<()> {
+    if data.len() == len {
+        Ok(())
+    } else {
+        Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "{} has {} items but {} are expected",
+                field,
+                data.len(),
+                len
+            ),
+        ))
+    }
+}
@@ -29,10 +29,13 @@ impl ApplicationExtension<OpenPal3Application> for OpenPal3Application {
 
         let input_engine = app.engine_mut().input_engine();
         let audio_engine = app.engine_mut().audio_engine();
-        self.asset_mgr = Some(Rc::new(AssetManager::new(
-            app.engine_mut().rendering_component_factory(),
-            &self.root_path,
-        )));
+        self.asset_mgr = Some(Rc::new(
+            AssetManager::new(
+                app.engine_mut().rendering_component_factory(),
+                &self.root_path,
+            )
+            .unwrap_or_else(|e| panic!("Cannot open {:?}: {}", &self.root_path, e)),
+        ));
 
         let director = main_menu_director::MainMenuDirector::new(
             self.asset_mgr.as_ref().unwrap().clone(),
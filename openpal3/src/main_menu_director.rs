@@ -22,9 +22,11 @@ impl MainMenuDirector {
         audio_engine: Rc<dyn AudioEngine>,
         input_engine: Rc<RefCell<dyn InputEngine>>,
     ) -> Self {
-        let data = asset_mgr.load_music_data("PI01");
         let mut main_theme_source = audio_engine.create_source();
-        main_theme_source.play(data, Codec::Mp3, true);
+        match asset_mgr.load_music_data("PI01") {
+            Ok(data) => main_theme_source.play(data, Codec::Mp3, true),
+            Err(e) => log::error!("Cannot open the main theme: {}", e),
+        }
 
         Self {
             asset_mgr,
@@ -51,12 +53,15 @@ impl Director for MainMenuDirector {
         self.main_theme_source.update();
 
         if ui.button(im_str!("开始游戏"), [120., 40.]) {
-            return Some(Rc::new(RefCell::new(AdventureDirector::new(
+            match AdventureDirector::new(
                 "OpenPAL3",
                 self.asset_mgr.clone(),
                 self.audio_engine.clone(),
                 self.input_engine.clone(),
-            ))));
+            ) {
+                Ok(director) => return Some(Rc::new(RefCell::new(director))),
+                Err(e) => log::error!("Cannot start a new game: {}", e),
+            }
         } else {
             for i in 1..5 {
                 if ui.button(&im_str!("存档 {}", i), [120., 40.]) {
@@ -69,7 +74,9 @@ impl Director for MainMenuDirector {
                         i,
                     );
 
-                    return Some(Rc::new(RefCell::new(director.unwrap())));
+                    if let Some(director) = director {
+                        return Some(Rc::new(RefCell::new(director)));
+                    }
                 }
             }
 
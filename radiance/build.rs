@@ -1,8 +1,15 @@
 use std::process::Command;
 
 fn main() {
+    // The shaders are only needed by the rendering engine
+    if std::env::var("CARGO_FEATURE_ENGINE").is_err() {
+        return;
+    }
+
     build_shader("simple_triangle.vert");
     build_shader("simple_triangle.frag");
+    build_shader("lit_texture.vert");
+    build_shader("lit_texture.frag");
 }
 
 fn build_shader(shader_name: &str) {
@@ -1,18 +1,27 @@
 #![feature(arbitrary_self_types)]
 #![allow(unused_variables)]
 
+#[cfg(feature = "engine")]
 #[macro_use]
 mod macros;
 
+#[cfg(feature = "engine")]
 pub mod application;
+#[cfg(feature = "engine")]
 pub mod audio;
+#[cfg(feature = "engine")]
 pub mod imgui;
+#[cfg(feature = "engine")]
 pub mod input;
 pub mod math;
+#[cfg(feature = "engine")]
 pub mod radiance;
+#[cfg(feature = "engine")]
 pub mod rendering;
+#[cfg(feature = "engine")]
 pub mod scene;
 
+#[cfg(feature = "engine")]
 mod constants;
 
 #[macro_use]
@@ -45,6 +45,46 @@ impl Quaternion {
         self
     }
 
+    pub fn normalize(&mut self) -> &mut Self {
+        let norm = (self.x * self.x + self.y * self.y + self.z * self.z + self.w * self.w).sqrt();
+        if norm != 0. {
+            self.x /= norm;
+            self.y /= norm;
+            self.z /= norm;
+            self.w /= norm;
+        }
+
+        self
+    }
+
+    /// Spherical linear interpolation, following the shorter arc.
+    pub fn slerp(from: &Quaternion, to: &Quaternion, t: f32) -> Self {
+        let mut cos = from.x * to.x + from.y * to.y + from.z * to.z + from.w * to.w;
+        let mut to = *to;
+        if cos < 0. {
+            cos = -cos;
+            to = Quaternion::new(-to.x, -to.y, -to.z, -to.w);
+        }
+
+        // Fall back to lerp when the quaternions are too close
+        let (k0, k1) = if cos > 0.9995 {
+            (1. - t, t)
+        } else {
+            let theta = cos.acos();
+            let sin = theta.sin();
+            (((1. - t) * theta).sin() / sin, (t * theta).sin() / sin)
+        };
+
+        let mut q = Quaternion::new(
+            k0 * from.x + k1 * to.x,
+            k0 * from.y + k1 * to.y,
+            k0 * from.z + k1 * to.z,
+            k0 * from.w + k1 * to.w,
+        );
+        q.normalize();
+        q
+    }
+
     pub fn to_rotate_matrix(&self) -> Mat44 {
         let x2 = self.x * self.x;
         let y2 = self.y * self.y;
@@ -73,3 +113,71 @@ impl Quaternion {
         matrix
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::Quaternion;
+    use crate::math::Vec3;
+
+    fn assert_close(actual: &Quaternion, expected: &Quaternion) {
+        let components = [
+            (actual.x, expected.x),
+            (actual.y, expected.y),
+            (actual.z, expected.z),
+            (actual.w, expected.w),
+        ];
+        for (a, e) in components.iter() {
+            assert!((a - e).abs() < 1e-5, "{:?} != {:?}", actual, expected);
+        }
+    }
+
+    fn around_y(angle: f32) -> Quaternion {
+        Quaternion::from_axis_angle(&Vec3::new(0., 1., 0.), angle)
+    }
+
+    #[test]
+    fn slerp_returns_the_ends_at_zero_and_one() {
+        let from = around_y(0.);
+        let to = around_y(std::f32::consts::FRAC_PI_2);
+
+        assert_close(&Quaternion::slerp(&from, &to, 0.), &from);
+        assert_close(&Quaternion::slerp(&from, &to, 1.), &to);
+    }
+
+    #[test]
+    fn slerp_rotates_at_a_constant_speed() {
+        let from = around_y(0.);
+        let to = around_y(std::f32::consts::FRAC_PI_2);
+
+        let quarter = Quaternion::slerp(&from, &to, 0.25);
+        assert_close(&quarter, &around_y(std::f32::consts::FRAC_PI_8));
+
+        let half = Quaternion::slerp(&from, &to, 0.5);
+        assert_close(&half, &around_y(std::f32::consts::FRAC_PI_4));
+    }
+
+    #[test]
+    fn slerp_takes_the_shorter_arc() {
+        // 350 degrees is the same rotation as -10 degrees, so the way from
+        // 10 degrees goes through 0 instead of 180.
+        let from = around_y(10f32.to_radians());
+        let to = around_y(350f32.to_radians());
+
+        let half = Quaternion::slerp(&from, &to, 0.5);
+        let mut expected = around_y(0.);
+        if half.w < 0. {
+            expected = Quaternion::new(-expected.x, -expected.y, -expected.z, -expected.w);
+        }
+
+        assert_close(&half, &expected);
+    }
+
+    #[test]
+    fn slerp_handles_nearly_equal_quaternions() {
+        let from = around_y(0.);
+        let to = around_y(0.001);
+
+        let half = Quaternion::slerp(&from, &to, 0.5);
+        assert_close(&half, &around_y(0.0005));
+    }
+}
@@ -88,6 +88,14 @@ impl Vec3 {
         )
     }
 
+    pub fn lerp(from: &Vec3, to: &Vec3, t: f32) -> Self {
+        Vec3::new(
+            from.x + (to.x - from.x) * t,
+            from.y + (to.y - from.y) * t,
+            from.z + (to.z - from.z) * t,
+        )
+    }
+
     pub fn normalized(vec: &Vec3) -> Self {
         let norm = (vec.x * vec.x + vec.y * vec.y + vec.z * vec.z).sqrt();
         if norm == 0. {
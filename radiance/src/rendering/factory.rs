@@ -1,10 +1,19 @@
 use super::{
-    texture::TextureDef, Material, MaterialDef, RenderObject, RenderingComponent, Shader,
-    ShaderDef, Texture, VertexBuffer,
+    texture::TextureDef, ImguiTexture, Material, MaterialDef, RenderObject, RenderingComponent,
+    Shader, ShaderDef, Texture, VertexBuffer,
 };
+use std::error::Error;
 
 pub trait ComponentFactory {
     fn create_texture(&self, texture_def: &TextureDef) -> Box<dyn Texture>;
+
+    /// Creates a texture that the imgui windows can draw. It has to be kept
+    /// alive while it's drawn.
+    fn create_imgui_texture(
+        &self,
+        texture_def: &TextureDef,
+    ) -> Result<ImguiTexture, Box<dyn Error>>;
+
     fn create_shader(&self, shader_def: &ShaderDef) -> Box<dyn Shader>;
     fn create_material(&self, material_def: &MaterialDef) -> Box<dyn Material>;
     fn create_render_object(
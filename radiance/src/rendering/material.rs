@@ -1,6 +1,6 @@
-use image::ImageFormat;
+use image::{ImageFormat, RgbaImage};
 
-use super::{texture::TextureDef, ShaderDef, SIMPLE_SHADER_DEF};
+use super::{texture::TextureDef, ShaderDef, LIT_SHADER_DEF, SIMPLE_SHADER_DEF};
 use std::io::Read;
 
 pub trait Material: downcast_rs::Downcast + std::fmt::Debug {}
@@ -44,22 +44,35 @@ impl MaterialDef {
 pub struct SimpleMaterialDef;
 impl SimpleMaterialDef {
     pub fn create<R: Read>(reader: Option<&mut R>, use_alpha: bool) -> MaterialDef {
-        let data = if let Some(r) = reader {
-            let mut buf = Vec::new();
-            r.read_to_end(&mut buf).unwrap();
-            image::load_from_memory(&buf)
-                .or_else(|_| image::load_from_memory_with_format(&buf, ImageFormat::Tga))
-                .and_then(|img| Ok(img.to_rgba8()))
-                .ok()
-        } else {
-            None
-        };
-
         MaterialDef::new(
             "simple_material",
             SIMPLE_SHADER_DEF.clone(),
-            vec![TextureDef::ImageTextureDef(data)],
+            vec![TextureDef::ImageTextureDef(load_image(reader))],
+            use_alpha,
+        )
+    }
+}
+
+/// Same as `SimpleMaterialDef` but lit by the light of the scene, so the
+/// vertices need normals.
+pub struct LitMaterialDef;
+impl LitMaterialDef {
+    pub fn create<R: Read>(reader: Option<&mut R>, use_alpha: bool) -> MaterialDef {
+        MaterialDef::new(
+            "lit_material",
+            LIT_SHADER_DEF.clone(),
+            vec![TextureDef::ImageTextureDef(load_image(reader))],
             use_alpha,
         )
     }
 }
+
+fn load_image<R: Read>(reader: Option<&mut R>) -> Option<RgbaImage> {
+    let r = reader?;
+    let mut buf = Vec::new();
+    r.read_to_end(&mut buf).unwrap();
+    image::load_from_memory(&buf)
+        .or_else(|_| image::load_from_memory_with_format(&buf, ImageFormat::Tga))
+        .and_then(|img| Ok(img.to_rgba8()))
+        .ok()
+}
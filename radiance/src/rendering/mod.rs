@@ -11,11 +11,11 @@ mod vulkan;
 
 pub use engine::RenderingEngine;
 pub use factory::ComponentFactory;
-pub use material::{Material, MaterialDef, SimpleMaterialDef};
+pub use material::{LitMaterialDef, Material, MaterialDef, SimpleMaterialDef};
 pub use platform::Window;
 pub use render_object::RenderObject;
 pub use rendering_component::RenderingComponent;
-pub use shader::{Shader, ShaderDef, SIMPLE_SHADER_DEF};
-pub use texture::{Texture, TextureDef};
+pub use shader::{Shader, ShaderDef, LIT_SHADER_DEF, SIMPLE_SHADER_DEF};
+pub use texture::{ImguiTexture, Texture, TextureDef};
 pub use vertex_buffer::{VertexBuffer, VertexComponents};
 pub use vulkan::VulkanRenderingEngine;
@@ -18,6 +18,10 @@ static SIMPLE_TRIANGLE_VERT: &'static [u8] =
     include_bytes!(concat!(env!("OUT_DIR"), "/simple_triangle.vert.spv"));
 static SIMPLE_TRIANGLE_FRAG: &'static [u8] =
     include_bytes!(concat!(env!("OUT_DIR"), "/simple_triangle.frag.spv"));
+static LIT_TEXTURE_VERT: &'static [u8] =
+    include_bytes!(concat!(env!("OUT_DIR"), "/lit_texture.vert.spv"));
+static LIT_TEXTURE_FRAG: &'static [u8] =
+    include_bytes!(concat!(env!("OUT_DIR"), "/lit_texture.frag.spv"));
 
 lazy_static! {
     pub static ref SIMPLE_SHADER_DEF: ShaderDef = ShaderDef::new(
@@ -26,6 +30,12 @@ lazy_static! {
         SIMPLE_TRIANGLE_VERT,
         SIMPLE_TRIANGLE_FRAG,
     );
+    pub static ref LIT_SHADER_DEF: ShaderDef = ShaderDef::new(
+        "lit_texture",
+        VertexComponents::POSITION | VertexComponents::NORMAL | VertexComponents::TEXCOORD,
+        LIT_TEXTURE_VERT,
+        LIT_TEXTURE_FRAG,
+    );
 }
 
 impl ShaderDef {
@@ -1,4 +1,5 @@
 use image::RgbaImage;
+use imgui::TextureId;
 
 pub trait Texture: downcast_rs::Downcast {
     fn width(&self) -> u32;
@@ -7,6 +8,33 @@ pub trait Texture: downcast_rs::Downcast {
 
 downcast_rs::impl_downcast!(Texture);
 
+/// A texture that the imgui windows can draw by its id.
+pub struct ImguiTexture {
+    texture: Box<dyn Texture>,
+    texture_id: TextureId,
+}
+
+impl ImguiTexture {
+    pub fn new(texture: Box<dyn Texture>, texture_id: TextureId) -> Self {
+        Self {
+            texture,
+            texture_id,
+        }
+    }
+
+    pub fn texture_id(&self) -> TextureId {
+        self.texture_id
+    }
+
+    pub fn width(&self) -> u32 {
+        self.texture.width()
+    }
+
+    pub fn height(&self) -> u32 {
+        self.texture.height()
+    }
+}
+
 pub enum TextureDef {
     // PathTextureDef(PathBuf),
     ImageTextureDef(Option<RgbaImage>),
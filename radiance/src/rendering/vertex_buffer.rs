@@ -146,6 +146,10 @@ impl VertexBuffer {
         self.get_component(index, VertexComponents::POSITION)
     }
 
+    pub fn normal(&self, index: usize) -> Option<&Vec3> {
+        self.get_component(index, VertexComponents::NORMAL)
+    }
+
     pub fn tex_coord(&self, index: usize) -> Option<&Vec2> {
         self.get_component(index, VertexComponents::TEXCOORD)
     }
@@ -3,6 +3,7 @@ use super::{
     descriptor_set_layout::DescriptorSetLayout, device::Device,
 };
 use crate::rendering::vulkan::material::VulkanMaterial;
+use crate::rendering::vulkan::texture::VulkanTexture;
 use crate::rendering::vulkan::uniform_buffers::PerFrameUniformBuffer;
 use ash::prelude::VkResult;
 use ash::vk;
@@ -20,6 +21,7 @@ pub struct DescriptorManager {
     per_frame_pool: vk::DescriptorPool,
     per_object_pool: vk::DescriptorPool,
     per_frame_layout: vk::DescriptorSetLayout,
+    imgui_texture_layout: vk::DescriptorSetLayout,
     per_material_layouts: Arc<Mutex<HashMap<String, vk::DescriptorSetLayout>>>,
     dub_descriptor_manager: DynamicUniformBufferDescriptorManager,
 }
@@ -31,7 +33,13 @@ impl DescriptorManager {
         let per_frame_layout = Self::create_descriptor_set_layout(
             &device,
             vk::DescriptorType::UNIFORM_BUFFER,
-            vk::ShaderStageFlags::VERTEX,
+            vk::ShaderStageFlags::VERTEX | vk::ShaderStageFlags::FRAGMENT,
+            1,
+        )?;
+        let imgui_texture_layout = Self::create_descriptor_set_layout(
+            &device,
+            vk::DescriptorType::COMBINED_IMAGE_SAMPLER,
+            vk::ShaderStageFlags::FRAGMENT,
             1,
         )?;
         let dub_descriptor_manager = DynamicUniformBufferDescriptorManager::new(device.clone());
@@ -41,6 +49,7 @@ impl DescriptorManager {
             per_frame_pool,
             per_object_pool,
             per_frame_layout,
+            imgui_texture_layout,
             per_material_layouts: Arc::new(Mutex::new(HashMap::new())),
             dub_descriptor_manager,
         })
@@ -88,6 +97,41 @@ impl DescriptorManager {
         Ok(descriptor_sets[0])
     }
 
+    pub fn allocate_imgui_texture_descriptor_set(
+        &self,
+        texture: &VulkanTexture,
+    ) -> VkResult<vk::DescriptorSet> {
+        let layouts = [self.imgui_texture_layout];
+        let create_info = vk::DescriptorSetAllocateInfo::builder()
+            .descriptor_pool(self.per_object_pool)
+            .set_layouts(&layouts)
+            .build();
+        let descriptor_sets = self.device.allocate_descriptor_sets(&create_info)?;
+
+        let image_info = [vk::DescriptorImageInfo::builder()
+            .image_layout(vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL)
+            .image_view(texture.image_view().vk_image_view())
+            .sampler(texture.sampler().vk_sampler())
+            .build()];
+        let write_descriptor_set = vk::WriteDescriptorSet::builder()
+            .descriptor_type(vk::DescriptorType::COMBINED_IMAGE_SAMPLER)
+            .dst_set(descriptor_sets[0])
+            .dst_binding(0)
+            .dst_array_element(0)
+            .image_info(&image_info)
+            .build();
+        let write_descriptor_sets = [write_descriptor_set];
+        self.device
+            .update_descriptor_sets(&write_descriptor_sets, &[]);
+
+        Ok(descriptor_sets[0])
+    }
+
+    pub fn free_imgui_texture_descriptor_set(&self, descriptor_set: vk::DescriptorSet) {
+        self.device
+            .free_descriptor_sets(self.per_object_pool, &[descriptor_set]);
+    }
+
     pub fn allocate_per_frame_descriptor_sets(
         &self,
         uniform_buffers: &[Buffer],
@@ -161,8 +205,10 @@ impl DescriptorManager {
             .ty(vk::DescriptorType::COMBINED_IMAGE_SAMPLER)
             .build();
 
+        // The imgui textures give their descriptor sets back when dropped
         let pool_sizes = [sampler_pool_size];
         let create_info = vk::DescriptorPoolCreateInfo::builder()
+            .flags(vk::DescriptorPoolCreateFlags::FREE_DESCRIPTOR_SET)
             .pool_sizes(&pool_sizes)
             .max_sets(MAX_DESCRIPTOR_SET_COUNT)
             .build();
@@ -215,6 +261,8 @@ impl Drop for DescriptorManager {
     fn drop(&mut self) {
         self.device
             .destroy_descriptor_set_layout(self.per_frame_layout);
+        self.device
+            .destroy_descriptor_set_layout(self.imgui_texture_layout);
 
         for layout in self.per_material_layouts.lock().unwrap().values() {
             self.device.destroy_descriptor_set_layout(*layout);
@@ -99,6 +99,12 @@ impl Device {
         }
     }
 
+    pub fn free_descriptor_sets(&self, pool: DescriptorPool, descriptor_sets: &[DescriptorSet]) {
+        unsafe {
+            self.device.free_descriptor_sets(pool, descriptor_sets);
+        }
+    }
+
     pub fn reset_descriptor_pool(&self, pool: DescriptorPool) -> VkResult<()> {
         unsafe {
             self.device
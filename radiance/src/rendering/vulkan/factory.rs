@@ -1,12 +1,15 @@
 use super::{
     adhoc_command_runner::AdhocCommandRunner, descriptor_managers::DescriptorManager,
-    device::Device, material::VulkanMaterial, render_object::VulkanRenderObject,
-    shader::VulkanShader, texture::VulkanTexture, uniform_buffers::DynamicUniformBufferManager,
+    device::Device, imgui::ImguiTextureStore, imgui::VulkanImguiTexture, material::VulkanMaterial,
+    render_object::VulkanRenderObject, shader::VulkanShader, texture::VulkanTexture,
+    uniform_buffers::DynamicUniformBufferManager,
 };
 use crate::rendering::{
-    factory::ComponentFactory, texture::TextureDef, Material, MaterialDef, RenderObject,
-    RenderingComponent, Shader, ShaderDef, Texture, VertexBuffer,
+    factory::ComponentFactory, texture::TextureDef, ImguiTexture, Material, MaterialDef,
+    RenderObject, RenderingComponent, Shader, ShaderDef, Texture, VertexBuffer,
 };
+use std::cell::RefCell;
+use std::error::Error;
 use std::rc::Rc;
 use std::sync::Arc;
 
@@ -16,6 +19,7 @@ pub struct VulkanComponentFactory {
     descriptor_manager: Rc<DescriptorManager>,
     dub_manager: Arc<DynamicUniformBufferManager>,
     command_runner: Rc<AdhocCommandRunner>,
+    imgui_texture_store: Rc<RefCell<ImguiTextureStore>>,
 }
 
 impl ComponentFactory for VulkanComponentFactory {
@@ -31,6 +35,23 @@ impl ComponentFactory for VulkanComponentFactory {
         )
     }
 
+    fn create_imgui_texture(
+        &self,
+        texture_def: &TextureDef,
+    ) -> Result<ImguiTexture, Box<dyn Error>> {
+        let texture = VulkanTexture::new(
+            texture_def,
+            &self.device,
+            &self.allocator,
+            &self.command_runner,
+        )?;
+        let texture =
+            VulkanImguiTexture::new(texture, &self.descriptor_manager, &self.imgui_texture_store)?;
+        let texture_id = texture.texture_id();
+
+        Ok(ImguiTexture::new(Box::new(texture), texture_id))
+    }
+
     fn create_shader(&self, shader_def: &ShaderDef) -> Box<dyn Shader> {
         Box::new(VulkanShader::new(shader_def, self.device.clone()).unwrap())
     }
@@ -87,6 +108,7 @@ impl VulkanComponentFactory {
         descriptor_manager: &Rc<DescriptorManager>,
         dub_manager: &Arc<DynamicUniformBufferManager>,
         command_runner: &Rc<AdhocCommandRunner>,
+        imgui_texture_store: &Rc<RefCell<ImguiTextureStore>>,
     ) -> Self {
         Self {
             device,
@@ -94,6 +116,7 @@ impl VulkanComponentFactory {
             descriptor_manager: descriptor_manager.clone(),
             dub_manager: dub_manager.clone(),
             command_runner: command_runner.clone(),
+            imgui_texture_store: imgui_texture_store.clone(),
         }
     }
 
@@ -1,13 +1,106 @@
-use super::{device::Device, instance::Instance};
+use super::{
+    descriptor_managers::DescriptorManager, device::Device, instance::Instance,
+    texture::VulkanTexture,
+};
 use crate::imgui::{ImguiContext, ImguiFrame};
+use crate::rendering::Texture;
+use ash::prelude::VkResult;
 use ash::vk;
 use imgui::*;
 use imgui_rs_vulkan_renderer::*;
-use std::rc::Rc;
+use std::collections::{HashMap, HashSet};
+use std::{cell::RefCell, rc::Rc};
+
+/// The textures that the imgui windows can draw. The ids stay the same when
+/// the swapchain and its imgui renderer are recreated.
+pub struct ImguiTextureStore {
+    descriptor_sets: HashMap<usize, vk::DescriptorSet>,
+    next_id: usize,
+}
+
+impl Default for ImguiTextureStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ImguiTextureStore {
+    pub fn new() -> Self {
+        Self {
+            descriptor_sets: HashMap::new(),
+            // The imgui renderer keeps the id 0 for the font atlas
+            next_id: 1,
+        }
+    }
+
+    pub fn add(&mut self, descriptor_set: vk::DescriptorSet) -> TextureId {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.descriptor_sets.insert(id, descriptor_set);
+        TextureId::from(id)
+    }
+
+    pub fn remove(&mut self, texture_id: TextureId) {
+        self.descriptor_sets.remove(&texture_id.id());
+    }
+}
+
+/// A texture with its own imgui descriptor set. Dropping it takes it out of
+/// the store and frees the descriptor set.
+pub struct VulkanImguiTexture {
+    texture: VulkanTexture,
+    descriptor_set: vk::DescriptorSet,
+    texture_id: TextureId,
+    descriptor_manager: Rc<DescriptorManager>,
+    texture_store: Rc<RefCell<ImguiTextureStore>>,
+}
+
+impl Texture for VulkanImguiTexture {
+    fn width(&self) -> u32 {
+        self.texture.width()
+    }
+
+    fn height(&self) -> u32 {
+        self.texture.height()
+    }
+}
+
+impl VulkanImguiTexture {
+    pub fn new(
+        texture: VulkanTexture,
+        descriptor_manager: &Rc<DescriptorManager>,
+        texture_store: &Rc<RefCell<ImguiTextureStore>>,
+    ) -> VkResult<Self> {
+        let descriptor_set = descriptor_manager.allocate_imgui_texture_descriptor_set(&texture)?;
+        let texture_id = texture_store.borrow_mut().add(descriptor_set);
+
+        Ok(Self {
+            texture,
+            descriptor_set,
+            texture_id,
+            descriptor_manager: descriptor_manager.clone(),
+            texture_store: texture_store.clone(),
+        })
+    }
+
+    pub fn texture_id(&self) -> TextureId {
+        self.texture_id
+    }
+}
+
+impl Drop for VulkanImguiTexture {
+    fn drop(&mut self) {
+        self.texture_store.borrow_mut().remove(self.texture_id);
+        self.descriptor_manager
+            .free_imgui_texture_descriptor_set(self.descriptor_set);
+    }
+}
 
 pub struct ImguiVulkanContext {
     renderer: Renderer,
     vk_context: VkContext,
+    texture_store: Rc<RefCell<ImguiTextureStore>>,
+    registered_texture_ids: HashSet<usize>,
 }
 
 impl ImguiVulkanContext {
@@ -20,6 +113,7 @@ impl ImguiVulkanContext {
         render_pass: vk::RenderPass,
         in_flight_frames: usize,
         context: &mut ImguiContext,
+        texture_store: Rc<RefCell<ImguiTextureStore>>,
     ) -> Self {
         let vk_context = VkContext {
             instance,
@@ -40,6 +134,8 @@ impl ImguiVulkanContext {
         Self {
             renderer,
             vk_context,
+            texture_store,
+            registered_texture_ids: HashSet::new(),
         }
     }
 
@@ -53,12 +149,35 @@ impl ImguiVulkanContext {
             &*(sys::igGetDrawData() as *mut DrawData)
         };
 
+        self.register_textures();
         if draw_data.total_idx_count > 0 {
             self.renderer
                 .cmd_draw(&self.vk_context, command_buffer, draw_data)
                 .unwrap();
         }
     }
+
+    // Hands the textures added since the last frame to the renderer and
+    // takes the dropped ones away
+    fn register_textures(&mut self) {
+        let texture_store = self.texture_store.borrow();
+        let descriptor_sets = &texture_store.descriptor_sets;
+        let textures = self.renderer.textures();
+        self.registered_texture_ids.retain(|id| {
+            let alive = descriptor_sets.contains_key(id);
+            if !alive {
+                textures.remove(TextureId::from(*id));
+            }
+
+            alive
+        });
+
+        for (id, descriptor_set) in descriptor_sets {
+            if self.registered_texture_ids.insert(*id) {
+                textures.replace(TextureId::from(*id), *descriptor_set);
+            }
+        }
+    }
 }
 
 impl Drop for ImguiVulkanContext {
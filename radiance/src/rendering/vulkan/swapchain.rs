@@ -12,12 +12,12 @@ use super::{
 };
 use crate::{
     imgui::{ImguiContext, ImguiFrame},
-    rendering::vulkan::imgui::ImguiVulkanContext,
+    rendering::vulkan::imgui::{ImguiTextureStore, ImguiVulkanContext},
 };
 use ash::prelude::VkResult;
 use ash::vk;
 use std::cmp::Ordering;
-use std::rc::Rc;
+use std::{cell::RefCell, rc::Rc};
 
 pub struct SwapChain {
     device: Rc<Device>,
@@ -53,6 +53,7 @@ impl SwapChain {
         descriptor_manager: &Rc<DescriptorManager>,
         command_runner: &Rc<AdhocCommandRunner>,
         gui_context: &mut ImguiContext,
+        imgui_texture_store: &Rc<RefCell<ImguiTextureStore>>,
     ) -> Result<Self, Box<dyn std::error::Error>> {
 
         // Make it at least 1x1 pixel for images
@@ -140,6 +141,7 @@ impl SwapChain {
             pipeline_manager.render_pass().vk_render_pass(),
             images.len(),
             gui_context,
+            imgui_texture_store.clone(),
         );
 
         Ok(Self {
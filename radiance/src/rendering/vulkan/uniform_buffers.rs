@@ -1,6 +1,7 @@
 use super::buffer::{Buffer, BufferType};
-use crate::math::Mat44;
+use crate::math::{Mat44, Vec3};
 use crate::rendering::vulkan::descriptor_managers::DynamicUniformBufferDescriptorManager;
+use crate::scene::Light;
 use ash::vk;
 use std::{rc::Rc, sync::Mutex};
 
@@ -99,17 +100,30 @@ impl DynamicUniformBufferManager {
     }
 }
 
+// The vec3 members are padded to 16 bytes to follow the std140 layout
 #[repr(C)]
 pub struct PerFrameUniformBuffer {
     view: Mat44,
     projection: Mat44,
+    light_direction: Vec3,
+    _padding0: f32,
+    light_color: Vec3,
+    _padding1: f32,
+    ambient_color: Vec3,
+    _padding2: f32,
 }
 
 impl PerFrameUniformBuffer {
-    pub fn new(view: &Mat44, projection: &Mat44) -> Self {
+    pub fn new(view: &Mat44, projection: &Mat44, light: &Light) -> Self {
         Self {
             view: *view,
             projection: *projection,
+            light_direction: *light.direction(),
+            _padding0: 0.,
+            light_color: *light.color(),
+            _padding1: 0.,
+            ambient_color: *light.ambient(),
+            _padding2: 0.,
         }
     }
 }
@@ -1,5 +1,6 @@
 use super::descriptor_managers::DescriptorManager;
 use super::helpers;
+use super::imgui::ImguiTextureStore;
 use super::render_object::VulkanRenderObject;
 use super::swapchain::SwapChain;
 use super::{adhoc_command_runner::AdhocCommandRunner, device::Device};
@@ -47,6 +48,7 @@ pub struct VulkanRenderingEngine {
     render_finished_semaphore: vk::Semaphore,
 
     imgui_context: Rc<RefCell<ImguiContext>>,
+    imgui_texture_store: Rc<RefCell<ImguiTextureStore>>,
 }
 
 impl RenderingEngine for VulkanRenderingEngine {
@@ -159,6 +161,7 @@ impl VulkanRenderingEngine {
 
         let adhoc_command_runner =
             Rc::new(AdhocCommandRunner::new(device.clone(), command_pool, queue));
+        let imgui_texture_store = Rc::new(RefCell::new(ImguiTextureStore::new()));
         let swapchain = SwapChain::new(
             &instance,
             device.clone(),
@@ -173,6 +176,7 @@ impl VulkanRenderingEngine {
             &descriptor_manager,
             &adhoc_command_runner,
             &mut imgui_context.borrow_mut(),
+            &imgui_texture_store,
         )
         .unwrap();
 
@@ -186,6 +190,7 @@ impl VulkanRenderingEngine {
             &descriptor_manager,
             &dub_manager,
             &adhoc_command_runner,
+            &imgui_texture_store,
         ));
 
         // DEBUG INFO
@@ -223,6 +228,7 @@ impl VulkanRenderingEngine {
             image_available_semaphore,
             render_finished_semaphore,
             imgui_context,
+            imgui_texture_store,
         };
 
         return Ok(vulkan);
@@ -279,6 +285,7 @@ impl VulkanRenderingEngine {
             self.descriptor_manager(),
             &self.adhoc_command_runner,
             &mut self.imgui_context.borrow_mut(),
+            &self.imgui_texture_store,
         )?);
 
         Ok(())
@@ -326,7 +333,7 @@ impl VulkanRenderingEngine {
                 let camera = scene.camera();
                 let view = Mat44::inversed(camera.transform().matrix());
                 let proj = camera.projection_matrix();
-                PerFrameUniformBuffer::new(&view, proj)
+                PerFrameUniformBuffer::new(&view, proj, scene.light())
             };
 
             swapchain!().update_ubo(image_index as usize, &[ubo]);
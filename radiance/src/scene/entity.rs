@@ -156,6 +156,10 @@ impl<TExtension: EntityExtension + 'static> Entity for CoreEntity<TExtension> {
 
     fn update(&mut self, delta_sec: f32) {
         self.on_updating(delta_sec);
+
+        for e in &mut self.children {
+            e.update(delta_sec);
+        }
     }
 
     fn transform(&self) -> &Transform {
@@ -232,3 +236,51 @@ impl<TExtension: EntityExtension + 'static> Entity for CoreEntity<TExtension> {
         self.children.iter().map(|e| e.as_ref()).collect()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{CoreEntity, EntityExtension};
+    use crate::scene::{DefaultScene, Scene};
+    use std::{cell::Cell, rc::Rc};
+
+    struct UpdateCounter {
+        updates: Rc<Cell<u32>>,
+    }
+
+    impl EntityExtension for UpdateCounter {
+        fn on_updating(self: &mut CoreEntity<Self>, _delta_sec: f32) {
+            self.updates.set(self.updates.get() + 1);
+        }
+    }
+
+    fn counted_entity(name: &str) -> (CoreEntity<UpdateCounter>, Rc<Cell<u32>>) {
+        let updates = Rc::new(Cell::new(0));
+        let entity = CoreEntity::new(
+            UpdateCounter {
+                updates: updates.clone(),
+            },
+            name.to_string(),
+            true,
+        );
+
+        (entity, updates)
+    }
+
+    #[test]
+    fn every_entity_is_updated_once_per_frame() {
+        let (mut root, root_updates) = counted_entity("root");
+        let (mut child, child_updates) = counted_entity("child");
+        let (grandchild, grandchild_updates) = counted_entity("grandchild");
+        child.attach(Box::new(grandchild));
+        root.attach(Box::new(child));
+
+        let mut scene = DefaultScene::create();
+        scene.add_entity(Box::new(root));
+        for frame in 1..=3 {
+            scene.update(0.1);
+            assert_eq!(root_updates.get(), frame);
+            assert_eq!(child_updates.get(), frame);
+            assert_eq!(grandchild_updates.get(), frame);
+        }
+    }
+}
@@ -0,0 +1,55 @@
+use crate::math::Vec3;
+
+/// A directional light together with the ambient light of a scene. It's
+/// only used by the lit materials.
+#[derive(Copy, Clone, Debug)]
+pub struct Light {
+    direction: Vec3,
+    color: Vec3,
+    ambient: Vec3,
+}
+
+impl Light {
+    pub fn new(direction: Vec3, color: Vec3, ambient: Vec3) -> Self {
+        Self {
+            direction: Vec3::normalized(&direction),
+            color,
+            ambient,
+        }
+    }
+
+    /// The direction the light travels along, in world space.
+    pub fn direction(&self) -> &Vec3 {
+        &self.direction
+    }
+
+    pub fn set_direction(&mut self, direction: &Vec3) {
+        self.direction = Vec3::normalized(direction);
+    }
+
+    pub fn color(&self) -> &Vec3 {
+        &self.color
+    }
+
+    pub fn set_color(&mut self, color: &Vec3) {
+        self.color = *color;
+    }
+
+    pub fn ambient(&self) -> &Vec3 {
+        &self.ambient
+    }
+
+    pub fn set_ambient(&mut self, ambient: &Vec3) {
+        self.ambient = *ambient;
+    }
+}
+
+impl Default for Light {
+    fn default() -> Self {
+        Self::new(
+            Vec3::new(-0.3, -1., -0.4),
+            Vec3::new(0.5, 0.5, 0.5),
+            Vec3::new(0.6, 0.6, 0.6),
+        )
+    }
+}
@@ -1,11 +1,15 @@
 mod camera;
 mod director;
 mod entity;
+mod light;
 mod scene;
 mod scene_manager;
+mod screen_fade;
 
 pub use camera::Camera;
 pub use director::Director;
 pub use entity::{entity_add_component, entity_get_component, CoreEntity, Entity, EntityExtension};
+pub use light::Light;
 pub use scene::{CoreScene, DefaultScene, Scene, SceneExtension};
 pub use scene_manager::{DefaultSceneManager, SceneManager};
+pub use screen_fade::ScreenFade;
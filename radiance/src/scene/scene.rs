@@ -1,6 +1,6 @@
 use crate::math::Transform;
 
-use super::{entity::Entity, Camera};
+use super::{entity::Entity, Camera, Light};
 use std::ops::{Deref, DerefMut};
 
 pub trait Scene: downcast_rs::Downcast {
@@ -14,6 +14,8 @@ pub trait Scene: downcast_rs::Downcast {
     fn root_entities_mut(&mut self) -> &mut Vec<Box<dyn Entity>>;
     fn camera(&self) -> &Camera;
     fn camera_mut(&mut self) -> &mut Camera;
+    fn light(&self) -> &Light;
+    fn light_mut(&mut self) -> &mut Light;
 }
 
 downcast_rs::impl_downcast!(Scene);
@@ -54,6 +56,7 @@ pub struct CoreScene<TExtension: SceneExtension> {
     entities: Vec<Box<dyn Entity>>,
     extension: TExtension,
     camera: Camera,
+    light: Light,
 }
 
 impl<TExtension: SceneExtension> CoreScene<TExtension> {
@@ -62,6 +65,7 @@ impl<TExtension: SceneExtension> CoreScene<TExtension> {
             entities: vec![],
             extension: ext_calls,
             camera: Camera::new(),
+            light: Light::default(),
         }
     }
 
@@ -169,4 +173,12 @@ impl<TExtension: 'static + SceneExtension> Scene for CoreScene<TExtension> {
     fn camera_mut(&mut self) -> &mut Camera {
         &mut self.camera
     }
+
+    fn light(&self) -> &Light {
+        &self.light
+    }
+
+    fn light_mut(&mut self) -> &mut Light {
+        &mut self.light
+    }
 }
@@ -1,10 +1,12 @@
-use super::{Director, Scene};
+use super::{Director, Scene, ScreenFade};
 use imgui::Ui;
 use std::{cell::RefCell, rc::Rc};
 
 pub trait SceneManager {
     fn update(&mut self, ui: &mut Ui, delta_sec: f32);
     fn scene_mut(&mut self) -> Option<&mut dyn Scene>;
+    fn screen_fade(&self) -> &ScreenFade;
+    fn screen_fade_mut(&mut self) -> &mut ScreenFade;
 
     fn set_view_extent(&mut self, extent: (u32, u32));
     fn set_director(&mut self, director: Rc<RefCell<dyn Director>>);
@@ -18,6 +20,7 @@ pub struct DefaultSceneManager {
     director: Option<Rc<RefCell<dyn Director>>>,
     scenes: Vec<Box<dyn Scene>>,
     view_extent: (u32, u32),
+    screen_fade: ScreenFade,
 }
 
 impl DefaultSceneManager {
@@ -26,6 +29,7 @@ impl DefaultSceneManager {
             director: None,
             scenes: vec![],
             view_extent: (1024, 768),
+            screen_fade: ScreenFade::new(),
         }
     }
 }
@@ -50,12 +54,23 @@ impl SceneManager for DefaultSceneManager {
         if let Some(s) = scene!(self) {
             s.update(delta_sec);
         }
+
+        self.screen_fade.update(delta_sec);
+        self.screen_fade.draw(ui);
     }
 
     fn scene_mut(&mut self) -> Option<&mut dyn Scene> {
         scene!(self)
     }
 
+    fn screen_fade(&self) -> &ScreenFade {
+        &self.screen_fade
+    }
+
+    fn screen_fade_mut(&mut self) -> &mut ScreenFade {
+        &mut self.screen_fade
+    }
+
     fn set_view_extent(&mut self, extent: (u32, u32)) {
         self.view_extent = extent;
     }
@@ -0,0 +1,95 @@
+use crate::math::Vec3;
+use imgui::Ui;
+
+/// A full screen overlay of a single colour that fades the scene in and
+/// out. It's drawn over the scene but under the imgui windows.
+pub struct ScreenFade {
+    color: Vec3,
+    alpha: f32,
+    from_alpha: f32,
+    to_alpha: f32,
+    duration: f32,
+    elapsed: f32,
+}
+
+impl Default for ScreenFade {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ScreenFade {
+    pub fn new() -> Self {
+        Self {
+            color: Vec3::new(0., 0., 0.),
+            alpha: 0.,
+            from_alpha: 0.,
+            to_alpha: 0.,
+            duration: 0.,
+            elapsed: 0.,
+        }
+    }
+
+    /// Covers the screen with `color` in `duration_sec` seconds.
+    pub fn fade_out(&mut self, color: &Vec3, duration_sec: f32) {
+        self.color = *color;
+        self.start(1., duration_sec);
+    }
+
+    /// Uncovers the screen in `duration_sec` seconds.
+    pub fn fade_in(&mut self, duration_sec: f32) {
+        self.start(0., duration_sec);
+    }
+
+    pub fn is_fading(&self) -> bool {
+        self.elapsed < self.duration
+    }
+
+    /// Whether the screen is fully covered.
+    pub fn is_covered(&self) -> bool {
+        !self.is_fading() && self.alpha >= 1.
+    }
+
+    pub fn color(&self) -> &Vec3 {
+        &self.color
+    }
+
+    pub fn alpha(&self) -> f32 {
+        self.alpha
+    }
+
+    pub fn update(&mut self, delta_sec: f32) {
+        if !self.is_fading() {
+            return;
+        }
+
+        self.elapsed = (self.elapsed + delta_sec).min(self.duration);
+        let t = self.elapsed / self.duration;
+        self.alpha = self.from_alpha + (self.to_alpha - self.from_alpha) * t;
+    }
+
+    pub fn draw(&self, ui: &Ui) {
+        if self.alpha <= 0. {
+            return;
+        }
+
+        ui.get_background_draw_list()
+            .add_rect(
+                [0., 0.],
+                ui.io().display_size,
+                [self.color.x, self.color.y, self.color.z, self.alpha],
+            )
+            .filled(true)
+            .build();
+    }
+
+    fn start(&mut self, to_alpha: f32, duration_sec: f32) {
+        self.from_alpha = self.alpha;
+        self.to_alpha = to_alpha;
+        self.elapsed = 0.;
+        self.duration = duration_sec.max(0.);
+        if self.duration == 0. {
+            self.alpha = to_alpha;
+        }
+    }
+}
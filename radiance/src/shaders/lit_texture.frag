@@ -0,0 +1,28 @@
+#version 450
+#extension GL_ARB_separate_shader_objects : enable
+
+layout(set = 0, binding = 0) uniform PerFrameUbo {
+    mat4 view;
+    mat4 proj;
+    vec3 lightDirection;
+    vec3 lightColor;
+    vec3 ambientColor;
+} perFrameUbo;
+
+layout(set = 2, binding = 0) uniform sampler2D texSampler;
+
+layout(location = 0) in vec2 fragTexCoord;
+layout(location = 1) in vec3 fragNormal;
+
+layout(location = 0) out vec4 outColor;
+
+void main() {
+    vec4 color = texture(texSampler, fragTexCoord);
+    if (color.a == 0.0) {
+        discard;
+    }
+
+    float diffuse = max(dot(normalize(fragNormal), -perFrameUbo.lightDirection), 0.0);
+    vec3 light = perFrameUbo.ambientColor + perFrameUbo.lightColor * diffuse;
+    outColor = vec4(color.rgb * light, color.a);
+}
\ No newline at end of file
@@ -0,0 +1,33 @@
+#version 450
+#extension GL_ARB_separate_shader_objects : enable
+
+layout(set = 0, binding = 0) uniform PerFrameUbo {
+    mat4 view;
+    mat4 proj;
+    vec3 lightDirection;
+    vec3 lightColor;
+    vec3 ambientColor;
+} perFrameUbo;
+
+layout(set = 1, binding = 0) uniform PerInstanceUbo {
+    mat4 model;
+} perInstanceUbo;
+
+layout(location = 0) in vec3 position;
+layout(location = 1) in vec3 normal;
+layout(location = 2) in vec2 inTexCoord;
+
+layout(location = 0) out vec2 fragTexCoord;
+layout(location = 1) out vec3 fragNormal;
+
+mat4 clip = mat4(vec4(1.0, 0.0, 0.0, 0.0),
+                 vec4(0.0, -1.0, 0.0, 0.0),
+                 vec4(0.0, 0.0, 0.5, 0.5),
+                 vec4(0.0, 0.0, 0, 1.0));
+
+void main() {
+    gl_Position = vec4(position, 1.0) * perInstanceUbo.model * perFrameUbo.view * perFrameUbo.proj * clip;
+
+    fragTexCoord = inTexCoord;
+    fragNormal = (vec4(normal, 0.0) * perInstanceUbo.model).xyz;
+}
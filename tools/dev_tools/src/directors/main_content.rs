@@ -4,9 +4,13 @@ use imgui::{im_str, TabBar, TabBarFlags, TabItem, TabItemFlags, Ui};
 use mini_fs::MiniFs;
 use opengb::{
     loaders::{
-        cvd_loader::cvd_load_from_file, mv3_loader::mv3_load_from_file,
-        pol_loader::pol_load_from_file, sce_loader::sce_load_from_file,
-        scn_loader::scn_load_from_file,
+        ani_loader::ani_load_from_file,
+        cvd_loader::cvd_load_from_file,
+        mv3_loader::mv3_load_from_file,
+        nav_loader::nav_load_from_file,
+        pol_loader::pol_load_from_file,
+        sce_loader::sce_load_from_file,
+        scn_loader::{scn_load_from_file, scn_to_json},
     },
     utilities::StoreExt2,
 };
@@ -47,9 +51,14 @@ impl ContentTabs {
             Some("scn") => self.open_scn(vfs, path),
             Some("sce") => self.open_json_from(
                 path.as_ref(),
-                || Some(sce_load_from_file(vfs, path.as_ref())),
+                || sce_load_from_file(vfs, path.as_ref()).ok(),
                 true,
             ),
+            Some("nav") => self.open_json_from(
+                path.as_ref(),
+                || nav_load_from_file(vfs, path.as_ref()).ok(),
+                false,
+            ),
             Some("mv3") => self.open_json_from(
                 path.as_ref(),
                 || mv3_load_from_file(vfs, path.as_ref()).ok(),
@@ -60,6 +69,11 @@ impl ContentTabs {
                 || cvd_load_from_file(vfs, path.as_ref()).ok(),
                 true,
             ),
+            Some("ani") => self.open_json_from(
+                path.as_ref(),
+                || ani_load_from_file(vfs, path.as_ref()).ok(),
+                false,
+            ),
             Some("pol") => self.open_json_from(
                 path.as_ref(),
                 || pol_load_from_file(vfs, path.as_ref()).ok(),
@@ -97,8 +111,12 @@ impl ContentTabs {
 
         let tab_name = path.as_ref().to_string_lossy().to_string();
         self.show_or_add_tab(tab_name, || {
-            let content = serde_json::to_string_pretty(&scn_file)
-                .unwrap_or("Cannot serialize as Json".to_string());
+            let content = match scn_file {
+                Ok(scn_file) => {
+                    scn_to_json(&scn_file).unwrap_or("Cannot serialize as Json".to_string())
+                }
+                Err(e) => e.to_string(),
+            };
             Box::new(TextPane::new(content, path.as_ref().to_owned(), None))
         });
     }
@@ -47,7 +47,7 @@ impl Director for PreviewDirector {
                     RoleAnimation::new(
                         &self.asset_mgr.component_factory(),
                         f,
-                        self.asset_mgr.load_mv3_material(f, &self.path),
+                        self.asset_mgr.load_mv3_materials(f, &self.path),
                         RoleAnimationRepeatMode::NoRepeat,
                     )
                 });
@@ -68,22 +68,27 @@ impl Director for PreviewDirector {
                 })
                 .ok()
             }
-            Some("pol") => Some(Box::new(CoreEntity::new(
-                PolModelEntity::new(
-                    &self.asset_mgr.component_factory(),
-                    &self.asset_mgr.vfs(),
-                    &self.path,
-                ),
-                "preview".to_string(),
-                true
-            )) as Box<dyn Entity>),
-            Some("cvd") => Some(Box::new(CvdModelEntity::create(
+            Some("pol") => PolModelEntity::new(
+                &self.asset_mgr.component_factory(),
+                &self.asset_mgr.vfs(),
+                &self.path,
+            )
+            .map(|p| {
+                Box::new(CoreEntity::new(p, "preview".to_string(), true)) as Box<dyn Entity>
+            })
+            .ok(),
+            Some("cvd") => CvdModelEntity::create(
                 self.asset_mgr.component_factory().clone(),
                 &self.asset_mgr.vfs(),
                 &self.path,
                 "preview".to_string(),
-                true
-            )) as Box<dyn Entity>),
+                true,
+            )
+            .map(|mut c| {
+                c.play(true);
+                Box::new(c) as Box<dyn Entity>
+            })
+            .ok(),
             _ => None,
         };
 
@@ -18,7 +18,8 @@ impl ApplicationExtension<ApplicationCallbacks> for ApplicationCallbacks {
     fn on_initialized(&mut self, app: &mut Application<ApplicationCallbacks>) {
         let factory = app.engine_mut().rendering_component_factory();
 
-        let asset_mgr = AssetManager::new(factory, &self.config.asset_path);
+        let asset_mgr = AssetManager::new(factory, &self.config.asset_path)
+            .unwrap_or_else(|e| panic!("Cannot open {}: {}", &self.config.asset_path, e));
         let input_engine = app.engine_mut().input_engine();
         let audio_engine = app.engine_mut().audio_engine();
 
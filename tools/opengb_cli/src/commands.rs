@@ -0,0 +1,292 @@
+use mini_fs::{Entry, EntryKind, MiniFs, StoreExt};
+use opengb::{
+    exporters::{
+        encode_png,
+        mv3_exporter::mv3_export_gltf_from_file,
+        pol_exporter::{pol_export_gltf_from_file, pol_export_obj, pol_png_file_name},
+    },
+    loaders::{
+        ani_loader::ani_load_from_file,
+        cvd_loader::cvd_load_from_file,
+        mv3_loader::mv3_load_from_file,
+        nav_loader::nav_load_from_file,
+        pol_loader::{pol_load_from_file, pol_texture_path},
+        sce_loader::{sce_load_from_file, sce_write},
+        scn_loader::{scn_load_from_file, scn_to_json},
+    },
+    sce::{sce_assemble, sce_disassemble},
+    utilities::StoreExt2,
+};
+use serde::Serialize;
+use std::{
+    cmp::Ordering,
+    error::Error,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+pub fn ls(vfs: &MiniFs, path: &str, recursive: bool) -> Result<(), Box<dyn Error>> {
+    ls_internal(vfs, Path::new(path), recursive)
+}
+
+pub fn cat(vfs: &MiniFs, path: &str) -> Result<(), Box<dyn Error>> {
+    let data = vfs.read_to_end(path)?;
+    let stdout = io::stdout();
+    let mut handle = stdout.lock();
+    handle.write_all(&data)?;
+    handle.flush()?;
+    Ok(())
+}
+
+pub fn extract(vfs: &MiniFs, path: &str, output: &str) -> Result<(), Box<dyn Error>> {
+    let path = Path::new(path);
+    let output = PathBuf::from(output);
+    match entry_kind(vfs, path)? {
+        EntryKind::Dir => extract_dir(vfs, path, &output),
+        EntryKind::File => {
+            let output = if output.is_dir() {
+                output.join(path.file_name().unwrap_or_default())
+            } else {
+                output
+            };
+
+            extract_file(vfs, path, &output)
+        }
+    }
+}
+
+pub fn info(vfs: &MiniFs, path: &str) -> Result<(), Box<dyn Error>> {
+    let path = Path::new(path);
+    if entry_kind(vfs, path)? == EntryKind::Dir {
+        let entries = get_entries(vfs, path)?;
+        let dir_count = entries.iter().filter(|e| e.kind == EntryKind::Dir).count();
+        println!("Path: {}", path.display());
+        println!("Type: folder");
+        println!("Folders: {}", dir_count);
+        println!("Files: {}", entries.len() - dir_count);
+        return Ok(());
+    }
+
+    let size = vfs.read_to_end(path)?.len();
+    println!("Path: {}", path.display());
+    println!("Type: file");
+    println!("Size: {}", size);
+
+    match extension(path).as_ref().map(|e| e.as_str()) {
+        Some("scn") => {
+            let scn_file = scn_load_from_file(vfs, path)?;
+            println!("Cpk name: {}", scn_file.cpk_name);
+            println!("Scn name: {}", scn_file.scn_name);
+            println!("Scn base name: {}", scn_file.scn_base_name);
+            println!("Roles: {}", scn_file.roles.len());
+            println!("Nodes: {}", scn_file.nodes.len());
+        }
+        Some("sce") => {
+            let sce_file = sce_load_from_file(vfs, path)?;
+            println!("Procs: {}", sce_file.proc_num);
+            for header in &sce_file.proc_headers {
+                println!("    {:>6} {}", header.id, header.name);
+            }
+        }
+        Some("nav") => {
+            let nav_file = nav_load_from_file(vfs, path)?;
+            println!("Version: {}", nav_file.version);
+            println!("Maps: {}", nav_file.maps.len());
+            for map in &nav_file.maps {
+                println!("    {} x {}", map.width, map.height);
+            }
+        }
+        Some("mv3") => {
+            let mv3_file = mv3_load_from_file(vfs, path)?;
+            println!("Textures: {}", mv3_file.texture_count);
+            println!("Actions: {}", mv3_file.action_count);
+            println!("Models: {}", mv3_file.model_count);
+            for model in &mv3_file.models {
+                println!(
+                    "    {} frames, {} vertices per frame, {} meshes",
+                    model.frame_count, model.vertex_per_frame, model.mesh_count
+                );
+            }
+        }
+        Some("pol") => {
+            let pol_file = pol_load_from_file(vfs, path)?;
+            println!("Meshes: {}", pol_file.mesh_count);
+            for mesh in &pol_file.meshes {
+                println!(
+                    "    {} vertices, {} materials",
+                    mesh.vertex_count, mesh.material_info_count
+                );
+            }
+        }
+        Some("cvd") => {
+            let cvd_file = cvd_load_from_file(vfs, path)?;
+            println!("Models: {}", cvd_file.model_count);
+        }
+        Some("ani") => {
+            let ani_file = ani_load_from_file(vfs, path)?;
+            println!("Ani size: {}", ani_file.size);
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+pub fn dump_json(vfs: &MiniFs, path: &str) -> Result<(), Box<dyn Error>> {
+    let path = Path::new(path);
+    let content = match extension(path).as_ref().map(|e| e.as_str()) {
+        Some("scn") => scn_to_json(&scn_load_from_file(vfs, path)?)?,
+        Some("sce") => to_json(&sce_load_from_file(vfs, path)?)?,
+        Some("nav") => to_json(&nav_load_from_file(vfs, path)?)?,
+        Some("mv3") => to_json(&mv3_load_from_file(vfs, path)?)?,
+        Some("cvd") => to_json(&cvd_load_from_file(vfs, path)?)?,
+        Some("pol") => to_json(&pol_load_from_file(vfs, path)?)?,
+        Some("ani") => to_json(&ani_load_from_file(vfs, path)?)?,
+        _ => return Err(format!("Unsupported file type: {}", path.display()).into()),
+    };
+
+    println!("{}", content);
+    Ok(())
+}
+
+pub fn disasm(vfs: &MiniFs, path: &str) -> Result<(), Box<dyn Error>> {
+    let sce_file = sce_load_from_file(vfs, path)?;
+    print!("{}", sce_disassemble(&sce_file));
+    Ok(())
+}
+
+pub fn asm(listing: &str, output: &str) -> Result<(), Box<dyn Error>> {
+    let sce_file = sce_assemble(&std::fs::read_to_string(listing)?)?;
+    let mut writer = io::BufWriter::new(std::fs::File::create(output)?);
+    sce_write(&sce_file, &mut writer)?;
+    writer.flush()?;
+    Ok(())
+}
+
+pub fn gltf(vfs: &MiniFs, path: &str, output: &str) -> Result<(), Box<dyn Error>> {
+    let path = Path::new(path);
+    let mut writer = io::BufWriter::new(std::fs::File::create(output)?);
+    match extension(path).as_ref().map(|e| e.as_str()) {
+        Some("mv3") => mv3_export_gltf_from_file(vfs, path, &mut writer)?,
+        Some("pol") => pol_export_gltf_from_file(vfs, path, &mut writer)?,
+        _ => return Err(format!("Unsupported file type: {}", path.display()).into()),
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+pub fn obj(vfs: &MiniFs, path: &str, output: &str) -> Result<(), Box<dyn Error>> {
+    let path = Path::new(path);
+    let output = PathBuf::from(output);
+    let mtl_output = output.with_extension("mtl");
+    let mtl_file_name = mtl_output
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    let pol_file = pol_load_from_file(vfs, path)?;
+    let mut obj_writer = io::BufWriter::new(std::fs::File::create(&output)?);
+    let mut mtl_writer = io::BufWriter::new(std::fs::File::create(&mtl_output)?);
+    pol_export_obj(&pol_file, &mtl_file_name, &mut obj_writer, &mut mtl_writer)?;
+    obj_writer.flush()?;
+    mtl_writer.flush()?;
+
+    let mut texture_names: Vec<&String> = pol_file
+        .meshes
+        .iter()
+        .flat_map(|m| m.material_info.iter())
+        .flat_map(|m| m.texture_names.iter())
+        .collect();
+    texture_names.sort();
+    texture_names.dedup();
+
+    let output_dir = output.parent().unwrap_or(Path::new(""));
+    for name in texture_names {
+        let texture_path = pol_texture_path(vfs, path, name);
+        match read_png(vfs, &texture_path) {
+            Ok(png) => std::fs::write(output_dir.join(pol_png_file_name(name)), png)?,
+            Err(e) => eprintln!("{}: {}", texture_path.display(), e),
+        }
+    }
+
+    Ok(())
+}
+
+fn read_png(vfs: &MiniFs, path: &Path) -> Result<Vec<u8>, Box<dyn Error>> {
+    Ok(encode_png(&vfs.read_to_end(path)?)?)
+}
+
+fn to_json<O: Serialize>(obj: &O) -> Result<String, Box<dyn Error>> {
+    Ok(serde_json::to_string_pretty(obj)?)
+}
+
+fn ls_internal(vfs: &MiniFs, path: &Path, recursive: bool) -> Result<(), Box<dyn Error>> {
+    for e in get_entries(vfs, path)? {
+        let e_path = path.join(&e.name);
+        match e.kind {
+            EntryKind::Dir => {
+                println!("{}/", e_path.display());
+                if recursive {
+                    ls_internal(vfs, &e_path, recursive)?;
+                }
+            }
+            EntryKind::File => println!("{}", e_path.display()),
+        }
+    }
+
+    Ok(())
+}
+
+fn extract_dir(vfs: &MiniFs, path: &Path, output: &Path) -> Result<(), Box<dyn Error>> {
+    std::fs::create_dir_all(output)?;
+    for e in get_entries(vfs, path)? {
+        let e_path = path.join(&e.name);
+        let e_output = output.join(&e.name);
+        match e.kind {
+            EntryKind::Dir => extract_dir(vfs, &e_path, &e_output)?,
+            EntryKind::File => extract_file(vfs, &e_path, &e_output)?,
+        }
+    }
+
+    Ok(())
+}
+
+fn extract_file(vfs: &MiniFs, path: &Path, output: &Path) -> Result<(), Box<dyn Error>> {
+    if let Some(parent) = output.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    std::fs::write(output, vfs.read_to_end(path)?)?;
+    eprintln!("{} -> {}", path.display(), output.display());
+    Ok(())
+}
+
+fn entry_kind(vfs: &MiniFs, path: &Path) -> io::Result<EntryKind> {
+    let (parent, name) = match (path.parent(), path.file_name()) {
+        (Some(parent), Some(name)) => (parent, name),
+        _ => return Ok(EntryKind::Dir),
+    };
+
+    get_entries(vfs, parent)?
+        .into_iter()
+        .find(|e| e.name.as_os_str() == name)
+        .map(|e| e.kind)
+        .ok_or(io::Error::from(io::ErrorKind::NotFound))
+}
+
+fn get_entries(vfs: &MiniFs, path: &Path) -> io::Result<Vec<Entry>> {
+    let mut entries = vfs.entries(path)?.collect::<io::Result<Vec<Entry>>>()?;
+    entries.sort_by(|a, b| match (a.kind, b.kind) {
+        (EntryKind::Dir, EntryKind::File) => Ordering::Less,
+        (EntryKind::File, EntryKind::Dir) => Ordering::Greater,
+        _ => a.name.cmp(&b.name),
+    });
+
+    Ok(entries)
+}
+
+fn extension(path: &Path) -> Option<String> {
+    path.extension()
+        .map(|e| e.to_string_lossy().to_ascii_lowercase())
+}
@@ -0,0 +1,75 @@
+mod commands;
+
+use opengb::{config::OpenGbConfig, cpk::create_vfs};
+use std::path::PathBuf;
+
+const USAGE: &str = "Usage: opengb-cli [--asset-path <path>] <command> [args]
+
+Commands:
+    ls [-r] <vfs_path>              List the entries under a vfs folder
+    cat <vfs_path>                  Write the file content to stdout
+    extract <vfs_path> <out_path>   Extract a file or a folder to the local disk
+    info <vfs_path>                 Show a summary of a file or a folder
+    dump-json <vfs_path>            Dump a sce/scn/nav/pol/cvd/mv3/ani file as Json
+    disasm <vfs_path>               Disassemble the procs in a sce file
+    asm <listing> <out_path>        Assemble a listing from the local disk into a sce file
+    gltf <vfs_path> <out_path>      Export a mv3 or pol file as a glb file
+    obj <vfs_path> <out_path>       Export a pol file as obj, with its mtl and png textures
+
+The asset path is the folder where PAL3.exe is. When it is not specified,
+it will be read from openpal3.toml or the OPENPAL3_ASSET_PATH env variable.";
+
+fn main() {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let asset_path = match take_option(&mut args, "--asset-path") {
+        Some(path) => path,
+        None => OpenGbConfig::load("openpal3", "OPENPAL3").asset_path,
+    };
+
+    if args.is_empty() {
+        exit_with_usage();
+    }
+
+    let vfs = match create_vfs(PathBuf::from(&asset_path)) {
+        Ok(vfs) => vfs,
+        Err(e) => {
+            eprintln!("opengb-cli: cannot open {}: {}", asset_path, e);
+            std::process::exit(1);
+        }
+    };
+    let command = args.remove(0);
+    let result = match (command.as_str(), args.as_slice()) {
+        ("ls", [flag, path]) if flag == "-r" => commands::ls(&vfs, path, true),
+        ("ls", [path]) => commands::ls(&vfs, path, false),
+        ("cat", [path]) => commands::cat(&vfs, path),
+        ("extract", [path, output]) => commands::extract(&vfs, path, output),
+        ("info", [path]) => commands::info(&vfs, path),
+        ("dump-json", [path]) => commands::dump_json(&vfs, path),
+        ("disasm", [path]) => commands::disasm(&vfs, path),
+        ("asm", [listing, output]) => commands::asm(listing, output),
+        ("gltf", [path, output]) => commands::gltf(&vfs, path, output),
+        ("obj", [path, output]) => commands::obj(&vfs, path, output),
+        _ => exit_with_usage(),
+    };
+
+    if let Err(e) = result {
+        eprintln!("opengb-cli: {}", e);
+        std::process::exit(1);
+    }
+}
+
+fn take_option(args: &mut Vec<String>, name: &str) -> Option<String> {
+    let index = args.iter().position(|a| a == name)?;
+    if index + 1 >= args.len() {
+        exit_with_usage();
+    }
+
+    let value = args.remove(index + 1);
+    args.remove(index);
+    Some(value)
+}
+
+fn exit_with_usage() -> ! {
+    eprintln!("{}", USAGE);
+    std::process::exit(2);
+}
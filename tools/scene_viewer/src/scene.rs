@@ -37,7 +37,7 @@ impl ScnScene {
     pub fn new(path: String, factory: Rc<dyn ComponentFactory>) -> Self {
         let local = LocalFs::new("E:\\CubeLibrary\\apps\\1000039\\basedata");
         let vfs = MiniFs::new_case_insensitive().mount("/", local);
-        let scn_file = scn_load_from_file(&vfs, &path);
+        let scn_file = scn_load_from_file(&vfs, &path).unwrap();
         println!("{:?}", scn_file);
         Self { path, scn_file, vfs, factory}
     }